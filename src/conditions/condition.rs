@@ -1,4 +1,10 @@
-use crate::errors::tperrors::Tperrors;
+use crate::{
+    errors::tperrors::Tperrors,
+    expressions::{
+        expression::{column_position, is_qualified_name, BinaryOperator, Expression, Row},
+        parser::ExpressionParser,
+    },
+};
 
 use super::value::Value;
 
@@ -20,490 +26,135 @@ impl Condition {
     /// a query with ```"Name = 'John'"``` will return true
     ///
     pub fn matches_condition(&self, conditions: &str) -> Result<bool, Tperrors> {
-        let tokens = self.preprocess_conditions(conditions);
-        let open_parenthesis_count = tokens.iter().filter(|x| *x == "(").count();
-        let close_parenthesis_count = tokens.iter().filter(|x| *x == ")").count();
-
-        if open_parenthesis_count != close_parenthesis_count {
-            return Err(Tperrors::Syntax("near ';'".to_string()));
-        }
-
-        let mut i = 0;
-        self.evaluate_expression(&tokens, &mut i)
+        let columns = self
+            .data
+            .iter()
+            .map(|(column, _)| column.to_string())
+            .collect::<Vec<String>>();
+        let values = self
+            .data
+            .iter()
+            .map(|(_, value)| value.clone())
+            .collect::<Vec<Value>>();
+
+        let expression = Self::compile(conditions, &columns)?;
+        expression.matches(&Row::new(&columns, &values))
     }
 
-    /// Recursion function to evaluate the expression
+    /// Parses the conditions of a query, so they can be checked against every row
     ///
-    /// This function will evaluate the expression and return the result
+    /// This must be done only once per query, and not once per row.
     ///
     /// # Arguments
     ///
-    /// * `tokens` - A vector of strings that contains the tokens of the expression
+    /// * `conditions` - The conditions as str, Example: ```Edad >= 45 AND Nombre = 'Juan'```
     ///
-    /// * `i` - A mutable reference to the index of the current token being evaluated
+    /// * `columns` - The columns of the table
     ///
-    /// Returns a Result with the boolean result of the expression
-    fn evaluate_expression(&self, tokens: &[String], i: &mut usize) -> Result<bool, Tperrors> {
-        let mut operator_stack: Vec<String> = vec![];
-        let mut result = true;
-        let mut negate_next = false;
-        while *i < tokens.len() {
-            let token = &tokens[*i];
-            match token.as_str() {
-                "NOT" => {
-                    negate_next = true; // Apply negation
-                    *i += 1;
-                }
-                "(" => {
-                    *i += 1; // Skip '('
-                    let sub_result = self.evaluate_expression(tokens, i)?;
-                    let final_result = if negate_next { !sub_result } else { sub_result };
-                    result =
-                        self.combine_with_operator(result, final_result, operator_stack.last());
-                    negate_next = false;
-                }
-                ")" => {
-                    *i += 1; // Skip ')'
-                    return Ok(result);
-                }
-                "AND" | "OR" => {
-                    operator_stack.push(token.to_string());
-                    *i += 1;
-                }
-                _ => {
-                    let condition_result = self.evaluate_condition(tokens, i, negate_next)?;
-
-                    result =
-                        self.combine_with_operator(result, condition_result, operator_stack.last());
-
-                    negate_next = false;
-                }
-            }
-        }
-        Ok(result)
-    }
-
-    /// Combine the result of two conditions with an operator (AND, OR)
-    fn combine_with_operator(&self, left: bool, right: bool, operator: Option<&String>) -> bool {
-        match operator.map(String::as_str) {
-            Some("AND") => left && right,
-            Some("OR") => left || right,
-            _ => right,
-        }
+    /// Returns the conditions as an expression, or an error if the syntax is wrong or a column
+    /// doesn't exist
+    pub fn compile(conditions: &str, columns: &[String]) -> Result<Expression, Tperrors> {
+        Self::compile_conditions(conditions, columns, true)
     }
 
-    /// Evaluate a single condition
+    /// Like compile, but every name must be a column (the shortcuts of resolve_names
+    /// are not used)
     ///
-    /// This function will evaluate a single condition and return the result
-    ///
-    /// # Arguments
-    ///
-    /// * `tokens` - A vector of strings that contains the tokens of the condition
-    ///
-    /// * `i` - A mutable reference to the index of the current token being evaluated
-    ///
-    /// * `negate` - A boolean that indicates if the condition should be negated
-    ///
-    /// Returns a Result with the boolean result of the condition
-    fn evaluate_condition(
-        &self,
-        tokens: &[String],
-        i: &mut usize,
-        negate: bool,
-    ) -> Result<bool, Tperrors> {
-        // *i + 2 >= tokens.len() || previously.
-        if ["AND", "OR", "NOT"].contains(&tokens[tokens.len() - 1].as_str()) {
-            return Err(Tperrors::Syntax("Condition incomplete".to_string()));
-        }
-        // maybe one of the tokens is without whitespaces
-        // we need to handle that posibility.
-        if tokens.iter().any(|token| {
-            (token.contains("=") || token.contains("<") || token.contains(">")) && token.len() > 2
-        }) {
-            let fixed_tokens = self.split_conditions(tokens)?;
-            return self.evaluate_expression(&fixed_tokens, i);
-        }
-        // lets implement a fix for scaped column or values
-        let column = self.return_fixed_column(tokens, i)?;
-        *i += 1; // we move the cursor to the operator
-        let operator = tokens[*i].to_string().replace('\n', "");
-        *i += 1; // we move the cursor to the value
-        let value = self.return_fixed_value(tokens, i)?;
-
-        if column.parse::<i64>().is_ok() && value.parse::<i64>().is_ok() {
-            // we are evaluating constants here...
-            let column = match column.parse::<i64>() {
-                Ok(val) => val,
-                Err(_) => {
-                    return Err(Tperrors::Syntax(
-                        "Detected a constant value as column, but error converting it".to_string(),
-                    ))
-                }
-            };
-
-            let value = match value.parse::<i64>() {
-                Ok(val) => val,
-                Err(_) => {
-                    return Err(Tperrors::Syntax(
-                        "Detected a constant value as value, but error converting it".to_string(),
-                    ))
-                }
-            };
-
-            let evaluation_check =
-                self.resolve_constant_evaluation(column, operator.as_str(), value);
-
-            *i += 1; // we need to go to the next check
-            return Ok(if negate {
-                !evaluation_check
-            } else {
-                evaluation_check
-            });
-        }
-
-        let found_column = self.data.iter().find(|(col, _)| col == column.as_str());
-        let found_value_or_column = self.data.iter().find(|(col, _)| col == value.as_str());
-
-        *i += 1;
-
-        if let Some((_, actual_value)) = found_column {
-            let evaluation_check = if let Some((_, column_value)) = found_value_or_column {
-                self.resolve_column_evaluation(actual_value, operator.as_str(), column_value)
-            } else {
-                self.resolve_evaluation(actual_value, operator.as_str(), value.as_str())
-            };
-
-            Ok(if negate {
-                !evaluation_check
-            } else {
-                evaluation_check
-            })
-        } else {
-            Err(Tperrors::Generic(format!(
-                "Error with column {}, maybe spaces is required?",
-                column
-            )))
-        }
-    }
-
-    /// Preprocess the conditions to split them into tokens
-    ///
-    /// This function will preprocess the conditions and split them into tokens
-    ///
-    /// # Arguments
-    ///
-    /// * `conditions` - A string that contains the conditions
-    ///
-    /// Returns a vector of strings with the tokens of the conditions
-    fn preprocess_conditions(&self, conditions: &str) -> Vec<String> {
-        let mut result = Vec::new();
-        let mut buffer = String::new();
-
-        for ch in conditions.chars() {
-            match ch {
-                '(' | ')' => {
-                    if !buffer.is_empty() {
-                        result.push(buffer); // Move buffer into result
-                        buffer = String::new(); // Reinitialize buffer
-                    }
-                    result.push(ch.to_string());
-                }
-                ' ' => {
-                    if !buffer.is_empty() {
-                        result.push(buffer); // Move buffer into result
-                        buffer = String::new(); // Reinitialize buffer
-                    }
-                }
-                _ => buffer.push(ch),
-            }
-        }
-
-        // After the loop, push any remaining content in the buffer
-        if !buffer.is_empty() {
-            result.push(buffer);
-        }
-
-        result
-    }
-
-    /// Private function that help to check if conditions are met.
-    fn resolve_evaluation(&self, actual_value: &Value, operator: &str, value: &str) -> bool {
-        match (actual_value, operator) {
-            (Value::String(actual), "=") => actual == value.trim_matches('\''),
-            (Value::String(actual), "!=") => actual != value.trim_matches('\''),
-            (Value::Integer(actual), "=") => {
-                if let Ok(val) = value.parse::<i64>() {
-                    actual == &val
-                } else {
-                    false
-                }
-            }
-            (Value::Integer(actual), "!=") => {
-                if let Ok(val) = value.parse::<i64>() {
-                    actual != &val
-                } else {
-                    false
-                }
-            }
-            (Value::Integer(actual), ">") => {
-                if let Ok(val) = value.parse::<i64>() {
-                    actual > &val
-                } else {
-                    false
-                }
-            }
-            (Value::Integer(actual), "<") => {
-                if let Ok(val) = value.parse::<i64>() {
-                    actual < &val
-                } else {
-                    false
-                }
-            }
-            (Value::Integer(actual), ">=") => {
-                if let Ok(val) = value.parse::<i64>() {
-                    actual >= &val
-                } else {
-                    false
-                }
-            }
-            (Value::Integer(actual), "<=") => {
-                if let Ok(val) = value.parse::<i64>() {
-                    actual <= &val
-                } else {
-                    false
-                }
-            }
-            _ => false,
-        }
+    /// Used on the ON of a join or a MERGE, where a misspelled column would otherwise
+    /// be compared as a value and silently match nothing
+    pub fn compile_strict(conditions: &str, columns: &[String]) -> Result<Expression, Tperrors> {
+        Self::compile_conditions(conditions, columns, false)
     }
 
-    /// Private function that help to check if conditions are met between columns
-    fn resolve_column_evaluation(&self, left: &Value, operator: &str, right: &Value) -> bool {
-        match (left, right) {
-            (Value::Integer(left_val), Value::Integer(right_val)) => match operator {
-                "=" => left_val == right_val,
-                "!=" => left_val != right_val,
-                ">" => left_val > right_val,
-                "<" => left_val < right_val,
-                ">=" => left_val >= right_val,
-                "<=" => left_val <= right_val,
-                _ => false,
-            },
-            (Value::String(left_val), Value::String(right_val)) => match operator {
-                "=" | "==" => left_val == right_val,
-                "!=" => left_val != right_val,
-                _ => false, // String comparisons like ">" are not usually supported
-            },
-            _ => false,
+    fn compile_conditions(
+        conditions: &str,
+        columns: &[String],
+        shortcuts: bool,
+    ) -> Result<Expression, Tperrors> {
+        let expression = ExpressionParser::parse(conditions)?;
+        if expression.contains_aggregate() {
+            return Err(Tperrors::Syntax(
+                "Aggregate functions can't be used on conditions".to_string(),
+            ));
         }
-    }
-    /// Private function that help to check if conditions are met between constants
-    ///
-    /// This function will check if the conditions are met between constants
-    ///
-    /// # Arguments
-    ///
-    /// * `left` - The left constant value
-    ///
-    /// * `operator` - The operator to be used in the comparison
-    ///
-    /// * `right` - The right constant value
-    ///
-    /// Returns a boolean indicating if the conditions are met
-    fn resolve_constant_evaluation(&self, left: i64, operator: &str, right: i64) -> bool {
-        // we reuse the same function, but we send the values as constants
-        self.resolve_column_evaluation(&Value::Integer(left), operator, &Value::Integer(right))
-    }
-
-    /// Split the conditions into tokens
-    ///
-    /// This function will split the conditions into proper tokens if operators are not separated by spaces
-    ///  
-    /// # Arguments
-    ///
-    /// * `input` - A vector of strings that contains the conditions
-    ///
-    /// Returns a Result with a vector of strings containing the tokens of the conditions
-    ///
-    /// If an invalid operator is found, a Syntax error is returned
-    fn split_conditions(&self, input: &[String]) -> Result<Vec<String>, Tperrors> {
-        let mut result = Vec::new();
-
-        for element in input {
-            //for i in 0..input.len() {
-            // Check for combined conditions (e.g., "Edad>=45")
-            //let element = &input[i];
-            // we avoid => or <=
-            if element.contains("=>") || element.contains("=<") {
-                return Err(Tperrors::Syntax(
-                    "Invalid operator, use >= or <= (SQL: Near '<')".to_string(),
-                ));
-            }
-
-            if element.contains(">=") {
-                let parts: Vec<&str> = element.split(">=").collect();
-                result.push(parts[0].to_string());
-                result.push(">=".to_string());
-                result.push(parts[1].to_string());
-            } else if element.contains("<=") {
-                let parts: Vec<&str> = element.split("<=").collect();
-                result.push(parts[0].to_string());
-                result.push("<=".to_string());
-                result.push(parts[1].to_string());
-            } else if element.contains("!=") {
-                let parts: Vec<&str> = element.split("!=").collect();
-                result.push(parts[0].to_string());
-                result.push("!=".to_string());
-                result.push(parts[1].to_string());
-            } else if element.contains('=') {
-                let parts: Vec<&str> = element.split('=').collect();
-                if parts.len() == 2 {
-                    result.push(parts[0].to_string());
-                    result.push("=".to_string());
-                    result.push(parts[1].to_string());
-                }
-            } else if element.contains("<") {
-                let parts: Vec<&str> = element.split("<").collect();
-                result.push(parts[0].to_string());
-                result.push("<".to_string());
-                result.push(parts[1].to_string());
-            } else if element.contains(">") {
-                let parts: Vec<&str> = element.split(">").collect();
-                result.push(parts[0].to_string());
-                result.push(">".to_string());
-                result.push(parts[1].to_string());
-            } else {
-                // If the element is a logical operator or standalone column
-                result.push(element.to_string());
-            }
+        if expression.contains_window() {
+            return Err(Tperrors::Syntax(
+                "Window functions can't be used on conditions".to_string(),
+            ));
         }
-
-        Ok(result
-            .into_iter()
-            .filter(|element| !element.is_empty())
-            .map(|element| element.trim().to_string())
-            .collect::<Vec<String>>())
+        let expression = match shortcuts {
+            true => Self::resolve_names(expression, columns),
+            false => expression,
+        };
+        expression.validate_columns(columns)?;
+        Ok(expression)
     }
 
-    /// Function that will return the fixed column
-    ///
-    /// This function will return the fixed column
-    ///
-    /// # Arguments
-    ///
-    /// * `tokens` - A vector of strings that contains the tokens of the condition
-    ///
-    /// * `i` - A mutable reference to the index of the current token being evaluated
-    ///
-    /// Returns a Result with the fixed column
-    fn return_fixed_column(&self, tokens: &[String], i: &mut usize) -> Result<String, Tperrors> {
-        let result: Result<String, Tperrors> =
-            match tokens[*i].contains('\'') || tokens[*i].contains('\"') {
-                true => {
-                    let find_operator_pos = match tokens.iter().position(|x| {
-                        x.contains("=") || x.contains("<") || x.contains(">") || x.contains("!")
-                    }) {
-                        Some(pos) => pos,
-                        None => return Err(Tperrors::Syntax("Comparator error".to_string())),
+    /// Keeps the shortcuts that conditions always allowed on comparisons
+    ///
+    /// * A name that isn't a column on the right side is a value: ```Nombre = Luis```,
+    ///   but never a qualified one (```c.Idd``` is a misspelled column, not a value)
+    ///
+    /// * A quoted column on the left side is a column: ```'Correo electronico' = 'a@b.com'```
+    fn resolve_names(expression: Expression, columns: &[String]) -> Expression {
+        match expression {
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                let left = Self::resolve_names(*left, columns);
+                let right = Self::resolve_names(*right, columns);
+
+                if !Self::is_comparison(&operator) {
+                    return Expression::Binary {
+                        left: Box::new(left),
+                        operator,
+                        right: Box::new(right),
                     };
-
-                    // the last column scaped SHOULD be scaped, else we throw error syntax
-                    match tokens[find_operator_pos - 1].contains("'")
-                        || tokens[find_operator_pos - 1].contains("\"")
-                    {
-                        true => {
-                            let fixed_column = tokens[0..find_operator_pos].join(" ");
-                            // we make *i jump to the operator
-                            *i += find_operator_pos - 1;
-                            return Ok(fixed_column
-                                .replace("'", "")
-                                .replace("\"", "")
-                                .replace('\n', "")); //fix for new line
-                        }
-                        false => return Err(Tperrors::Syntax("Missing scaped column".to_string())),
-                    }
                 }
-                _ => {
-                    //*i += 1;
-                    Ok(tokens[*i].to_string().replace('\n', ""))
-                }
-            };
-        result
-    }
 
-    /// Function that will return the fixed value
-    ///
-    /// This function will return the fixed value
-    ///
-    /// # Arguments
-    ///
-    /// * `tokens` - A vector of strings that contains the tokens of the condition
-    ///
-    /// * `i` - A mutable reference to the index of the current token being evaluated
-    ///
-    /// Returns a Result with the fixed value
-    fn return_fixed_value(&self, tokens: &[String], i: &mut usize) -> Result<String, Tperrors> {
-        let result: Result<String, Tperrors> = match tokens[*i].contains('\'')
-            || tokens[*i].contains('\"')
-        {
-            true => {
-                let find_pos_operator = tokens[*i..]
-                    .iter()
-                    .position(|x| x.contains("AND") || x.contains("OR"));
-
-                match find_pos_operator {
-                    None | Some(0) => {
-                        // this means operator isnt found, so it MUST be the unique and last element of the token
-                        let fixed_value = tokens[*i..].join(" ");
-                        let distance = (tokens.len() - *i) - 1;
-                        let fixed_value = match fixed_value.rfind('\'') {
-                            // Idea here is to find the last scape and trim the string to that point.
-                            Some(pos) => {
-                                if pos == 0 {
-                                    // rightest value is at the start of the string?
-                                    // this mean isn't scaped.
-                                    return Err(Tperrors::Syntax(
-                                        "Missing scaped value".to_string(),
-                                    ));
-                                }
-                                fixed_value[1..pos].to_string()
-                            }
-                            None => fixed_value,
-                        };
-                        *i += distance; // we need to move the cursor to the distance required
-                        Ok(fixed_value
-                            .replace("'", "")
-                            .replace("\"", "")
-                            .replace('\n', ""))
+                let left = match left {
+                    Expression::Literal(Value::String(name))
+                        if column_position(columns, &name).is_some() =>
+                    {
+                        Expression::Column(name)
                     }
-                    Some(pos) => {
-                        // If we found this, it means that there we foun a operator.. we need to check if the previous
-                        // value MUST contain a scaped value, if not, we throw an error.
-                        if tokens[*i + pos - 1].contains('\'')
-                            || tokens[*i + pos - 1].contains('\"')
-                        {
-                            let fixed_value = tokens[*i..*i + pos].join(" ");
-
-                            *i += pos - 1;
-                            Ok(fixed_value
-                                .replace("'", "")
-                                .replace("\"", "")
-                                .replace('\n', ""))
-                        } else if tokens[*i + pos - 1] == ")" {
-                            // maybe the condition is nested?
-                            *i += pos - 1; // we iterate to the next operator
-                            Ok(tokens[*i - 1].to_string().replace('\n', "")) //return the last string we found
-                        } else {
-                            return Err(Tperrors::Syntax(
-                                "Missing scaped value, something is wrong".to_string(),
-                            ));
-                        }
+                    other => other,
+                };
+                let right = match right {
+                    Expression::Column(name)
+                        if !is_qualified_name(&name)
+                            && column_position(columns, &name).is_none() =>
+                    {
+                        Expression::Literal(Value::String(name))
                     }
+                    other => other,
+                };
+                Expression::Binary {
+                    left: Box::new(left),
+                    operator,
+                    right: Box::new(right),
                 }
             }
-            false => Ok(tokens[*i].to_string().replace('\n', "")),
-        };
-        result
+            Expression::Unary { operator, operand } => Expression::Unary {
+                operator,
+                operand: Box::new(Self::resolve_names(*operand, columns)),
+            },
+            other => other,
+        }
+    }
+
+    fn is_comparison(operator: &BinaryOperator) -> bool {
+        matches!(
+            operator,
+            BinaryOperator::Equal
+                | BinaryOperator::NotEqual
+                | BinaryOperator::Less
+                | BinaryOperator::Greater
+                | BinaryOperator::LessOrEqual
+                | BinaryOperator::GreaterOrEqual
+        )
     }
 }
 
@@ -526,7 +177,7 @@ mod test {
         ];
 
         for str_condition in str_conditions {
-            assert_eq!(conditions.matches_condition(str_condition).unwrap(), true);
+            assert!(conditions.matches_condition(str_condition).unwrap());
         }
     }
 
@@ -542,7 +193,7 @@ mod test {
         let str_conditions = vec!["name = 'John'", "age = 20 OR name = 'John'"];
 
         for str_condition in str_conditions {
-            assert_eq!(conditions.matches_condition(str_condition).unwrap(), true);
+            assert!(conditions.matches_condition(str_condition).unwrap());
         }
     }
 
@@ -558,7 +209,7 @@ mod test {
         ];
 
         for str_condition in str_conditions {
-            assert_ne!(conditions.matches_condition(str_condition).unwrap(), false);
+            assert!(conditions.matches_condition(str_condition).unwrap());
         }
     }
 
@@ -576,7 +227,7 @@ mod test {
         ];
 
         for str_condition in str_conditions {
-            assert_eq!(conditions.matches_condition(str_condition).unwrap(), true);
+            assert!(conditions.matches_condition(str_condition).unwrap());
         }
     }
     #[test]
@@ -588,11 +239,11 @@ mod test {
 
         let condition = "'Correo Electronico'='test@fi.uba.ar'";
 
-        assert_eq!(conditions.matches_condition(condition).unwrap(), true);
+        assert!(conditions.matches_condition(condition).unwrap());
 
         let condition = "'Correo Electronico'=test@fi.uba.ar";
 
-        assert_eq!(conditions.matches_condition(condition).unwrap(), true);
+        assert!(conditions.matches_condition(condition).unwrap());
     }
     #[test]
     fn condition_contains_spaces_missing_quote_returns_err() {
@@ -623,27 +274,27 @@ mod test {
 
         let condition = "20 = 20";
 
-        assert_eq!(conditions.matches_condition(condition).unwrap(), true);
+        assert!(conditions.matches_condition(condition).unwrap());
 
         let condition = "20!=20";
 
-        assert_eq!(conditions.matches_condition(condition).unwrap(), false);
+        assert!(!conditions.matches_condition(condition).unwrap());
 
         let condition = "20 > 20";
 
-        assert_eq!(conditions.matches_condition(condition).unwrap(), false);
+        assert!(!conditions.matches_condition(condition).unwrap());
 
         let condition = "20<20";
 
-        assert_eq!(conditions.matches_condition(condition).unwrap(), false);
+        assert!(!conditions.matches_condition(condition).unwrap());
 
         let condition = "20>=20";
 
-        assert_eq!(conditions.matches_condition(condition).unwrap(), true);
+        assert!(conditions.matches_condition(condition).unwrap());
 
         let condition = "20 <= 20";
 
-        assert_eq!(conditions.matches_condition(condition).unwrap(), true);
+        assert!(conditions.matches_condition(condition).unwrap());
     }
 
     #[test]
@@ -696,4 +347,28 @@ mod test {
 
         assert!(conditions.matches_condition(condition).is_ok());
     }
+
+    #[test]
+    fn conditions_like_and_ilike_matches() {
+        let conditions = Condition::new(Vec::from([
+            (
+                "Correo electronico".to_string(),
+                Value::String("csanchez@gmail.com".to_string()),
+            ),
+            ("Apellido".to_string(), Value::String("Sánchez".to_string())),
+        ]));
+
+        assert!(conditions
+            .matches_condition("Correo electronico LIKE '%@gmail.com'")
+            .unwrap());
+        assert!(!conditions
+            .matches_condition("Correo electronico NOT LIKE '%@gmail.com'")
+            .unwrap());
+        assert!(conditions
+            .matches_condition("Apellido ILIKE 's_nchez'")
+            .unwrap());
+        assert!(!conditions
+            .matches_condition("Apellido LIKE 's_nchez'")
+            .unwrap());
+    }
 }
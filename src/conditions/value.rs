@@ -1,6 +1,179 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Formatter, Result},
+};
+
 /// Representation of a value in a condition.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Integer(i64),
+    Real(f64),
     String(String),
+    Boolean(bool),
+    Null,
+}
+
+impl Value {
+    /// Converts a field readed from the csv into a Value
+    ///
+    /// Empty fields are NULL, numbers are only converted if writing them back
+    /// gives the same text (so ```007``` stays as a string and it's printed as is)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tp_individual::conditions::value::Value;
+    ///
+    /// assert_eq!(Value::from_field("32"), Value::Integer(32));
+    /// assert_eq!(Value::from_field("Juan"), Value::String("Juan".to_string()));
+    /// assert_eq!(Value::from_field(""), Value::Null);
+    /// ```
+    pub fn from_field(field: &str) -> Value {
+        let field = field.trim();
+
+        if field.is_empty() {
+            return Value::Null;
+        }
+        if let Ok(v) = field.parse::<i64>() {
+            if v.to_string() == field {
+                return Value::Integer(v);
+            }
+        }
+        if let Ok(v) = field.parse::<f64>() {
+            if v.is_finite() && v.to_string() == field {
+                return Value::Real(v);
+            }
+        }
+        Value::String(field.to_string())
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Returns the value as a number, if it can be seen as one
+    ///
+    /// Strings are parsed, so ```'32'``` can be compared against ```32```
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(v) => Some(*v as f64),
+            Value::Real(v) => Some(*v),
+            Value::String(s) => match s.trim().parse::<f64>() {
+                Ok(v) if v.is_finite() => Some(v),
+                _ => None,
+            },
+            Value::Boolean(_) | Value::Null => None,
+        }
+    }
+
+    /// Returns a key to store the value on a hash set (to group or deduplicate values)
+    ///
+    /// Like compare, numbers are equal no matter how they are written (```7``` and ```7.0```)
+    /// and texts only if they are the same text (```'007'``` and ```'7'``` are different).
+    /// NULL has no key.
+    ///
+    /// A number and a text that looks like it (```7``` and ```'007'```) are equal for
+    /// compare but get different keys, to look for equal values use match_key.
+    pub fn hash_key(&self) -> Option<String> {
+        match self {
+            Value::Null => None,
+            Value::Integer(_) | Value::Real(_) => self.as_f64().map(|n| format!("n{}", n)),
+            Value::String(_) | Value::Boolean(_) => Some(format!("s{}", self)),
+        }
+    }
+
+    /// Returns a key to look for the values that compare as equal to this one
+    ///
+    /// Every text that looks like a number gets the key of the number, so two values
+    /// that compare as equal always have the same key, but values with the same key
+    /// may be different (```'007'``` and ```'7'```): a match must be checked with compare.
+    /// NULL has no key.
+    pub fn match_key(&self) -> Option<String> {
+        match self {
+            Value::Null => None,
+            _ => match self.as_f64() {
+                Some(number) => Some(format!("n{}", number)),
+                None => Some(format!("s{}", self)),
+            },
+        }
+    }
+
+    /// Returns true if both values compare as equal (never with NULL)
+    pub fn equals(&self, other: &Value) -> bool {
+        matches!(self.compare(other), Some(Ordering::Equal))
+    }
+
+    /// Compares two values
+    ///
+    /// Returns None if any of them is NULL (SQL comparisons against NULL are unknown)
+    ///
+    /// Numbers are compared numerically even if one of them is written as text,
+    /// anything else is compared as text.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => None,
+            (Value::Integer(left), Value::Integer(right)) => Some(left.cmp(right)),
+            (Value::Boolean(left), Value::Boolean(right)) => Some(left.cmp(right)),
+            (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+            _ => match (self.as_f64(), other.as_f64()) {
+                (Some(left), Some(right)) => left.partial_cmp(&right),
+                _ => Some(self.to_string().cmp(&other.to_string())),
+            },
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Value::Integer(v) => write!(f, "{}", v),
+            Value::Real(v) => write!(f, "{}", v),
+            Value::String(v) => write!(f, "{}", v),
+            Value::Boolean(v) => write!(f, "{}", v),
+            Value::Null => write!(f, ""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_numbers_written_as_text() {
+        let left = Value::Integer(32);
+        let right = Value::String("32".to_string());
+
+        assert_eq!(left.compare(&right), Some(Ordering::Equal));
+        assert_eq!(
+            Value::Integer(100).compare(&Value::Integer(20)),
+            Some(Ordering::Greater)
+        );
+    }
+
+    #[test]
+    fn hash_key_follows_compare() {
+        let texts = ["007", "7", "7.0"].map(|text| Value::String(text.to_string()));
+        let number = Value::Integer(7);
+
+        // texts are only equal to the same text, even if they look like a number
+        assert!(texts[0].equals(&texts[0]));
+        assert!(!texts[0].equals(&texts[1]));
+        assert_ne!(texts[0].hash_key(), texts[1].hash_key());
+        assert_ne!(texts[1].hash_key(), texts[2].hash_key());
+        assert_eq!(number.hash_key(), Value::Real(7.0).hash_key());
+
+        // against a number they are compared numerically, the match key finds them
+        for text in &texts {
+            assert!(number.equals(text));
+            assert_eq!(number.match_key(), text.match_key());
+        }
+        assert_eq!(Value::Null.hash_key(), None);
+    }
+
+    #[test]
+    fn compare_against_null_is_unknown() {
+        assert_eq!(Value::Integer(1).compare(&Value::Null), None);
+        assert_eq!(Value::Null.compare(&Value::Null), None);
+    }
 }
@@ -0,0 +1,89 @@
+use std::{fs::File, rc::Rc, time::Instant};
+
+use crate::{
+    errors::tperrors::Tperrors,
+    expressions::subquery::SubqueryRunner,
+    extractors::{extractor::Extractor, sqlcommand::SQLCommand},
+    handler_tables::{folder_tables::FolderTables, table::Table},
+};
+
+use super::{insert::Insert, select::SelectRunner, summary::ExecutionSummary};
+
+/// Struct to handle the CREATE TABLE ... AS SELECT query.
+///
+/// The new table has the columns of the SELECT (with their names or aliases) and
+/// its rows are inserted like on an INSERT INTO ... SELECT.
+pub struct CreateTable;
+
+impl Default for CreateTable {
+    fn default() -> Self {
+        CreateTable::new()
+    }
+}
+
+impl CreateTable {
+    pub fn new() -> CreateTable {
+        CreateTable
+    }
+
+    /// A valid CREATE TABLE query contains CREATE TABLE and AS, and ends with ;
+    pub fn is_valid_query(&self, query: &str) -> bool {
+        let query = query.trim();
+
+        if query.starts_with("CREATE TABLE") && query.contains(" AS ") {
+            return query.ends_with(';');
+        }
+        false
+    }
+
+    /// Executes a CREATE TABLE ... AS SELECT query, writing the table on the folder
+    ///
+    /// If the rows can't be written, the table is removed, so it's never left half written
+    ///
+    /// Returns the summary of the query, like the one of a SELECT with the amount of rows written
+    pub fn execute(
+        &self,
+        folder_tables: &mut FolderTables,
+        consult: &str,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let started = Instant::now();
+        let (name, query) = Extractor.parser_create_table(consult)?;
+
+        let runner = SelectRunner::new(Rc::new(folder_tables.clone()));
+        let (header, rows) = runner.run(&query, None)?;
+        let table_path = folder_tables.create_table(&name, &header)?;
+
+        let result = match Table::<File>::new(table_path.clone()) {
+            Ok(mut table) => {
+                Insert.execute_insert_rows(&mut table, Vec::new(), rows, header.len(), None, None)
+            }
+            Err(e) => Err(Tperrors::Table(e.to_string())),
+        };
+        match result {
+            Ok(summary) => Ok(ExecutionSummary::new(
+                SQLCommand::Select,
+                summary.rows,
+                started,
+            )),
+            Err(e) => {
+                let _ = std::fs::remove_file(&table_path);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_query() {
+        let create_table = CreateTable;
+
+        assert!(create_table.is_valid_query("CREATE TABLE viejos AS SELECT * FROM clientes;"));
+        assert!(!create_table.is_valid_query("CREATE TABLE viejos AS SELECT * FROM clientes"));
+        assert!(!create_table.is_valid_query("CREATE TABLE viejos (Id, Nombre);"));
+        assert!(!create_table.is_valid_query("CREATE VIEW viejos AS SELECT * FROM clientes;"));
+    }
+}
@@ -1,7 +1,12 @@
 use std::io::{BufReader, Cursor, Read, Seek};
+use std::time::Instant;
 
+use crate::conditions::value::Value;
 use crate::errors::{fileerrors::FileErrors, tperrors::Tperrors};
-use crate::handler_tables::table::Table;
+use crate::extractors::sqlcommand::SQLCommand;
+use crate::handler_tables::{returning::Returning, row_source::RowSource, table::Table};
+
+use super::summary::ExecutionSummary;
 
 /// Struct to handle the DELETE query.
 pub struct Delete;
@@ -29,17 +34,44 @@ impl Delete {
         false
     }
     /// Execute the delete query
+    ///
+    /// Returns the summary of the query, with the amount of deleted rows, and the rows
+    /// of the RETURNING clause (computed with the deleted values) if it's given
     pub fn execute_delete<R: Read + Seek>(
         &self,
         table: &mut Table<R>,
         conditions: Option<&str>,
-    ) -> Result<(), Tperrors> {
+        returning: Option<&Returning>,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let started = Instant::now();
+        let columns = table.columns()?;
+        // the clause is checked before any row is deleted
+        if let Some(returning) = returning {
+            returning.validate(&columns)?;
+        }
+        let deleted_rows = self.delete_rows(table, conditions)?;
+        let summary = ExecutionSummary::new(SQLCommand::Delete, deleted_rows.len(), started);
+        match returning {
+            Some(returning) => {
+                let (header, rows) = returning.resolve(&columns, &deleted_rows)?;
+                Ok(summary.with_returning(header, rows))
+            }
+            None => Ok(summary),
+        }
+    }
+
+    /// Deletes the rows that meet the conditions, returning their values
+    fn delete_rows<R: Read + Seek>(
+        &self,
+        table: &mut Table<R>,
+        conditions: Option<&str>,
+    ) -> Result<Vec<Vec<Value>>, Tperrors> {
         let resolve = table.resolve_delete_for_file(conditions);
         match resolve {
-            Ok(temp_file_dir) => {
+            Ok((temp_file_dir, deleted_rows)) => {
                 match table.replace_original_with(temp_file_dir) {
                     Ok(_) => {
-                        Ok(()) // everything done propertly.
+                        Ok(deleted_rows) // everything done propertly.
                     }
                     Err(e) => match e {
                         FileErrors::DeletionFailed => {
@@ -77,9 +109,9 @@ mod tests {
     fn test_is_valid_query() {
         let delete = Delete;
         let query = "DELETE FROM table;";
-        assert_eq!(delete.is_valid_query(query), true);
+        assert!(delete.is_valid_query(query));
 
         let query = "DELETE FROM table";
-        assert_eq!(delete.is_valid_query(query), false);
+        assert!(!delete.is_valid_query(query));
     }
 }
@@ -1,7 +1,16 @@
 use std::io::{Read, Seek};
+use std::time::Instant;
 
+use crate::conditions::value::Value;
+use crate::errors::fileerrors::FileErrors;
 use crate::errors::tperrors::Tperrors;
-use crate::handler_tables::table::*;
+use crate::expressions::subquery::SubqueryRunner;
+use crate::extractors::sqlcommand::SQLCommand;
+use crate::handler_tables::{
+    on_conflict::OnConflict, returning::Returning, row_source::RowSource, table::*,
+};
+
+use super::summary::ExecutionSummary;
 
 /// Struct to handle the INSERT query.
 pub struct Insert;
@@ -17,13 +26,16 @@ impl Insert {
         Insert
     }
 
-    /// A valid INSERT query contains INSERT INTO and VALUES AND ends with ;
+    /// A valid INSERT query contains INSERT INTO and VALUES (or the SELECT that gives
+    /// the rows) AND ends with ;
     ///
     /// if the query is valid, it will return true
     pub fn is_valid_query(&self, query: &str) -> bool {
         let query = query.trim();
 
-        if query.starts_with("INSERT INTO") && query.contains("VALUES") {
+        if query.starts_with("INSERT INTO")
+            && (query.contains("VALUES") || query.contains("SELECT"))
+        {
             match query.chars().last() {
                 Some(';') => return true,
                 _ => return false,
@@ -33,31 +45,146 @@ impl Insert {
     }
 
     /// Execute the insert query
+    ///
+    /// With ON CONFLICT, the rows whose key is already on the table are skipped (DO NOTHING)
+    /// or update the row of the table (DO UPDATE), every row is applied on a single
+    /// rewrite of the file.
+    ///
+    /// Returns the summary of the query, with the amount of inserted (and updated) rows,
+    /// and the rows of the RETURNING clause if it's given
     pub fn execute_insert<R: Read + Seek>(
         &self,
         table: &mut Table<R>,
         columns: Vec<String>,
         values: Vec<Vec<String>>,
-    ) -> Result<(), Tperrors> {
-        let resolve = table.resolve_insert(columns, values);
-
-        match resolve {
-            Ok(lines) => {
-                for line in lines {
-                    let line = line.join(",");
-                    match table.insert_line_to_csv(line) {
-                        Ok(_) => {}
-                        Err(_) => {
-                            return Err(Tperrors::Generic("Error while inserting line".to_string()))
-                        }
-                    }
-                }
-                Ok(())
+        on_conflict: Option<&OnConflict>,
+        returning: Option<&Returning>,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let started = Instant::now();
+        let table_columns = Self::columns_for(table, returning)?;
+        let lines = table.resolve_insert(columns, values)?;
+        let changed_rows = Self::apply_lines(table, lines, on_conflict)?;
+        Self::summary_of(&table_columns, changed_rows, returning, started)
+    }
+
+    /// Execute an INSERT INTO ... SELECT query, the rows of the SELECT are inserted
+    ///
+    /// The SELECT must return one column for each column to insert, and its values must
+    /// have the types of the columns. Nothing is inserted if any row is invalid.
+    pub fn execute_insert_select<R: Read + Seek>(
+        &self,
+        table: &mut Table<R>,
+        columns: Vec<String>,
+        query: &str,
+        runner: &dyn SubqueryRunner,
+        on_conflict: Option<&OnConflict>,
+        returning: Option<&Returning>,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let (header, rows) = runner.run(query, None)?;
+        self.execute_insert_rows(table, columns, rows, header.len(), on_conflict, returning)
+    }
+
+    /// Execute the insert of rows already computed (each one with `width` values)
+    pub fn execute_insert_rows<R: Read + Seek>(
+        &self,
+        table: &mut Table<R>,
+        columns: Vec<String>,
+        rows: Vec<Vec<Value>>,
+        width: usize,
+        on_conflict: Option<&OnConflict>,
+        returning: Option<&Returning>,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let started = Instant::now();
+        let table_columns = Self::columns_for(table, returning)?;
+        let lines = table.resolve_insert_rows(&columns, rows, width)?;
+        let changed_rows = Self::apply_lines(table, lines, on_conflict)?;
+        Self::summary_of(&table_columns, changed_rows, returning, started)
+    }
+
+    /// The columns of the table, checking the RETURNING clause against them
+    /// before anything is written
+    fn columns_for<R: Read + Seek>(
+        table: &mut Table<R>,
+        returning: Option<&Returning>,
+    ) -> Result<Vec<String>, Tperrors> {
+        let table_columns = table.columns()?;
+        if let Some(returning) = returning {
+            returning.validate(&table_columns)?;
+        }
+        Ok(table_columns)
+    }
+
+    /// Writes the lines on the table, appending them or with its ON CONFLICT clause
+    ///
+    /// Returns the values of the inserted (and updated) rows
+    fn apply_lines<R: Read + Seek>(
+        table: &mut Table<R>,
+        lines: Vec<Vec<String>>,
+        on_conflict: Option<&OnConflict>,
+    ) -> Result<Vec<Vec<Value>>, Tperrors> {
+        match on_conflict {
+            Some(on_conflict) => Self::upsert_lines(table, lines, on_conflict),
+            None => Self::write_lines(table, lines),
+        }
+    }
+
+    /// Rewrites the table with the lines inserted (or the rows they conflict with updated)
+    ///
+    /// Returns the values of the updated and inserted rows
+    fn upsert_lines<R: Read + Seek>(
+        table: &mut Table<R>,
+        lines: Vec<Vec<String>>,
+        on_conflict: &OnConflict,
+    ) -> Result<Vec<Vec<Value>>, Tperrors> {
+        let (temporal_file, changed_rows) = table.resolve_upsert_for_file(lines, on_conflict)?;
+        match table.replace_original_with(temporal_file) {
+            Ok(_) => Ok(changed_rows),
+            Err(e) => match e {
+                FileErrors::DeletionFailed => Err(Tperrors::Generic("Deletion failed".to_string())),
+                FileErrors::InvalidFile => Err(Tperrors::Generic(
+                    "Error while inserting on the file".to_string(),
+                )),
+            },
+        }
+    }
+
+    /// The summary of an insert, with the rows of its RETURNING clause
+    fn summary_of(
+        columns: &[String],
+        changed_rows: Vec<Vec<Value>>,
+        returning: Option<&Returning>,
+        started: Instant,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let summary = ExecutionSummary::new(SQLCommand::Insert, changed_rows.len(), started);
+        match returning {
+            Some(returning) => {
+                let (header, rows) = returning.resolve(columns, &changed_rows)?;
+                Ok(summary.with_returning(header, rows))
             }
-            Err(e) => Err(e),
+            None => Ok(summary),
         }
     }
 
+    /// Appends the lines at the end of the file of the table
+    ///
+    /// Returns the values of every line, as they are read from the file
+    fn write_lines<R: Read + Seek>(
+        table: &mut Table<R>,
+        lines: Vec<Vec<String>>,
+    ) -> Result<Vec<Vec<Value>>, Tperrors> {
+        let mut inserted_rows: Vec<Vec<Value>> = Vec::new();
+        for line in lines {
+            // a full line may come as a single field, so the values are read from the line
+            let line = line.join(",");
+            inserted_rows.push(line.split(',').map(Value::from_field).collect());
+            match table.insert_line_to_csv(line) {
+                Ok(_) => {}
+                Err(_) => return Err(Tperrors::Generic("Error while inserting line".to_string())),
+            }
+        }
+        Ok(inserted_rows)
+    }
+
     /// Execute the insert query and return the inserted values but using a Mock
     ///
     /// This function is used for testing purposes
@@ -91,15 +218,18 @@ mod tests {
     fn test_is_valid_query() {
         let insert = Insert;
         let query = "INSERT INTO table VALUES ('Juan', 20);";
-        assert_eq!(insert.is_valid_query(query), true);
+        assert!(insert.is_valid_query(query));
 
         let query = "INSERT INTO table VALUES ('Juan', 20)";
-        assert_eq!(insert.is_valid_query(query), false);
+        assert!(!insert.is_valid_query(query));
 
         let query = "INSERT INTO table ('Juan', 20);";
-        assert_eq!(insert.is_valid_query(query), false);
+        assert!(!insert.is_valid_query(query));
 
         let query = "INSERT INTO table VALUES ('Juan', 20)";
-        assert_eq!(insert.is_valid_query(query), false);
+        assert!(!insert.is_valid_query(query));
+
+        let query = "INSERT INTO table (Nombre) SELECT Nombre FROM clientes;";
+        assert!(insert.is_valid_query(query));
     }
 }
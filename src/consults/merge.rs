@@ -0,0 +1,106 @@
+use std::{rc::Rc, time::Instant};
+
+use crate::{
+    conditions::value::Value,
+    errors::{fileerrors::FileErrors, tperrors::Tperrors},
+    extractors::{extractor::Extractor, sqlcommand::SQLCommand},
+    handler_tables::folder_tables::FolderTables,
+};
+
+use super::{select::SelectRunner, summary::ExecutionSummary};
+
+/// Struct to handle the MERGE INTO ... USING ... query.
+///
+/// The rows of the source are read once, and the target is rewritten once with the
+/// rows updated, deleted and inserted by the WHEN clauses.
+pub struct Merge;
+
+impl Default for Merge {
+    fn default() -> Self {
+        Merge::new()
+    }
+}
+
+impl Merge {
+    pub fn new() -> Merge {
+        Merge
+    }
+
+    /// A valid MERGE query contains MERGE INTO, USING, ON and WHEN, and ends with ;
+    pub fn is_valid_query(&self, query: &str) -> bool {
+        let query = query.trim();
+
+        if query.starts_with("MERGE INTO")
+            && query.contains("USING")
+            && query.contains(" ON ")
+            && query.contains("WHEN")
+        {
+            return query.ends_with(';');
+        }
+        false
+    }
+
+    /// Executes a MERGE query
+    ///
+    /// The target must be a table of the folder, the source can also be a view or a
+    /// derived table. If a clause fails the target is left as it was.
+    ///
+    /// Returns the summary of the query, with the amount of rows updated, deleted and inserted
+    pub fn execute(
+        &self,
+        folder_tables: &FolderTables,
+        consult: &str,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let started = Instant::now();
+        let merge = Extractor.parser_merge(consult)?;
+
+        let target = &merge.target.name;
+        if folder_tables.is_materialized_view(target) {
+            return Err(Tperrors::Table(format!(
+                "{} is a materialized view, it can only be changed by REFRESH MATERIALIZED VIEW",
+                target
+            )));
+        }
+        let mut table = folder_tables.open_table(target)?;
+
+        let runner = SelectRunner::new(Rc::new(folder_tables.clone()));
+        let mut source = merge.source.open(folder_tables, Some(&runner))?;
+        let source_columns = source.columns()?;
+        let mut source_rows: Vec<Vec<Value>> = Vec::new();
+        source.scan(&mut |_, values| {
+            source_rows.push(values);
+            Ok(true)
+        })?;
+
+        let (temp_file_dir, changed) =
+            table.resolve_merge_for_file(&merge, &source_columns, &source_rows)?;
+        match table.replace_original_with(temp_file_dir) {
+            Ok(_) => Ok(ExecutionSummary::new(SQLCommand::Merge, changed, started)),
+            Err(e) => match e {
+                FileErrors::DeletionFailed => Err(Tperrors::Generic("Deletion failed".to_string())),
+                FileErrors::InvalidFile => Err(Tperrors::Generic(
+                    "Error while updating the file".to_string(),
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_query() {
+        let merge = Merge;
+
+        assert!(merge.is_valid_query(
+            "MERGE INTO clientes c USING nuevos n ON c.Id = n.Id WHEN MATCHED THEN DELETE;"
+        ));
+        assert!(!merge.is_valid_query(
+            "MERGE INTO clientes c USING nuevos n ON c.Id = n.Id WHEN MATCHED THEN DELETE"
+        ));
+        assert!(!merge.is_valid_query("MERGE INTO clientes c USING nuevos n ON c.Id = n.Id;"));
+        assert!(!merge.is_valid_query("MERGE clientes WHEN MATCHED THEN DELETE;"));
+    }
+}
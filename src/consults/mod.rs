@@ -1,4 +1,10 @@
+pub mod create_table;
 pub mod delete;
 pub mod insert;
+pub mod merge;
+pub mod safe_updates;
 pub mod select;
+pub mod summary;
+pub mod truncate;
 pub mod update;
+pub mod view;
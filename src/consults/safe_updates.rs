@@ -0,0 +1,143 @@
+use crate::{errors::tperrors::Tperrors, extractors::sqlcommand::SQLCommand};
+
+/// Flag that turns on the safe updates mode
+pub const SAFE_UPDATES_FLAG: &str = "--safe-updates";
+
+/// Flag that turns off the safe updates mode
+pub const UNSAFE_UPDATES_FLAG: &str = "--unsafe-updates";
+
+/// How an UPDATE or a DELETE without WHERE (the ones that change every row) is handled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SafeUpdates {
+    /// The query runs like any other
+    Off,
+    /// The user has to confirm the query before it runs
+    Confirm,
+    /// The query is rejected
+    Reject,
+}
+
+impl SafeUpdates {
+    /// The mode given by the flags of the command line
+    ///
+    /// Without flags the mode is on when the program is used interactively (Confirm)
+    /// and off otherwise, so scripts keep working. With ```--safe-updates``` a query that
+    /// can't be confirmed is rejected, and ```--unsafe-updates``` turns it off.
+    ///
+    /// Returns a Generic error if a flag is unknown
+    pub fn from_flags(flags: &[String], interactive: bool) -> Result<SafeUpdates, Tperrors> {
+        let mut mode = match interactive {
+            true => SafeUpdates::Confirm,
+            false => SafeUpdates::Off,
+        };
+        for flag in flags {
+            mode = match flag.as_str() {
+                SAFE_UPDATES_FLAG if interactive => SafeUpdates::Confirm,
+                SAFE_UPDATES_FLAG => SafeUpdates::Reject,
+                UNSAFE_UPDATES_FLAG => SafeUpdates::Off,
+                _ => {
+                    return Err(Tperrors::Generic(format!(
+                        "Invalid flag {} (expected {} or {})",
+                        flag, SAFE_UPDATES_FLAG, UNSAFE_UPDATES_FLAG
+                    )));
+                }
+            };
+        }
+        Ok(mode)
+    }
+
+    /// Checks if a query can run, only an UPDATE or a DELETE without conditions is checked
+    ///
+    /// On Confirm, `confirm` is called with a question for the user and the query runs
+    /// only if it returns true
+    pub fn check<F: FnOnce(&str) -> bool>(
+        &self,
+        command: SQLCommand,
+        table: &str,
+        conditions: Option<&str>,
+        confirm: F,
+    ) -> Result<(), Tperrors> {
+        let changes_every_row =
+            conditions.is_none() && matches!(command, SQLCommand::Update | SQLCommand::Delete);
+        if !changes_every_row {
+            return Ok(());
+        }
+
+        let rejected = Tperrors::Generic(format!(
+            "{} without WHERE changes every row of {}, rejected by the safe updates mode (add a WHERE, use TRUNCATE TABLE or pass {})",
+            command.name(),
+            table,
+            UNSAFE_UPDATES_FLAG
+        ));
+        match self {
+            SafeUpdates::Off => Ok(()),
+            SafeUpdates::Reject => Err(rejected),
+            SafeUpdates::Confirm => {
+                let question = format!(
+                    "{} without WHERE changes every row of {}, continue? [y/N] ",
+                    command.name(),
+                    table
+                );
+                match confirm(&question) {
+                    true => Ok(()),
+                    false => Err(rejected),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(flags: &[&str]) -> Vec<String> {
+        flags.iter().map(|flag| flag.to_string()).collect()
+    }
+
+    #[test]
+    fn mode_depends_on_the_flags_and_the_terminal() {
+        assert_eq!(
+            SafeUpdates::from_flags(&[], true).unwrap(),
+            SafeUpdates::Confirm
+        );
+        assert_eq!(
+            SafeUpdates::from_flags(&[], false).unwrap(),
+            SafeUpdates::Off
+        );
+        assert_eq!(
+            SafeUpdates::from_flags(&flags(&["--safe-updates"]), false).unwrap(),
+            SafeUpdates::Reject
+        );
+        assert_eq!(
+            SafeUpdates::from_flags(&flags(&["--unsafe-updates"]), true).unwrap(),
+            SafeUpdates::Off
+        );
+        assert!(SafeUpdates::from_flags(&flags(&["--force"]), true).is_err());
+    }
+
+    #[test]
+    fn only_changes_of_every_row_are_checked() {
+        let never = |_: &str| -> bool { panic!("the user shouldn't be asked") };
+
+        assert!(SafeUpdates::Reject
+            .check(SQLCommand::Delete, "clientes", Some("Id = 1"), never)
+            .is_ok());
+        assert!(SafeUpdates::Reject
+            .check(SQLCommand::Insert, "clientes", None, never)
+            .is_ok());
+        assert!(SafeUpdates::Off
+            .check(SQLCommand::Update, "clientes", None, never)
+            .is_ok());
+        assert!(SafeUpdates::Reject
+            .check(SQLCommand::Delete, "clientes", None, never)
+            .is_err());
+
+        assert!(SafeUpdates::Confirm
+            .check(SQLCommand::Update, "clientes", None, |_| true)
+            .is_ok());
+        assert!(SafeUpdates::Confirm
+            .check(SQLCommand::Delete, "clientes", None, |_| false)
+            .is_err());
+    }
+}
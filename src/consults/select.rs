@@ -1,9 +1,28 @@
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Seek};
+use std::rc::Rc;
 
+use crate::conditions::condition::Condition;
+use crate::conditions::value::Value;
 use crate::errors::tperrors::*;
+use crate::expressions::expression::{column_position, Expression, Row};
+use crate::expressions::parser::ExpressionParser;
+use crate::expressions::projection::Projection;
+use crate::expressions::subquery::SubqueryRunner;
+use crate::extractors::extractor::Extractor;
+use crate::handler_tables::common_table::{CommonTable, WithClause};
+use crate::handler_tables::folder_tables::FolderTables;
+use crate::handler_tables::join::FromClause;
+use crate::handler_tables::row_source::RowSource;
+use crate::handler_tables::select_options::SelectOptions;
+use crate::handler_tables::select_query::{QueryContext, SelectQuery};
+use crate::handler_tables::set_operation::SetOperator;
 use crate::handler_tables::table::*;
 use crate::sorter::sort::SortMethod;
 
+/// Max amount of times the recursive part of a ```WITH RECURSIVE``` table is run
+const RECURSION_LIMIT: usize = 1000;
+
 pub struct Select;
 
 impl Default for Select {
@@ -23,7 +42,8 @@ impl Select {
     pub fn is_valid_query(&self, query: &str) -> bool {
         let query = query.trim();
 
-        if query.starts_with("SELECT") && query.contains("FROM") {
+        // a query can start by defining the tables it uses (WITH)
+        if (query.starts_with("SELECT") || query.starts_with("WITH")) && query.contains("FROM") {
             match query.chars().last() {
                 Some(';') => return true,
                 _ => return false,
@@ -32,6 +52,73 @@ impl Select {
         false
     }
 
+    /// Splits a SELECT query in its clauses
+    ///
+    /// Returns the tables to read (FROM) and the rest of the query
+    pub fn parse_query(&self, consult: &str) -> Result<(FromClause, SelectQuery), Tperrors> {
+        if !self.is_valid_query(consult) {
+            return Err(Tperrors::Syntax(
+                "Invalid select query (Missing either SELECT , FROM or ;)".to_string(),
+            ));
+        }
+        let extractor = Extractor;
+
+        // the tables to read (and how they are joined)
+        let from = match extractor.extract_from_as_str(consult) {
+            Some(from) => extractor.parser_from_clause(from)?,
+            None => {
+                return Err(Tperrors::Syntax(
+                    "Invalid select query (Missing FROM)".to_string(),
+                ));
+            }
+        };
+
+        // lets get the columns selected from the query
+        let columns = extractor.extract_columns_for_select(consult)?;
+
+        // Conditions of the query (if they exists)
+        let conditions = extractor.extract_as_str_conditions(consult);
+
+        if let Some(c) = conditions {
+            if c.is_empty() {
+                return Err(Tperrors::Syntax("incomplete input".to_string()));
+            }
+        }
+
+        // Sorting method (if existst)
+        let sorting_vector = extractor
+            .extract_orderby_as_str(consult)
+            .map(|sorting| extractor.parser_orderby_from_str_to_vec(sorting));
+
+        // Grouping (if exists)
+        let group_by = match extractor.extract_groupby_as_str(consult) {
+            Some(groupby) => extractor.parser_groupby_from_str_to_vec(groupby),
+            None => Vec::new(),
+        };
+        let having = extractor.extract_having_as_str(consult);
+
+        if group_by.iter().any(|g| g.is_empty()) || having.is_some_and(|h| h.is_empty()) {
+            return Err(Tperrors::Syntax("incomplete input".to_string()));
+        }
+
+        // Amount of rows (if exists)
+        let (limit, offset) = extractor.extract_limit_and_offset(consult)?;
+
+        let query = SelectQuery {
+            columns,
+            conditions: conditions.map(|c| c.to_string()),
+            sorting: sorting_vector,
+            options: SelectOptions {
+                group_by,
+                having: having.map(|h| h.to_string()),
+                distinct: extractor.is_distinct_select(consult),
+                limit,
+                offset,
+            },
+        };
+        Ok((from, query))
+    }
+
     /// Given a table, columns, conditions and sorting method
     ///
     /// executes a SELECT query statement.
@@ -45,13 +132,32 @@ impl Select {
         conditions: Option<&str>,
         sorting_method: Option<Vec<SortMethod>>,
     ) -> Result<(), Tperrors> {
-        let csv_data = table.resolve_select(columns, conditions, sorting_method);
+        self.execute_select_with_options(
+            table,
+            columns,
+            conditions,
+            sorting_method,
+            SelectOptions::default(),
+        )
+    }
+
+    /// Same as execute_select, with the clauses applied after filtering the rows
+    /// (GROUP BY, HAVING, DISTINCT)
+    pub fn execute_select_with_options<R: Read + Seek>(
+        &self,
+        table: &mut Table<R>,
+        columns: Vec<String>,
+        conditions: Option<&str>,
+        sorting_method: Option<Vec<SortMethod>>,
+        options: SelectOptions,
+    ) -> Result<(), Tperrors> {
+        let csv_data =
+            table.resolve_select_with_options(columns, conditions, sorting_method, &options);
 
         match csv_data {
             Ok(data) => {
                 for line in data {
-                    let temp_line = line.join(",");
-                    println!("{}", temp_line);
+                    println!("{}", Self::csv_line(&line));
                 }
                 Ok(())
             }
@@ -59,6 +165,61 @@ impl Select {
         }
     }
 
+    /// Executes a SELECT query statement (or several of them combined with UNION,
+    /// INTERSECT or EXCEPT), reading the tables of the folder of the runner
+    ///
+    /// Returns ok if the query was executed successfully
+    pub fn execute_with_runner(
+        &self,
+        consult: &str,
+        runner: &SelectRunner,
+    ) -> Result<(), Tperrors> {
+        let (header, rows) = runner.run(consult, None)?;
+        self.print_rows(&header, &rows);
+        Ok(())
+    }
+
+    /// Prints the header and the rows of a query result, as the lines of a CSV
+    ///
+    /// (also used for the rows of a RETURNING clause)
+    pub fn print_rows(&self, header: &[String], rows: &[Vec<Value>]) {
+        println!("{}", Self::csv_line(header));
+        for row in rows {
+            let line = row.iter().map(|v| v.to_string()).collect::<Vec<String>>();
+            println!("{}", Self::csv_line(&line));
+        }
+    }
+
+    /// Joins the fields as a line of a CSV
+    ///
+    /// A field with a comma, a quote or a new line goes between double quotes (its quotes
+    /// are doubled), so ```SUBSTR(Apellido, 2, 3)``` is still a single field
+    pub fn csv_line(fields: &[String]) -> String {
+        fields
+            .iter()
+            .map(|field| {
+                if field.contains([',', '"', '\n', '\r']) {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                } else {
+                    field.to_string()
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    /// Executes a SELECT query statement reading the rows of any source
+    /// (Example: tables joined on the FROM clause), under a mock environment
+    ///
+    /// Returns a vector of vectors with the data
+    pub fn execute_query_mock(
+        &self,
+        source: &mut dyn RowSource,
+        query: SelectQuery,
+    ) -> Result<Vec<Vec<String>>, Tperrors> {
+        query.resolve(source)
+    }
+
     /// Given a table, columns, conditions and sorting method
     ///
     /// executes a SELECT query statement under a mock environment
@@ -76,6 +237,283 @@ impl Select {
     ) -> Result<Vec<Vec<String>>, Tperrors> {
         table.resolve_select(columns, conditions, sorting_method)
     }
+
+    /// Same as execute_select_mock, with the clauses applied after filtering the rows
+    /// (GROUP BY, HAVING, DISTINCT)
+    pub fn execute_select_mock_with_options<R: Read + Seek>(
+        &self,
+        table: &mut Table<R>,
+        columns: Vec<String>,
+        conditions: Option<&str>,
+        sorting_method: Option<Vec<SortMethod>>,
+        options: SelectOptions,
+    ) -> Result<Vec<Vec<String>>, Tperrors> {
+        table.resolve_select_with_options(columns, conditions, sorting_method, &options)
+    }
+}
+
+/// The columns and rows of the tables defined on a WITH, by their name
+type CommonTables = HashMap<String, (Vec<String>, Vec<Vec<Value>>)>;
+
+/// Runs the subqueries of a query, reading the tables of the folder
+/// (and the ones defined on the WITH of the query)
+#[derive(Clone)]
+pub struct SelectRunner {
+    folder_tables: Rc<FolderTables>,
+    common_tables: Rc<CommonTables>,
+    /// The views whose query is being run, so a view that reads itself is an error
+    expanding_views: Rc<HashSet<String>>,
+}
+
+impl SelectRunner {
+    pub fn new(folder_tables: Rc<FolderTables>) -> SelectRunner {
+        SelectRunner {
+            folder_tables,
+            common_tables: Rc::new(HashMap::new()),
+            expanding_views: Rc::new(HashSet::new()),
+        }
+    }
+
+    /// A runner that can also read the given table, as if it were on the folder
+    fn with_common_table(
+        &self,
+        name: &str,
+        columns: Vec<String>,
+        rows: Vec<Vec<Value>>,
+    ) -> SelectRunner {
+        let mut common_tables = self.common_tables.as_ref().clone();
+        common_tables.insert(name.to_string(), (columns, rows));
+        SelectRunner {
+            folder_tables: Rc::clone(&self.folder_tables),
+            common_tables: Rc::new(common_tables),
+            expanding_views: Rc::clone(&self.expanding_views),
+        }
+    }
+
+    /// Runs the queries of the tables of a WITH, in order
+    ///
+    /// Returns a runner that can read all of them
+    fn with_clause_runner(
+        &self,
+        with: &WithClause,
+        outer: Option<&Row>,
+    ) -> Result<SelectRunner, Tperrors> {
+        let mut runner = self.clone();
+        for table in &with.tables {
+            let (columns, rows) = match with.recursive {
+                true => runner.run_recursive(table, outer)?,
+                false => runner.run_common_table(table, outer)?,
+            };
+            runner = runner.with_common_table(&table.name, columns, rows);
+        }
+        Ok(runner)
+    }
+
+    /// Runs the query of a table of a WITH, its columns get the names given to the table
+    fn run_common_table(
+        &self,
+        table: &CommonTable,
+        outer: Option<&Row>,
+    ) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors> {
+        let (header, rows) = self.run(&table.query, outer)?;
+        Ok((table.column_names(header)?, rows))
+    }
+
+    /// Runs the query of a table of a ```WITH RECURSIVE```
+    ///
+    /// If it is ```SELECT ... UNION [ALL] SELECT ...``` and the second SELECT reads the table,
+    /// the first one gives the first rows. Then the second one is run again and again, reading
+    /// only the rows added the last time, until it doesn't add any.
+    ///
+    /// With UNION the rows already added are skipped, so cycles end. With UNION ALL a cycle
+    /// never ends, so it's an error to run it more than RECURSION_LIMIT times.
+    fn run_recursive(
+        &self,
+        table: &CommonTable,
+        outer: Option<&Row>,
+    ) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors> {
+        let compound = match Extractor.parser_set_operations(&table.query)? {
+            Some(compound) if compound.operations.len() == 1 => compound,
+            _ => return self.run_common_table(table, outer),
+        };
+        let (operator, recursive_query) = &compound.operations[0];
+        let distinct = match operator {
+            SetOperator::Union => true,
+            SetOperator::UnionAll => false,
+            _ => return self.run_common_table(table, outer),
+        };
+        let (from, _) = Select.parse_query(recursive_query)?;
+        let reads_itself = std::iter::once(&from.table)
+            .chain(from.joins.iter().map(|join| &join.table))
+            .any(|reference| reference.subquery.is_none() && reference.name == table.name);
+        if !reads_itself {
+            return self.run_common_table(table, outer);
+        }
+
+        let (header, first_rows) = self.run(&compound.first, outer)?;
+        let columns = table.column_names(header)?;
+
+        let mut seen: HashSet<Vec<Option<String>>> = HashSet::new();
+        let mut added = first_rows
+            .into_iter()
+            .filter(|row| !distinct || seen.insert(row.iter().map(|v| v.hash_key()).collect()))
+            .collect::<Vec<Vec<Value>>>();
+        let mut rows = added.clone();
+
+        let mut iterations = 0;
+        while !added.is_empty() {
+            iterations += 1;
+            if iterations > RECURSION_LIMIT {
+                return Err(Tperrors::Generic(format!(
+                    "Table {} is still adding rows after {} iterations, it may never end",
+                    table.name, RECURSION_LIMIT
+                )));
+            }
+
+            let runner = self.with_common_table(&table.name, columns.clone(), added);
+            let (step_columns, step_rows) = runner.run(recursive_query, outer)?;
+            if step_columns.len() != columns.len() {
+                return Err(Tperrors::Syntax(format!(
+                    "Each SELECT of table {} must return the same number of columns, {} and {} found",
+                    table.name,
+                    columns.len(),
+                    step_columns.len()
+                )));
+            }
+            added = step_rows
+                .into_iter()
+                .filter(|row| !distinct || seen.insert(row.iter().map(|v| v.hash_key()).collect()))
+                .collect();
+            rows.extend(added.iter().cloned());
+        }
+        Ok((columns, rows))
+    }
+
+    /// The context of a query whose subqueries are run by this runner
+    pub fn context(&self) -> QueryContext {
+        QueryContext {
+            runner: Some(Rc::new(self.clone())),
+            outer: None,
+        }
+    }
+
+    /// Every expression of the query (the SELECT list, WHERE, GROUP BY and HAVING),
+    /// checking that their columns are on the given ones
+    fn expressions_of(
+        query: &SelectQuery,
+        columns: &[String],
+    ) -> Result<Vec<Expression>, Tperrors> {
+        let select_list = query
+            .columns
+            .iter()
+            .filter(|item| item.trim() != "*")
+            .cloned()
+            .collect::<Vec<String>>();
+        let mut expressions = Projection::from_select_list(&select_list, columns)?
+            .into_iter()
+            .map(|projection| projection.expression)
+            .collect::<Vec<Expression>>();
+
+        if let Some(conditions) = &query.conditions {
+            expressions.push(Condition::compile(conditions, columns)?);
+        }
+        for item in query
+            .options
+            .group_by
+            .iter()
+            .chain(query.options.having.iter())
+        {
+            let expression = ExpressionParser::parse(item)?;
+            expression.validate_columns(columns)?;
+            expressions.push(expression);
+        }
+        Ok(expressions)
+    }
+}
+
+impl SubqueryRunner for SelectRunner {
+    fn run(
+        &self,
+        query: &str,
+        outer: Option<&Row>,
+    ) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors> {
+        if let Some(with) = Extractor.parser_with_clause(query)? {
+            let runner = self.with_clause_runner(&with, outer)?;
+            return runner.run(&with.body, outer);
+        }
+        if let Some(compound) = Extractor.parser_set_operations(query)? {
+            return compound.resolve(self, outer);
+        }
+        let (from, select_query) = Select.parse_query(query)?;
+        let mut source = from.open(&self.folder_tables, Some(self))?;
+
+        let mut context = self.context();
+        context.outer = outer.map(|row| (row.columns().to_vec(), row.values().to_vec()));
+        select_query.resolve_values(source.as_mut(), &context)
+    }
+
+    fn is_correlated(&self, query: &str, outer_columns: &[String]) -> Result<bool, Tperrors> {
+        // the tables of a WITH can't use the outer row, but the query that reads them can
+        if let Some(with) = Extractor.parser_with_clause(query)? {
+            let runner = self.with_clause_runner(&with, None)?;
+            return runner.is_correlated(&with.body, outer_columns);
+        }
+        if let Some(compound) = Extractor.parser_set_operations(query)? {
+            for select in compound.operations.iter().map(|(_, select)| select) {
+                if self.is_correlated(select, outer_columns)? {
+                    return Ok(true);
+                }
+            }
+            return self.is_correlated(&compound.first, outer_columns);
+        }
+        let (from, select_query) = Select.parse_query(query)?;
+        let source_columns = from.open(&self.folder_tables, Some(self))?.columns()?;
+        let all_columns = source_columns
+            .iter()
+            .chain(outer_columns.iter())
+            .cloned()
+            .collect::<Vec<String>>();
+
+        for expression in Self::expressions_of(&select_query, &all_columns)? {
+            // a column of the outer row that isn't on the tables of the subquery
+            let uses_outer = expression
+                .columns()
+                .into_iter()
+                .any(|column| column_position(&source_columns, column).is_none());
+            if uses_outer {
+                return Ok(true);
+            }
+            // the subqueries inside this one can also use the outer row
+            for subquery in expression.subqueries() {
+                if self.is_correlated(subquery.query(), outer_columns)? {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn common_table(&self, name: &str) -> Option<(Vec<String>, Vec<Vec<Value>>)> {
+        self.common_tables.get(name).cloned()
+    }
+
+    fn run_view(
+        &self,
+        name: &str,
+        query: &str,
+    ) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors> {
+        if self.expanding_views.contains(name) {
+            return Err(Tperrors::Table(format!("view {} reads itself", name)));
+        }
+        let mut expanding_views = self.expanding_views.as_ref().clone();
+        expanding_views.insert(name.to_string());
+        let runner = SelectRunner {
+            folder_tables: Rc::clone(&self.folder_tables),
+            common_tables: Rc::clone(&self.common_tables),
+            expanding_views: Rc::new(expanding_views),
+        };
+        runner.run(query, None)
+    }
 }
 
 #[cfg(test)]
@@ -94,7 +532,7 @@ mod tests {
             "name, age FROM",          // missing table name
         ]);
         for invalid_query in invalid_consults {
-            assert_eq!(select.is_valid_query(invalid_query), false);
+            assert!(!select.is_valid_query(invalid_query));
         }
     }
 
@@ -109,6 +547,6 @@ mod tests {
 
         let result = select.execute_select(&mut table, columns, conditions, sorting);
 
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 }
@@ -0,0 +1,78 @@
+use std::time::{Duration, Instant};
+
+use crate::{conditions::value::Value, extractors::sqlcommand::SQLCommand};
+
+/// What was done by a query that changes a table
+///
+/// # Fields
+///
+/// * `command` - The kind of query (CREATE TABLE ... AS SELECT, and the queries that write the
+///   rows of a materialized view, are reported as a SELECT).
+///
+/// * `rows` - Amount of rows inserted, updated, deleted or written.
+///
+/// * `elapsed` - How long the query took.
+///
+/// * `returning` - The header and the rows of the RETURNING clause (if the query has one).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionSummary {
+    pub command: SQLCommand,
+    pub rows: usize,
+    pub elapsed: Duration,
+    pub returning: Option<(Vec<String>, Vec<Vec<Value>>)>,
+}
+
+impl ExecutionSummary {
+    /// The summary of a query that started at `started` and changed `rows` rows
+    pub fn new(command: SQLCommand, rows: usize, started: Instant) -> ExecutionSummary {
+        ExecutionSummary {
+            command,
+            rows,
+            elapsed: started.elapsed(),
+            returning: None,
+        }
+    }
+
+    /// Adds the rows of the RETURNING clause to the summary
+    pub fn with_returning(
+        mut self,
+        header: Vec<String>,
+        rows: Vec<Vec<Value>>,
+    ) -> ExecutionSummary {
+        self.returning = Some((header, rows));
+        self
+    }
+
+    /// The command tag of the query, Example: ```UPDATE 3```
+    ///
+    /// Without the rows for the commands that don't change any, Example: ```CREATE VIEW```
+    pub fn tag(&self) -> String {
+        match self.command.counts_rows() {
+            true => format!("{} {}", self.command.name(), self.rows),
+            false => self.command.name().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_tag_has_the_command_and_the_rows() {
+        let summary = ExecutionSummary::new(SQLCommand::Update, 3, Instant::now());
+        assert_eq!(summary.tag(), "UPDATE 3");
+        assert!(summary.returning.is_none());
+
+        let summary = ExecutionSummary::new(SQLCommand::Delete, 0, Instant::now())
+            .with_returning(vec!["Id".to_string()], Vec::new());
+        assert_eq!(summary.tag(), "DELETE 0");
+        assert_eq!(
+            summary.returning,
+            Some((vec!["Id".to_string()], Vec::new()))
+        );
+
+        let summary = ExecutionSummary::new(SQLCommand::DropView, 0, Instant::now());
+        assert_eq!(summary.tag(), "DROP VIEW");
+    }
+}
@@ -0,0 +1,84 @@
+use std::io::{Read, Seek};
+use std::time::Instant;
+
+use crate::errors::{fileerrors::FileErrors, tperrors::Tperrors};
+use crate::extractors::sqlcommand::SQLCommand;
+use crate::handler_tables::table::Table;
+
+use super::summary::ExecutionSummary;
+
+/// Struct to handle the TRUNCATE TABLE query.
+///
+/// Removes every row of the table, only the header is kept.
+///
+/// The rows are still read to count them, but they are never split nor checked
+/// against a condition like on a DELETE.
+pub struct Truncate;
+
+impl Default for Truncate {
+    fn default() -> Self {
+        Truncate::new()
+    }
+}
+
+impl Truncate {
+    pub fn new() -> Truncate {
+        Truncate
+    }
+
+    /// A valid TRUNCATE query starts with TRUNCATE, has only the table (TABLE is optional)
+    /// and ends with ;
+    pub fn is_valid_query(&self, query: &str) -> bool {
+        let query = query.trim();
+
+        let table = match query
+            .strip_prefix("TRUNCATE")
+            .and_then(|rest| rest.strip_suffix(';'))
+        {
+            Some(rest) => rest.trim(),
+            None => return false,
+        };
+        let table = table.strip_prefix("TABLE ").unwrap_or(table).trim();
+        !table.is_empty() && table != "TABLE" && !table.contains(char::is_whitespace)
+    }
+
+    /// Execute the truncate query
+    ///
+    /// Returns the summary of the query, with the amount of removed rows
+    pub fn execute_truncate<R: Read + Seek>(
+        &self,
+        table: &mut Table<R>,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let started = Instant::now();
+        let (temp_file_dir, removed_rows) = table.resolve_truncate_for_file()?;
+        match table.replace_original_with(temp_file_dir) {
+            Ok(_) => Ok(ExecutionSummary::new(
+                SQLCommand::Truncate,
+                removed_rows,
+                started,
+            )),
+            Err(e) => match e {
+                FileErrors::DeletionFailed => Err(Tperrors::Generic("Deletion failed".to_string())),
+                FileErrors::InvalidFile => Err(Tperrors::Generic(
+                    "Error while updating the file".to_string(),
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_query() {
+        let truncate = Truncate;
+
+        assert!(truncate.is_valid_query("TRUNCATE TABLE clientes;"));
+        assert!(truncate.is_valid_query("TRUNCATE clientes;"));
+        assert!(!truncate.is_valid_query("TRUNCATE TABLE clientes"));
+        assert!(!truncate.is_valid_query("TRUNCATE TABLE;"));
+        assert!(!truncate.is_valid_query("TRUNCATE TABLE clientes WHERE Id = 1;"));
+    }
+}
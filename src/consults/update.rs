@@ -1,8 +1,13 @@
 use std::io::{BufReader, Cursor, Read, Seek};
+use std::time::Instant;
 
+use crate::conditions::value::Value;
 use crate::errors::fileerrors::*;
 use crate::errors::tperrors::Tperrors;
-use crate::handler_tables::table::*;
+use crate::extractors::sqlcommand::SQLCommand;
+use crate::handler_tables::{returning::Returning, row_source::RowSource, table::*};
+
+use super::summary::ExecutionSummary;
 
 pub struct Update;
 
@@ -42,17 +47,46 @@ impl Update {
     /// UPDATE table_name SET column1 = value1, column2 = value2 WHERE condition;
     ///
     /// UPDATE table_name SET column1 = value1, column2 = value2;
+    ///
+    /// Returns the summary of the query, with the amount of updated rows, and the rows
+    /// of the RETURNING clause (computed with the new values) if it's given
     pub fn execute_update<R: Read + Seek>(
         &self,
         table: &mut Table<R>,
         columns: Vec<String>,
         values: Vec<String>,
         conditions: Option<&str>,
-    ) -> Result<(), Tperrors> {
+        returning: Option<&Returning>,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let started = Instant::now();
+        let table_columns = table.columns()?;
+        // the clause is checked before any row is changed
+        if let Some(returning) = returning {
+            returning.validate(&table_columns)?;
+        }
+        let changed_rows = self.update_rows(table, columns, values, conditions)?;
+        let summary = ExecutionSummary::new(SQLCommand::Update, changed_rows.len(), started);
+        match returning {
+            Some(returning) => {
+                let (header, rows) = returning.resolve(&table_columns, &changed_rows)?;
+                Ok(summary.with_returning(header, rows))
+            }
+            None => Ok(summary),
+        }
+    }
+
+    /// Updates the rows that meet the conditions, returning their new values
+    fn update_rows<R: Read + Seek>(
+        &self,
+        table: &mut Table<R>,
+        columns: Vec<String>,
+        values: Vec<String>,
+        conditions: Option<&str>,
+    ) -> Result<Vec<Vec<Value>>, Tperrors> {
         let resolve = table.resolve_update_for_file(columns, values, conditions);
 
         match resolve {
-            Ok(temporal_directory_filename) => {
+            Ok((temporal_directory_filename, changed_rows)) => {
                 match table.replace_original_with(temporal_directory_filename) {
                     Ok(_) => {}
                     Err(e) => match e {
@@ -67,7 +101,7 @@ impl Update {
                     },
                 }
 
-                Ok(())
+                Ok(changed_rows)
             }
             Err(e) => Err(e),
         }
@@ -100,21 +134,21 @@ mod tests {
         let update = Update::new();
 
         let query = "UPDATE table_name SET column1 = value1, column2 = value2 WHERE condition;";
-        assert_eq!(update.is_valid_query(query), true);
+        assert!(update.is_valid_query(query));
 
         let query = "UPDATE table_name SET column1 = value1, column2 = value2;";
-        assert_eq!(update.is_valid_query(query), true);
+        assert!(update.is_valid_query(query));
 
         let query = "UPDATE table_name SET column1 = value1, column2 = value2";
-        assert_eq!(update.is_valid_query(query), false);
+        assert!(!update.is_valid_query(query));
 
         let query = "UPDATE table_name SET column1 = value1, column2 = value2 WHERE condition";
-        assert_eq!(update.is_valid_query(query), false);
+        assert!(!update.is_valid_query(query));
 
         let query = "UPDATE table_name SET column1 = value1, column2 = value2 WHERE condition";
-        assert_eq!(update.is_valid_query(query), false);
+        assert!(!update.is_valid_query(query));
 
         let query = "UPDATE table_name SET column1 = value1, column2 = value2 WHERE condition";
-        assert_eq!(update.is_valid_query(query), false);
+        assert!(!update.is_valid_query(query));
     }
 }
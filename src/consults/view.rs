@@ -0,0 +1,165 @@
+use std::{rc::Rc, time::Instant};
+
+use crate::{
+    errors::tperrors::Tperrors,
+    expressions::subquery::SubqueryRunner,
+    extractors::{extractor::Extractor, sqlcommand::SQLCommand},
+    handler_tables::folder_tables::FolderTables,
+};
+
+use super::{select::SelectRunner, summary::ExecutionSummary};
+
+/// Struct to handle the CREATE VIEW, DROP VIEW and REFRESH MATERIALIZED VIEW queries.
+///
+/// A view is a SELECT saved on the folder (as ```name.view.sql```), it's read like a table
+/// and its query is run every time it's used.
+///
+/// A materialized view keeps the rows of its query as a table (```name.csv```), they are
+/// computed again only with REFRESH MATERIALIZED VIEW.
+pub struct View;
+
+impl Default for View {
+    fn default() -> Self {
+        View::new()
+    }
+}
+
+impl View {
+    pub fn new() -> View {
+        View
+    }
+
+    /// A valid CREATE VIEW query contains CREATE [MATERIALIZED] VIEW and AS, and ends with ;
+    pub fn is_valid_create_query(&self, query: &str) -> bool {
+        let query = query.trim();
+
+        if (query.starts_with("CREATE VIEW") || query.starts_with("CREATE MATERIALIZED VIEW"))
+            && query.contains(" AS ")
+        {
+            return query.ends_with(';');
+        }
+        false
+    }
+
+    /// A valid DROP VIEW query contains DROP [MATERIALIZED] VIEW and ends with ;
+    pub fn is_valid_drop_query(&self, query: &str) -> bool {
+        let query = query.trim();
+        (query.starts_with("DROP VIEW") || query.starts_with("DROP MATERIALIZED VIEW"))
+            && query.ends_with(';')
+    }
+
+    /// A valid REFRESH query contains REFRESH MATERIALIZED VIEW and ends with ;
+    pub fn is_valid_refresh_query(&self, query: &str) -> bool {
+        let query = query.trim();
+        query.starts_with("REFRESH MATERIALIZED VIEW") && query.ends_with(';')
+    }
+
+    /// Executes a CREATE [MATERIALIZED] VIEW query, saving the view on the folder
+    ///
+    /// The query of the view is run once, so a view that can't be read is never saved
+    /// (and a materialized view is saved with the rows of that run)
+    ///
+    /// Returns the summary of the query, a materialized view is reported as a SELECT
+    /// with the amount of rows written
+    pub fn execute_create(
+        &self,
+        folder_tables: &mut FolderTables,
+        consult: &str,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let started = Instant::now();
+        let (name, query) = Extractor.parser_create_view(consult)?;
+
+        let runner = SelectRunner::new(Rc::new(folder_tables.clone()));
+        let (header, rows) = runner.run(&query, None)?;
+
+        match Self::is_materialized(consult) {
+            true => {
+                folder_tables.create_materialized_view(&name, &query, &header, &rows)?;
+                Ok(ExecutionSummary::new(
+                    SQLCommand::Select,
+                    rows.len(),
+                    started,
+                ))
+            }
+            false => {
+                folder_tables.create_view(&name, &query)?;
+                Ok(ExecutionSummary::new(SQLCommand::CreateView, 0, started))
+            }
+        }
+    }
+
+    /// Executes a DROP [MATERIALIZED] VIEW query, removing the view from the folder
+    ///
+    /// With IF EXISTS, a view that isn't on the folder is not an error
+    pub fn execute_drop(
+        &self,
+        folder_tables: &mut FolderTables,
+        consult: &str,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let started = Instant::now();
+        let (name, if_exists) = Extractor.parser_drop_view(consult)?;
+        let command = match Self::is_materialized(consult) {
+            true => SQLCommand::DropMaterializedView,
+            false => SQLCommand::DropView,
+        };
+
+        if if_exists && !folder_tables.is_view(&name) && folder_tables.get_path(&name).is_none() {
+            return Ok(ExecutionSummary::new(command, 0, started));
+        }
+        match command {
+            SQLCommand::DropMaterializedView => folder_tables.drop_materialized_view(&name)?,
+            _ => folder_tables.drop_view(&name)?,
+        }
+        Ok(ExecutionSummary::new(command, 0, started))
+    }
+
+    /// Executes a REFRESH MATERIALIZED VIEW query, running its query again
+    /// and replacing its rows
+    ///
+    /// Returns the summary of the query, like the one of a SELECT with the amount of rows written
+    pub fn execute_refresh(
+        &self,
+        folder_tables: &mut FolderTables,
+        consult: &str,
+    ) -> Result<ExecutionSummary, Tperrors> {
+        let started = Instant::now();
+        let name = Extractor.parser_refresh_view(consult)?;
+        let query = folder_tables.materialized_view_query(&name)?;
+
+        let runner = SelectRunner::new(Rc::new(folder_tables.clone()));
+        let (header, rows) = runner.run(&query, None)?;
+
+        folder_tables.refresh_materialized_view(&name, &header, &rows)?;
+        Ok(ExecutionSummary::new(
+            SQLCommand::Select,
+            rows.len(),
+            started,
+        ))
+    }
+
+    /// Returns true if the query is about a materialized view, Example: ```DROP MATERIALIZED VIEW v;```
+    fn is_materialized(consult: &str) -> bool {
+        consult.split_whitespace().nth(1) == Some("MATERIALIZED")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_query() {
+        let view = View;
+        assert!(view.is_valid_create_query("CREATE VIEW v AS SELECT * FROM t;"));
+        assert!(!view.is_valid_create_query("CREATE VIEW v AS SELECT * FROM t"));
+        assert!(!view.is_valid_create_query("CREATE VIEW v SELECT * FROM t;"));
+
+        assert!(view.is_valid_create_query("CREATE MATERIALIZED VIEW v AS SELECT * FROM t;"));
+
+        assert!(view.is_valid_drop_query("DROP VIEW v;"));
+        assert!(view.is_valid_drop_query("DROP MATERIALIZED VIEW v;"));
+        assert!(view.is_valid_refresh_query("REFRESH MATERIALIZED VIEW v;"));
+        assert!(!view.is_valid_refresh_query("REFRESH VIEW v;"));
+        assert!(!view.is_valid_drop_query("DROP TABLE v;"));
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::HashSet;
+
+use crate::{conditions::value::Value, errors::tperrors::Tperrors};
+
+use super::expression::{Expression, Row};
+
+/// Functions that compute a single value from every row of a group
+#[derive(Debug, Clone, PartialEq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    /// Given the name of a function, returns it if it is an aggregate function
+    ///
+    /// The name is not case sensitive, Example: ```count``` and ```COUNT``` are the same
+    pub fn from_name(name: &str) -> Option<AggregateFunction> {
+        match name.to_uppercase().as_str() {
+            "COUNT" => Some(AggregateFunction::Count),
+            "SUM" => Some(AggregateFunction::Sum),
+            "AVG" => Some(AggregateFunction::Avg),
+            "MIN" => Some(AggregateFunction::Min),
+            "MAX" => Some(AggregateFunction::Max),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            AggregateFunction::Count => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        }
+    }
+}
+
+/// The partial result of an aggregate function, updated with every row of a group
+///
+/// NULL values are ignored, so ```COUNT(Edad)``` only counts the rows with an Edad
+/// and ```AVG(Edad)``` is NULL if no row has one.
+pub struct Accumulator {
+    function: AggregateFunction,
+    count: i64,
+    sum: Value,
+    /// the smallest (MIN) or biggest (MAX) value seen
+    best: Value,
+    /// values already seen, only for DISTINCT
+    seen: Option<HashSet<String>>,
+}
+
+impl Accumulator {
+    pub fn new(function: AggregateFunction, distinct: bool) -> Accumulator {
+        Accumulator {
+            function,
+            count: 0,
+            sum: Value::Integer(0),
+            best: Value::Null,
+            seen: if distinct { Some(HashSet::new()) } else { None },
+        }
+    }
+
+    /// Adds the value of a row
+    ///
+    /// Returns an error if SUM or AVG get something that isn't a number
+    pub fn update(&mut self, value: Value) -> Result<(), Tperrors> {
+        let key = match value.hash_key() {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+        if let Some(seen) = &mut self.seen {
+            if !seen.insert(key) {
+                return Ok(());
+            }
+        }
+        self.count += 1;
+
+        match self.function {
+            AggregateFunction::Count => {}
+            AggregateFunction::Sum | AggregateFunction::Avg => {
+                self.sum = match (&self.sum, &value) {
+                    (Value::Integer(sum), Value::Integer(v)) => match sum.checked_add(*v) {
+                        Some(sum) => Value::Integer(sum),
+                        None => {
+                            return Err(Tperrors::Generic("Integer out of range".to_string()));
+                        }
+                    },
+                    _ => match (self.sum.as_f64(), value.as_f64()) {
+                        (Some(sum), Some(v)) => Value::Real(sum + v),
+                        _ => {
+                            return Err(Tperrors::Syntax(format!(
+                                "{} expected numbers, found {}",
+                                self.function.name(),
+                                value
+                            )));
+                        }
+                    },
+                };
+            }
+            AggregateFunction::Min | AggregateFunction::Max => {
+                let replace = match value.compare(&self.best) {
+                    None => true, // first value seen
+                    Some(ordering) => match self.function {
+                        AggregateFunction::Min => ordering.is_lt(),
+                        _ => ordering.is_gt(),
+                    },
+                };
+                if replace {
+                    self.best = value;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the final value of the function
+    pub fn result(&self) -> Value {
+        match self.function {
+            AggregateFunction::Count => Value::Integer(self.count),
+            _ if self.count == 0 => Value::Null,
+            AggregateFunction::Sum => self.sum.clone(),
+            AggregateFunction::Avg => match self.sum.as_f64() {
+                Some(sum) => Value::Real(sum / self.count as f64),
+                None => Value::Null,
+            },
+            AggregateFunction::Min | AggregateFunction::Max => self.best.clone(),
+        }
+    }
+}
+
+/// The aggregate functions used by a query
+///
+/// Every aggregate function found on the expressions of the query is taken out of them and
+/// replaced by a column, so after computing them over a group, the expressions can be
+/// evaluated as usual. Example: ```MAX(Edad) - MIN(Edad)``` is evaluated as ```MAX#0 - MIN#1```
+pub struct Aggregation {
+    aggregates: Vec<AggregateCall>,
+}
+
+/// A call to an aggregate function found on the query
+#[derive(PartialEq)]
+struct AggregateCall {
+    function: AggregateFunction,
+    argument: Option<Expression>,
+    distinct: bool,
+}
+
+impl Default for Aggregation {
+    fn default() -> Self {
+        Aggregation::new()
+    }
+}
+
+impl Aggregation {
+    pub fn new() -> Aggregation {
+        Aggregation {
+            aggregates: Vec::new(),
+        }
+    }
+
+    /// Replaces the aggregate functions inside the expression by the columns that will hold
+    /// their result
+    ///
+    /// The same function used twice (on the SELECT and the ORDER BY) is computed only once
+    pub fn extract(&mut self, expression: &mut Expression) -> Result<(), Tperrors> {
+        if let Expression::Aggregate {
+            function,
+            argument,
+            distinct,
+        } = expression
+        {
+            if argument.as_ref().is_some_and(|a| a.contains_aggregate()) {
+                return Err(Tperrors::Syntax(format!(
+                    "Aggregate function {} can't be used inside another one",
+                    function.name()
+                )));
+            }
+            let call = AggregateCall {
+                function: function.clone(),
+                argument: argument.as_deref().cloned(),
+                distinct: *distinct,
+            };
+            let position = match self.aggregates.iter().position(|a| *a == call) {
+                Some(position) => position,
+                None => {
+                    self.aggregates.push(call);
+                    self.aggregates.len() - 1
+                }
+            };
+            *expression = Expression::Column(self.column(position));
+            return Ok(());
+        }
+
+        for child in expression.children_mut() {
+            self.extract(child)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aggregates.is_empty()
+    }
+
+    /// Names of the columns that hold the result of every aggregate function
+    pub fn columns(&self) -> Vec<String> {
+        (0..self.aggregates.len()).map(|i| self.column(i)).collect()
+    }
+
+    /// Returns a new accumulator for each aggregate function, to be used by a group
+    pub fn accumulators(&self) -> Vec<Accumulator> {
+        self.aggregates
+            .iter()
+            .map(|call| Accumulator::new(call.function.clone(), call.distinct))
+            .collect()
+    }
+
+    /// Updates the accumulators of a group with a row
+    pub fn update(&self, accumulators: &mut [Accumulator], row: &Row) -> Result<(), Tperrors> {
+        for (call, accumulator) in self.aggregates.iter().zip(accumulators.iter_mut()) {
+            let value = match &call.argument {
+                Some(argument) => argument.evaluate(row)?,
+                // COUNT(*) counts every row
+                None => Value::Boolean(true),
+            };
+            accumulator.update(value)?;
+        }
+        Ok(())
+    }
+
+    fn column(&self, position: usize) -> String {
+        format!("{}#{}", self.aggregates[position].function.name(), position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulators_ignore_null() {
+        let values = vec![
+            Value::Integer(32),
+            Value::Null,
+            Value::Integer(28),
+            Value::Integer(32),
+        ];
+        let expected = vec![
+            (AggregateFunction::Count, false, Value::Integer(3)),
+            (AggregateFunction::Count, true, Value::Integer(2)),
+            (AggregateFunction::Sum, false, Value::Integer(92)),
+            (AggregateFunction::Sum, true, Value::Integer(60)),
+            (AggregateFunction::Min, false, Value::Integer(28)),
+            (AggregateFunction::Max, false, Value::Integer(32)),
+        ];
+
+        for (function, distinct, result) in expected {
+            let mut accumulator = Accumulator::new(function, distinct);
+            for value in &values {
+                accumulator.update(value.clone()).unwrap();
+            }
+            assert_eq!(accumulator.result(), result);
+        }
+    }
+
+    #[test]
+    fn accumulators_without_values() {
+        let count = Accumulator::new(AggregateFunction::Count, false);
+        let avg = Accumulator::new(AggregateFunction::Avg, false);
+
+        assert_eq!(count.result(), Value::Integer(0));
+        assert_eq!(avg.result(), Value::Null);
+    }
+
+    #[test]
+    fn sum_of_text_fails() {
+        let mut sum = Accumulator::new(AggregateFunction::Sum, false);
+
+        assert!(sum.update(Value::String("Juan".to_string())).is_err());
+    }
+}
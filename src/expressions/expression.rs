@@ -0,0 +1,871 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{conditions::value::Value, errors::tperrors::Tperrors};
+
+use super::{
+    aggregate::AggregateFunction,
+    functions::{CastType, ScalarFunction},
+    pattern::Pattern,
+    subquery::{Subquery, SubqueryRunner},
+    window::{WindowFunction, WindowSpec},
+};
+
+/// Operators that take a single operand
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnaryOperator {
+    Minus,
+    Not,
+}
+
+/// Operators that take two operands
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryOperator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessOrEqual,
+    GreaterOrEqual,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    Concat,
+}
+
+/// Representation of an expression already parsed
+///
+/// It is parsed once per query and then evaluated against every row
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Literal(Value),
+    Column(String),
+    Unary {
+        operator: UnaryOperator,
+        operand: Box<Expression>,
+    },
+    Binary {
+        left: Box<Expression>,
+        operator: BinaryOperator,
+        right: Box<Expression>,
+    },
+    /// ```operand [NOT] LIKE|ILIKE pattern [ESCAPE 'c']```
+    ///
+    /// If the pattern is a literal it's already compiled, else it's compiled on every row
+    Like {
+        operand: Box<Expression>,
+        pattern: Box<Expression>,
+        compiled: Option<Pattern>,
+        escape: Option<char>,
+        case_insensitive: bool,
+        negated: bool,
+    },
+    /// ```operand [NOT] IN (value, value, ...)```
+    ///
+    /// If every value of the list is a literal, their positions are stored on a hash map
+    /// by their match key, so each row is checked without going through the whole list
+    InList {
+        operand: Box<Expression>,
+        list: Vec<Expression>,
+        lookup: Option<HashMap<String, Vec<usize>>>,
+        negated: bool,
+    },
+    /// ```operand [NOT] BETWEEN low AND high```, both limits included
+    Between {
+        operand: Box<Expression>,
+        low: Box<Expression>,
+        high: Box<Expression>,
+        negated: bool,
+    },
+    /// ```operand [NOT] IN (SELECT ...)```
+    InSubquery {
+        operand: Box<Expression>,
+        subquery: Subquery,
+        negated: bool,
+    },
+    /// ```EXISTS (SELECT ...)```, true if the subquery returns any row
+    Exists(Subquery),
+    /// ```(SELECT ...)``` used as a value, the subquery must return a single column
+    /// and at most one row (NULL if there are none)
+    ScalarSubquery(Subquery),
+    /// ```operand IS [NOT] NULL```, never unknown
+    IsNull {
+        operand: Box<Expression>,
+        negated: bool,
+    },
+    /// A call to a scalar function, Example: ```UPPER(Nombre)```
+    Function {
+        function: ScalarFunction,
+        arguments: Vec<Expression>,
+    },
+    /// ```CASE [operand] WHEN condition THEN result ... [ELSE result] END```
+    ///
+    /// With an operand, each WHEN is a value compared against it
+    Case {
+        operand: Option<Box<Expression>>,
+        branches: Vec<(Expression, Expression)>,
+        otherwise: Option<Box<Expression>>,
+    },
+    /// ```CAST(operand AS type)```
+    Cast {
+        operand: Box<Expression>,
+        target: CastType,
+    },
+    /// An aggregate function, Example: ```COUNT(DISTINCT Profesion)```
+    ///
+    /// The argument is None for ```COUNT(*)```. It can't be evaluated against a single row,
+    /// it is computed over every row of a group (see Aggregation)
+    Aggregate {
+        function: AggregateFunction,
+        argument: Option<Box<Expression>>,
+        distinct: bool,
+    },
+    /// A window function, Example: ```RANK() OVER (PARTITION BY id_cliente ORDER BY cantidad)```
+    ///
+    /// Like an aggregate function, it can't be evaluated against a single row, it is computed
+    /// over the rows of its partition (see Windowing)
+    Window {
+        function: WindowFunction,
+        arguments: Vec<Expression>,
+        window: WindowSpec,
+    },
+}
+
+/// A row of a table, the names of the columns and the values of this row
+///
+/// If it is known, it also has the number of the row inside the table (used on errors)
+pub struct Row<'a> {
+    columns: &'a [String],
+    values: &'a [Value],
+    number: Option<usize>,
+}
+
+impl<'a> Row<'a> {
+    pub fn new(columns: &'a [String], values: &'a [Value]) -> Row<'a> {
+        Row {
+            columns,
+            values,
+            number: None,
+        }
+    }
+
+    /// Sets the number of the row, starting at 1 with the first row after the header
+    pub fn with_number(mut self, number: usize) -> Row<'a> {
+        self.number = Some(number);
+        self
+    }
+
+    pub fn columns(&self) -> &[String] {
+        self.columns
+    }
+
+    pub fn values(&self) -> &[Value] {
+        self.values
+    }
+
+    /// Given a column name, returns its value on this row
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        column_position(self.columns, column).and_then(|i| self.values.get(i))
+    }
+}
+
+/// Finds the position of a column by its name
+///
+/// On joins the columns are qualified with the alias of their table (```c.Nombre```), so a
+/// name without it (```Nombre```) finds the first column of any table with that name.
+/// Ambiguous names are rejected before (see validate_columns).
+pub fn column_position(columns: &[String], name: &str) -> Option<usize> {
+    match columns.iter().position(|c| c == name) {
+        Some(position) => Some(position),
+        None => columns.iter().position(|c| is_qualified_as(c, name)),
+    }
+}
+
+/// Returns true if the name is a column qualified with a table (```c.Nombre```), a word
+/// with other symbols (```test@fi.uba.ar```) is not
+pub fn is_qualified_name(name: &str) -> bool {
+    let is_word =
+        |part: &str| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_');
+    match name.split_once('.') {
+        Some((table, column)) => is_word(table) && is_word(column),
+        None => false,
+    }
+}
+
+/// Returns true if the column is the name qualified with a table (```c.Nombre``` for ```Nombre```)
+fn is_qualified_as(column: &str, name: &str) -> bool {
+    !name.contains('.')
+        && column.len() > name.len()
+        && column.ends_with(name)
+        && column[..column.len() - name.len()].ends_with('.')
+}
+
+impl Expression {
+    /// Evaluates the expression against a row
+    ///
+    /// Returns the resulting value, or an error if a column doesn't exist
+    /// or the operation can't be done (Example: ```'Juan' * 2```)
+    pub fn evaluate(&self, row: &Row) -> Result<Value, Tperrors> {
+        match self {
+            Expression::Literal(value) => Ok(value.clone()),
+            Expression::Column(name) => match row.get(name) {
+                Some(value) => Ok(value.clone()),
+                None => Err(Tperrors::Column(format!(
+                    "Invalid column {} inside the query",
+                    name
+                ))),
+            },
+            Expression::Unary { operator, operand } => {
+                let value = operand.evaluate(row)?;
+                Self::resolve_unary(operator, value)
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => match operator {
+                BinaryOperator::And | BinaryOperator::Or => {
+                    let left = Self::as_logical(left.evaluate(row)?)?;
+                    // no need to check the right side if the result is already known
+                    match (operator, left) {
+                        (BinaryOperator::And, Some(false)) => return Ok(Value::Boolean(false)),
+                        (BinaryOperator::Or, Some(true)) => return Ok(Value::Boolean(true)),
+                        _ => {}
+                    }
+                    let right = Self::as_logical(right.evaluate(row)?)?;
+                    Ok(Self::resolve_logical(operator, left, right))
+                }
+                _ => {
+                    let left = left.evaluate(row)?;
+                    let right = right.evaluate(row)?;
+                    Self::resolve_binary(operator, left, right)
+                }
+            },
+            Expression::Like {
+                operand,
+                pattern,
+                compiled,
+                escape,
+                case_insensitive,
+                negated,
+            } => {
+                let value = operand.evaluate(row)?;
+                if value.is_null() {
+                    return Ok(Value::Null);
+                }
+                let matches = match compiled {
+                    Some(compiled) => compiled.matches(&value.to_string()),
+                    None => {
+                        let pattern = pattern.evaluate(row)?;
+                        if pattern.is_null() {
+                            return Ok(Value::Null);
+                        }
+                        Pattern::compile(&pattern.to_string(), *escape, *case_insensitive)?
+                            .matches(&value.to_string())
+                    }
+                };
+                Ok(Value::Boolean(matches != *negated))
+            }
+            Expression::InList {
+                operand,
+                list,
+                lookup,
+                negated,
+            } => {
+                let value = operand.evaluate(row)?;
+                if value.is_null() {
+                    return Ok(Value::Null);
+                }
+                let mut found_null = false;
+                let found = match (lookup, value.match_key()) {
+                    (Some(lookup), Some(key)) => {
+                        found_null = list.contains(&Expression::Literal(Value::Null));
+                        // the values with the same key are only candidates ('007' and '7')
+                        lookup.get(&key).is_some_and(|positions| {
+                            positions.iter().any(|i| match &list[*i] {
+                                Expression::Literal(item) => value.equals(item),
+                                _ => false,
+                            })
+                        })
+                    }
+                    _ => {
+                        let mut found = false;
+                        for item in list {
+                            let item = item.evaluate(row)?;
+                            match value.compare(&item) {
+                                Some(ordering) if ordering.is_eq() => {
+                                    found = true;
+                                    break;
+                                }
+                                Some(_) => {}
+                                None => found_null = true,
+                            }
+                        }
+                        found
+                    }
+                };
+                // x IN (1, NULL) is unknown if x isn't 1
+                match (found, found_null) {
+                    (true, _) => Ok(Value::Boolean(!*negated)),
+                    (false, true) => Ok(Value::Null),
+                    (false, false) => Ok(Value::Boolean(*negated)),
+                }
+            }
+            Expression::Between {
+                operand,
+                low,
+                high,
+                negated,
+            } => {
+                let value = operand.evaluate(row)?;
+                let from_low = Self::resolve_binary(
+                    &BinaryOperator::GreaterOrEqual,
+                    value.clone(),
+                    low.evaluate(row)?,
+                )?;
+                let to_high =
+                    Self::resolve_binary(&BinaryOperator::LessOrEqual, value, high.evaluate(row)?)?;
+                let result = Self::resolve_logical(
+                    &BinaryOperator::And,
+                    Self::as_logical(from_low)?,
+                    Self::as_logical(to_high)?,
+                );
+                if *negated {
+                    Self::resolve_unary(&UnaryOperator::Not, result)
+                } else {
+                    Ok(result)
+                }
+            }
+            Expression::InSubquery {
+                operand,
+                subquery,
+                negated,
+            } => {
+                let value = operand.evaluate(row)?;
+                let result = subquery.result(row)?;
+                let found = match value.is_null() && !result.rows.is_empty() {
+                    true => None,
+                    false => result.contains(&value)?,
+                };
+                match found {
+                    Some(found) => Ok(Value::Boolean(found != *negated)),
+                    None => Ok(Value::Null),
+                }
+            }
+            Expression::Exists(subquery) => {
+                Ok(Value::Boolean(!subquery.result(row)?.rows.is_empty()))
+            }
+            Expression::ScalarSubquery(subquery) => subquery.result(row)?.scalar(),
+            Expression::IsNull { operand, negated } => {
+                Ok(Value::Boolean(operand.evaluate(row)?.is_null() != *negated))
+            }
+            Expression::Function {
+                function,
+                arguments,
+            } => {
+                let values = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(row))
+                    .collect::<Result<Vec<Value>, Tperrors>>()?;
+                function.apply(&values)
+            }
+            Expression::Case {
+                operand,
+                branches,
+                otherwise,
+            } => {
+                let operand = match operand {
+                    Some(operand) => Some(operand.evaluate(row)?),
+                    None => None,
+                };
+                for (condition, result) in branches {
+                    let matched = match &operand {
+                        Some(value) => value
+                            .compare(&condition.evaluate(row)?)
+                            .is_some_and(|ordering| ordering.is_eq()),
+                        None => condition.matches(row)?,
+                    };
+                    if matched {
+                        return result.evaluate(row);
+                    }
+                }
+                match otherwise {
+                    Some(otherwise) => otherwise.evaluate(row),
+                    None => Ok(Value::Null),
+                }
+            }
+            Expression::Cast { operand, target } => {
+                let value = operand.evaluate(row)?;
+                match target.cast(&value) {
+                    Some(casted) => Ok(casted),
+                    None => Err(Tperrors::Generic(format!(
+                        "Can't cast {} to {}{}",
+                        value,
+                        target.name(),
+                        Self::location(operand, row)
+                    ))),
+                }
+            }
+            Expression::Aggregate { function, .. } => Err(Tperrors::Syntax(format!(
+                "Aggregate function {} can't be used here",
+                function.name()
+            ))),
+            Expression::Window { function, .. } => Err(Tperrors::Syntax(format!(
+                "Window function {} can't be used here",
+                function.name()
+            ))),
+        }
+    }
+
+    /// Evaluates the expression as a condition
+    ///
+    /// NULL (unknown) is not a match.
+    pub fn matches(&self, row: &Row) -> Result<bool, Tperrors> {
+        Ok(Self::as_logical(self.evaluate(row)?)? == Some(true))
+    }
+
+    /// Checks that every column used by the expression exists
+    ///
+    /// Returns a Column error with the first one that doesn't, or that is ambiguous
+    /// (a name without its table that is on more than one table of a join).
+    pub fn validate_columns(&self, columns: &[String]) -> Result<(), Tperrors> {
+        for name in self.columns() {
+            if columns.contains(name) {
+                continue;
+            }
+            match columns.iter().filter(|c| is_qualified_as(c, name)).count() {
+                0 => {
+                    return Err(Tperrors::Column(format!(
+                        "Invalid column {} inside the query",
+                        name
+                    )));
+                }
+                1 => {}
+                _ => {
+                    return Err(Tperrors::Column(format!(
+                        "Column {} is ambiguous, use the name of its table (Example: t.{})",
+                        name, name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Gives every subquery inside the expression the runner of the query
+    ///
+    /// `columns` are the columns of the rows the expression will be evaluated with
+    pub fn bind_subqueries(
+        &mut self,
+        runner: &Rc<dyn SubqueryRunner>,
+        columns: &[String],
+    ) -> Result<(), Tperrors> {
+        match self {
+            Expression::InSubquery { subquery, .. }
+            | Expression::Exists(subquery)
+            | Expression::ScalarSubquery(subquery) => subquery.bind(runner, columns)?,
+            _ => {}
+        }
+        for child in self.children_mut() {
+            child.bind_subqueries(runner, columns)?;
+        }
+        Ok(())
+    }
+
+    /// Returns every subquery inside the expression
+    pub fn subqueries(&self) -> Vec<&Subquery> {
+        let mut subqueries: Vec<&Subquery> = Vec::new();
+        if let Expression::InSubquery { subquery, .. }
+        | Expression::Exists(subquery)
+        | Expression::ScalarSubquery(subquery) = self
+        {
+            subqueries.push(subquery);
+        }
+        for child in self.children() {
+            subqueries.extend(child.subqueries());
+        }
+        subqueries
+    }
+
+    /// Returns the names of every column used by the expression
+    pub fn columns(&self) -> Vec<&String> {
+        match self {
+            Expression::Column(name) => vec![name],
+            _ => self
+                .children()
+                .into_iter()
+                .flat_map(|child| child.columns())
+                .collect(),
+        }
+    }
+
+    /// Returns true if there is an aggregate function inside the expression
+    pub fn contains_aggregate(&self) -> bool {
+        match self {
+            Expression::Aggregate { .. } => true,
+            _ => self
+                .children()
+                .into_iter()
+                .any(|child| child.contains_aggregate()),
+        }
+    }
+
+    /// Returns true if there is a window function inside the expression
+    pub fn contains_window(&self) -> bool {
+        match self {
+            Expression::Window { .. } => true,
+            _ => self
+                .children()
+                .into_iter()
+                .any(|child| child.contains_window()),
+        }
+    }
+
+    /// The expressions directly inside this one
+    pub fn children(&self) -> Vec<&Expression> {
+        match self {
+            Expression::Literal(_)
+            | Expression::Column(_)
+            | Expression::Exists(_)
+            | Expression::ScalarSubquery(_) => Vec::new(),
+            Expression::Unary { operand, .. }
+            | Expression::Cast { operand, .. }
+            | Expression::IsNull { operand, .. }
+            | Expression::InSubquery { operand, .. } => vec![operand],
+            Expression::Binary { left, right, .. } => vec![left, right],
+            Expression::Like {
+                operand, pattern, ..
+            } => vec![operand, pattern],
+            Expression::InList { operand, list, .. } => {
+                let mut children = vec![operand.as_ref()];
+                children.extend(list.iter());
+                children
+            }
+            Expression::Between {
+                operand, low, high, ..
+            } => vec![operand, low, high],
+            Expression::Function { arguments, .. } => arguments.iter().collect(),
+            Expression::Case {
+                operand,
+                branches,
+                otherwise,
+            } => {
+                let mut children: Vec<&Expression> = Vec::new();
+                children.extend(operand.as_deref());
+                branches.iter().for_each(|(condition, result)| {
+                    children.push(condition);
+                    children.push(result);
+                });
+                children.extend(otherwise.as_deref());
+                children
+            }
+            Expression::Aggregate { argument, .. } => argument.as_deref().into_iter().collect(),
+            Expression::Window {
+                arguments, window, ..
+            } => arguments
+                .iter()
+                .chain(window.partition_by.iter())
+                .chain(window.order_by.iter().map(|(e, _)| e))
+                .collect(),
+        }
+    }
+
+    /// The expressions directly inside this one, so they can be replaced
+    pub fn children_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Expression::Literal(_)
+            | Expression::Column(_)
+            | Expression::Exists(_)
+            | Expression::ScalarSubquery(_) => Vec::new(),
+            Expression::Unary { operand, .. }
+            | Expression::Cast { operand, .. }
+            | Expression::IsNull { operand, .. }
+            | Expression::InSubquery { operand, .. } => vec![operand],
+            Expression::Binary { left, right, .. } => vec![left, right],
+            Expression::Like {
+                operand, pattern, ..
+            } => vec![operand, pattern],
+            Expression::InList { operand, list, .. } => {
+                let mut children = vec![operand.as_mut()];
+                children.extend(list.iter_mut());
+                children
+            }
+            Expression::Between {
+                operand, low, high, ..
+            } => vec![operand, low, high],
+            Expression::Function { arguments, .. } => arguments.iter_mut().collect(),
+            Expression::Case {
+                operand,
+                branches,
+                otherwise,
+            } => {
+                let mut children: Vec<&mut Expression> = Vec::new();
+                children.extend(operand.as_deref_mut());
+                branches.iter_mut().for_each(|(condition, result)| {
+                    children.push(condition);
+                    children.push(result);
+                });
+                children.extend(otherwise.as_deref_mut());
+                children
+            }
+            Expression::Aggregate { argument, .. } => argument.as_deref_mut().into_iter().collect(),
+            Expression::Window {
+                arguments, window, ..
+            } => arguments
+                .iter_mut()
+                .chain(window.partition_by.iter_mut())
+                .chain(window.order_by.iter_mut().map(|(e, _)| e))
+                .collect(),
+        }
+    }
+
+    /// Describes where a value came from for error messages, Example: ``` (row 3, column Edad)```
+    fn location(expression: &Expression, row: &Row) -> String {
+        let mut location: Vec<String> = Vec::new();
+        if let Some(number) = row.number {
+            location.push(format!("row {}", number));
+        }
+        if let Expression::Column(name) = expression {
+            location.push(format!("column {}", name));
+        }
+        if location.is_empty() {
+            return String::new();
+        }
+        format!(" ({})", location.join(", "))
+    }
+
+    /// Converts a value to a logical one, None means unknown (NULL)
+    fn as_logical(value: Value) -> Result<Option<bool>, Tperrors> {
+        match value {
+            Value::Boolean(b) => Ok(Some(b)),
+            Value::Integer(i) => Ok(Some(i != 0)),
+            Value::Null => Ok(None),
+            other => Err(Tperrors::Syntax(format!(
+                "Expected a condition, found the value {}",
+                other
+            ))),
+        }
+    }
+
+    /// AND / OR following the SQL three valued logic
+    fn resolve_logical(
+        operator: &BinaryOperator,
+        left: Option<bool>,
+        right: Option<bool>,
+    ) -> Value {
+        let result = match operator {
+            BinaryOperator::And => match (left, right) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            },
+            _ => match (left, right) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+        };
+        match result {
+            Some(b) => Value::Boolean(b),
+            None => Value::Null,
+        }
+    }
+
+    fn resolve_unary(operator: &UnaryOperator, value: Value) -> Result<Value, Tperrors> {
+        match (operator, value) {
+            (_, Value::Null) => Ok(Value::Null),
+            (UnaryOperator::Not, value) => match Self::as_logical(value)? {
+                Some(b) => Ok(Value::Boolean(!b)),
+                None => Ok(Value::Null),
+            },
+            (UnaryOperator::Minus, Value::Integer(i)) => Ok(Value::Integer(-i)),
+            (UnaryOperator::Minus, value) => match value.as_f64() {
+                Some(v) => Ok(Value::Real(-v)),
+                None => Err(Tperrors::Syntax(format!(
+                    "Can't negate the value {}",
+                    value
+                ))),
+            },
+        }
+    }
+
+    fn resolve_binary(
+        operator: &BinaryOperator,
+        left: Value,
+        right: Value,
+    ) -> Result<Value, Tperrors> {
+        if left.is_null() || right.is_null() {
+            return Ok(Value::Null);
+        }
+
+        match operator {
+            BinaryOperator::Concat => Ok(Value::String(format!("{}{}", left, right))),
+            BinaryOperator::Equal
+            | BinaryOperator::NotEqual
+            | BinaryOperator::Less
+            | BinaryOperator::Greater
+            | BinaryOperator::LessOrEqual
+            | BinaryOperator::GreaterOrEqual => {
+                let ordering = match left.compare(&right) {
+                    Some(ordering) => ordering,
+                    None => return Ok(Value::Null),
+                };
+                let result = match operator {
+                    BinaryOperator::Equal => ordering.is_eq(),
+                    BinaryOperator::NotEqual => ordering.is_ne(),
+                    BinaryOperator::Less => ordering.is_lt(),
+                    BinaryOperator::Greater => ordering.is_gt(),
+                    BinaryOperator::LessOrEqual => ordering.is_le(),
+                    _ => ordering.is_ge(),
+                };
+                Ok(Value::Boolean(result))
+            }
+            _ => Self::resolve_arithmetic(operator, left, right),
+        }
+    }
+
+    /// Resolves +, -, *, / and %
+    ///
+    /// Two integers give an integer (like SQL, 7 / 2 = 3), otherwise a real
+    fn resolve_arithmetic(
+        operator: &BinaryOperator,
+        left: Value,
+        right: Value,
+    ) -> Result<Value, Tperrors> {
+        if let (Value::Integer(l), Value::Integer(r)) = (&left, &right) {
+            let (l, r) = (*l, *r);
+            if r == 0 && matches!(operator, BinaryOperator::Divide | BinaryOperator::Modulo) {
+                return Err(Tperrors::Generic("Division by zero".to_string()));
+            }
+            let result = match operator {
+                BinaryOperator::Plus => l.checked_add(r),
+                BinaryOperator::Minus => l.checked_sub(r),
+                BinaryOperator::Multiply => l.checked_mul(r),
+                BinaryOperator::Divide => l.checked_div(r),
+                _ => l.checked_rem(r),
+            };
+            return match result {
+                Some(v) => Ok(Value::Integer(v)),
+                None => Err(Tperrors::Generic("Integer out of range".to_string())),
+            };
+        }
+
+        let (l, r) = match (left.as_f64(), right.as_f64()) {
+            (Some(l), Some(r)) => (l, r),
+            _ => {
+                return Err(Tperrors::Syntax(format!(
+                    "Invalid operation between {} and {}",
+                    left, right
+                )));
+            }
+        };
+        if r == 0.0 && matches!(operator, BinaryOperator::Divide | BinaryOperator::Modulo) {
+            return Err(Tperrors::Generic("Division by zero".to_string()));
+        }
+        let result = match operator {
+            BinaryOperator::Plus => l + r,
+            BinaryOperator::Minus => l - r,
+            BinaryOperator::Multiply => l * r,
+            BinaryOperator::Divide => l / r,
+            _ => l % r,
+        };
+        Ok(Value::Real(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expressions::parser::ExpressionParser;
+
+    #[test]
+    fn evaluate_arithmetic_and_concat() {
+        let columns = vec!["Nombre".to_string(), "Edad".to_string()];
+        let values = vec![Value::String("Juan".to_string()), Value::Integer(32)];
+        let row = Row::new(&columns, &values);
+
+        let months = Expression::Binary {
+            left: Box::new(Expression::Column("Edad".to_string())),
+            operator: BinaryOperator::Multiply,
+            right: Box::new(Expression::Literal(Value::Integer(12))),
+        };
+        assert_eq!(months.evaluate(&row).unwrap(), Value::Integer(384));
+
+        let greeting = Expression::Binary {
+            left: Box::new(Expression::Literal(Value::String("Hola ".to_string()))),
+            operator: BinaryOperator::Concat,
+            right: Box::new(Expression::Column("Nombre".to_string())),
+        };
+        assert_eq!(
+            greeting.evaluate(&row).unwrap(),
+            Value::String("Hola Juan".to_string())
+        );
+    }
+
+    #[test]
+    fn evaluate_in_list_and_between() {
+        let columns = vec!["Id".to_string(), "Edad".to_string()];
+        let values = vec![Value::Integer(3), Value::Integer(45)];
+        let row = Row::new(&columns, &values);
+
+        let matching = vec![
+            "Id IN (1, 3, 5)",
+            "Id IN ('3')",
+            "Id NOT IN (1, 2)",
+            "Edad BETWEEN 40 AND 50",
+            "Edad NOT BETWEEN 30 AND 40",
+            "Edad IN (Id * 15)",
+            "Id IS NOT NULL",
+            "NULL IS NULL",
+        ];
+        for condition in matching {
+            let expression = ExpressionParser::parse(condition).unwrap();
+            assert!(expression.matches(&row).unwrap(), "{}", condition);
+        }
+
+        // 3 isn't on the list, but NULL could be anything, so it's unknown
+        let unknown = ExpressionParser::parse("Id NOT IN (1, NULL)").unwrap();
+        assert_eq!(unknown.evaluate(&row).unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn evaluate_case_and_cast() {
+        let columns = vec!["Nombre".to_string(), "Edad".to_string()];
+        let values = vec![Value::String("Juan".to_string()), Value::Integer(45)];
+        let row = Row::new(&columns, &values).with_number(3);
+
+        let cases = vec![
+            ("CASE WHEN Edad > 40 THEN 'mayor' ELSE 'menor' END", "mayor"),
+            ("CASE Nombre WHEN 'Ana' THEN 1 WHEN 'Juan' THEN 2 END", "2"),
+            ("CASE WHEN Edad < 18 THEN 'menor' END", ""),
+            ("CAST(Edad AS TEXT) || '!'", "45!"),
+            ("COALESCE(NULLIF(Edad, 45), 0)", "0"),
+        ];
+        for (expression, expected) in cases {
+            let parsed = ExpressionParser::parse(expression).unwrap();
+            assert_eq!(parsed.evaluate(&row).unwrap().to_string(), expected);
+        }
+
+        let invalid_cast = ExpressionParser::parse("CAST(Nombre AS INTEGER)").unwrap();
+        match invalid_cast.evaluate(&row) {
+            Err(Tperrors::Generic(message)) => {
+                assert_eq!(message, "Can't cast Juan to INTEGER (row 3, column Nombre)")
+            }
+            other => panic!("Expected a cast error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn evaluate_unknown_column_fails() {
+        let columns = vec!["Nombre".to_string()];
+        let values = vec![Value::String("Juan".to_string())];
+        let row = Row::new(&columns, &values);
+
+        let expression = Expression::Column("Apellido".to_string());
+        assert!(expression.evaluate(&row).is_err());
+        assert!(expression.validate_columns(&columns).is_err());
+    }
+}
@@ -0,0 +1,489 @@
+use crate::{conditions::value::Value, errors::tperrors::Tperrors};
+
+/// The longest text that LPAD and RPAD can build, in characters
+const MAX_PAD_LENGTH: i64 = 65_535;
+
+/// Scalar functions, they take the values of a single row and return a single value
+///
+/// Every function works over characters (not bytes), so ```LENGTH('Lucía')``` is 5
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScalarFunction {
+    Upper,
+    Lower,
+    Length,
+    Substr,
+    Trim,
+    Ltrim,
+    Rtrim,
+    Replace,
+    Concat,
+    Instr,
+    Lpad,
+    Rpad,
+    Coalesce,
+    Nullif,
+    Ifnull,
+}
+
+impl ScalarFunction {
+    /// Given the name of a function, returns it if it exists
+    ///
+    /// The name is not case sensitive, Example: ```upper``` and ```UPPER``` are the same
+    pub fn from_name(name: &str) -> Option<ScalarFunction> {
+        match name.to_uppercase().as_str() {
+            "UPPER" => Some(ScalarFunction::Upper),
+            "LOWER" => Some(ScalarFunction::Lower),
+            "LENGTH" => Some(ScalarFunction::Length),
+            "SUBSTR" | "SUBSTRING" => Some(ScalarFunction::Substr),
+            "TRIM" => Some(ScalarFunction::Trim),
+            "LTRIM" => Some(ScalarFunction::Ltrim),
+            "RTRIM" => Some(ScalarFunction::Rtrim),
+            "REPLACE" => Some(ScalarFunction::Replace),
+            "CONCAT" => Some(ScalarFunction::Concat),
+            "INSTR" => Some(ScalarFunction::Instr),
+            "LPAD" => Some(ScalarFunction::Lpad),
+            "RPAD" => Some(ScalarFunction::Rpad),
+            "COALESCE" => Some(ScalarFunction::Coalesce),
+            "NULLIF" => Some(ScalarFunction::Nullif),
+            "IFNULL" => Some(ScalarFunction::Ifnull),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            ScalarFunction::Upper => "UPPER",
+            ScalarFunction::Lower => "LOWER",
+            ScalarFunction::Length => "LENGTH",
+            ScalarFunction::Substr => "SUBSTR",
+            ScalarFunction::Trim => "TRIM",
+            ScalarFunction::Ltrim => "LTRIM",
+            ScalarFunction::Rtrim => "RTRIM",
+            ScalarFunction::Replace => "REPLACE",
+            ScalarFunction::Concat => "CONCAT",
+            ScalarFunction::Instr => "INSTR",
+            ScalarFunction::Lpad => "LPAD",
+            ScalarFunction::Rpad => "RPAD",
+            ScalarFunction::Coalesce => "COALESCE",
+            ScalarFunction::Nullif => "NULLIF",
+            ScalarFunction::Ifnull => "IFNULL",
+        }
+    }
+
+    /// Checks the amount of arguments the function was called with
+    ///
+    /// It's done while parsing, so a wrong call fails before reading the table
+    pub fn check_arguments(&self, amount: usize) -> Result<(), Tperrors> {
+        let (min, max) = match self {
+            ScalarFunction::Upper | ScalarFunction::Lower | ScalarFunction::Length => (1, Some(1)),
+            ScalarFunction::Trim | ScalarFunction::Ltrim | ScalarFunction::Rtrim => (1, Some(2)),
+            ScalarFunction::Substr => (2, Some(3)),
+            ScalarFunction::Replace => (3, Some(3)),
+            ScalarFunction::Instr | ScalarFunction::Nullif | ScalarFunction::Ifnull => (2, Some(2)),
+            ScalarFunction::Lpad | ScalarFunction::Rpad => (2, Some(3)),
+            ScalarFunction::Concat | ScalarFunction::Coalesce => (1, None),
+        };
+        if amount < min || max.is_some_and(|max| amount > max) {
+            return Err(Tperrors::Syntax(format!(
+                "Invalid amount of arguments for {}, found {}",
+                self.name(),
+                amount
+            )));
+        }
+        Ok(())
+    }
+
+    /// Applies the function to the arguments already evaluated
+    ///
+    /// Like in SQL, if an argument is NULL the result is NULL (except for CONCAT,
+    /// that ignores them, and the functions made to handle NULL)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tp_individual::conditions::value::Value;
+    /// use tp_individual::expressions::functions::ScalarFunction;
+    ///
+    /// let name = Value::String("Sánchez".to_string());
+    /// let upper = ScalarFunction::Upper.apply(&[name]).unwrap();
+    /// assert_eq!(upper, Value::String("SÁNCHEZ".to_string()));
+    /// ```
+    pub fn apply(&self, arguments: &[Value]) -> Result<Value, Tperrors> {
+        match self {
+            ScalarFunction::Coalesce | ScalarFunction::Ifnull => {
+                let first = arguments.iter().find(|argument| !argument.is_null());
+                return Ok(first.cloned().unwrap_or(Value::Null));
+            }
+            ScalarFunction::Nullif => {
+                return match arguments[0].compare(&arguments[1]) {
+                    Some(ordering) if ordering.is_eq() => Ok(Value::Null),
+                    _ => Ok(arguments[0].clone()),
+                };
+            }
+            ScalarFunction::Concat => {}
+            _ => {
+                if arguments.iter().any(|argument| argument.is_null()) {
+                    return Ok(Value::Null);
+                }
+            }
+        }
+
+        let text = arguments[0].to_string();
+        let result = match self {
+            ScalarFunction::Upper => Value::String(text.to_uppercase()),
+            ScalarFunction::Lower => Value::String(text.to_lowercase()),
+            ScalarFunction::Length => Value::Integer(text.chars().count() as i64),
+            ScalarFunction::Substr => {
+                let start = self.integer_argument(&arguments[1])?;
+                let length = match arguments.get(2) {
+                    Some(length) => Some(self.integer_argument(length)?),
+                    None => None,
+                };
+                Value::String(Self::substring(&text, start, length)?)
+            }
+            ScalarFunction::Trim | ScalarFunction::Ltrim | ScalarFunction::Rtrim => {
+                let characters = match arguments.get(1) {
+                    Some(characters) => characters.to_string().chars().collect::<Vec<char>>(),
+                    None => vec![' '],
+                };
+                let is_trimmed = |c: char| characters.contains(&c);
+                let trimmed = match self {
+                    ScalarFunction::Trim => text.trim_matches(is_trimmed),
+                    ScalarFunction::Ltrim => text.trim_start_matches(is_trimmed),
+                    _ => text.trim_end_matches(is_trimmed),
+                };
+                Value::String(trimmed.to_string())
+            }
+            ScalarFunction::Replace => {
+                let from = arguments[1].to_string();
+                if from.is_empty() {
+                    Value::String(text)
+                } else {
+                    Value::String(text.replace(&from, &arguments[2].to_string()))
+                }
+            }
+            ScalarFunction::Instr => {
+                let searched = arguments[1].to_string();
+                // position counted in characters and starting at 1, 0 if it isn't found
+                let position = match text.find(&searched) {
+                    Some(byte_position) => text[..byte_position].chars().count() as i64 + 1,
+                    None => 0,
+                };
+                Value::Integer(position)
+            }
+            ScalarFunction::Lpad | ScalarFunction::Rpad => {
+                let length = self.integer_argument(&arguments[1])?;
+                let fill = match arguments.get(2) {
+                    Some(fill) => fill.to_string(),
+                    None => " ".to_string(),
+                };
+                Value::String(self.pad(&text, length, &fill)?)
+            }
+            // NULL is shown as an empty text, so they are ignored
+            ScalarFunction::Concat => Value::String(
+                arguments
+                    .iter()
+                    .map(|argument| argument.to_string())
+                    .collect::<String>(),
+            ),
+            ScalarFunction::Coalesce | ScalarFunction::Nullif | ScalarFunction::Ifnull => {
+                arguments[0].clone()
+            }
+        };
+        Ok(result)
+    }
+
+    /// ```SUBSTR(text, start, length)```, positions start at 1
+    ///
+    /// Positions outside the text are ignored, so ```SUBSTR('Ana', 0, 2)``` is ```A```
+    fn substring(text: &str, start: i64, length: Option<i64>) -> Result<String, Tperrors> {
+        let end = match length {
+            Some(length) if length < 0 => {
+                return Err(Tperrors::Syntax(format!(
+                    "SUBSTR length can't be negative, found {}",
+                    length
+                )));
+            }
+            Some(length) => start.saturating_add(length),
+            None => i64::MAX,
+        };
+        Ok(text
+            .chars()
+            .enumerate()
+            .filter(|(i, _)| {
+                let position = *i as i64 + 1;
+                position >= start && position < end
+            })
+            .map(|(_, c)| c)
+            .collect())
+    }
+
+    /// Fills the text until it has the length given, cutting it if it's longer
+    ///
+    /// The length can't be longer than MAX_PAD_LENGTH, so a typo can't fill the memory
+    fn pad(&self, text: &str, length: i64, fill: &str) -> Result<String, Tperrors> {
+        if length > MAX_PAD_LENGTH {
+            return Err(Tperrors::Syntax(format!(
+                "{} length can't be greater than {}, found {}",
+                self.name(),
+                MAX_PAD_LENGTH,
+                length
+            )));
+        }
+        let length = length.max(0) as usize;
+        let current = text.chars().count();
+        if current >= length || fill.is_empty() {
+            return Ok(text.chars().take(length).collect());
+        }
+        let padding = fill
+            .chars()
+            .cycle()
+            .take(length - current)
+            .collect::<String>();
+        match self {
+            ScalarFunction::Lpad => Ok(format!("{}{}", padding, text)),
+            _ => Ok(format!("{}{}", text, padding)),
+        }
+    }
+
+    fn integer_argument(&self, value: &Value) -> Result<i64, Tperrors> {
+        let integer = match value {
+            Value::Integer(v) => Some(*v),
+            Value::Real(v) if v.fract() == 0.0 => Some(*v as i64),
+            Value::String(s) => s.trim().parse::<i64>().ok(),
+            _ => None,
+        };
+        match integer {
+            Some(v) => Ok(v),
+            None => Err(Tperrors::Syntax(format!(
+                "{} expected an integer, found {}",
+                self.name(),
+                value
+            ))),
+        }
+    }
+}
+
+/// Types a value can be converted to with ```CAST(value AS type)```
+#[derive(Debug, Clone, PartialEq)]
+pub enum CastType {
+    Integer,
+    Real,
+    Text,
+    /// There is no date value, dates are texts written as ```YYYY-MM-DD```
+    /// so they can be compared and sorted as they are
+    Date,
+}
+
+impl CastType {
+    pub fn from_name(name: &str) -> Option<CastType> {
+        match name.to_uppercase().as_str() {
+            "INTEGER" | "INT" => Some(CastType::Integer),
+            "REAL" | "FLOAT" => Some(CastType::Real),
+            "TEXT" | "VARCHAR" => Some(CastType::Text),
+            "DATE" => Some(CastType::Date),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            CastType::Integer => "INTEGER",
+            CastType::Real => "REAL",
+            CastType::Text => "TEXT",
+            CastType::Date => "DATE",
+        }
+    }
+
+    /// Converts the value to this type
+    ///
+    /// Returns None if it can't be done, Example: ```CAST('Juan' AS INTEGER)```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tp_individual::conditions::value::Value;
+    /// use tp_individual::expressions::functions::CastType;
+    ///
+    /// let age = Value::String("45".to_string());
+    /// assert_eq!(CastType::Integer.cast(&age), Some(Value::Integer(45)));
+    /// assert_eq!(CastType::Integer.cast(&Value::String("Juan".to_string())), None);
+    /// ```
+    pub fn cast(&self, value: &Value) -> Option<Value> {
+        if value.is_null() {
+            return Some(Value::Null);
+        }
+        match (self, value) {
+            (CastType::Text, value) => Some(Value::String(value.to_string())),
+            (CastType::Integer, Value::Integer(v)) => Some(Value::Integer(*v)),
+            (CastType::Integer, Value::Boolean(b)) => Some(Value::Integer(*b as i64)),
+            (CastType::Real, Value::Boolean(b)) => Some(Value::Real(*b as i64 as f64)),
+            (CastType::Integer, value) => match value.to_string().trim().parse::<i64>() {
+                Ok(v) => Some(Value::Integer(v)),
+                // decimals are truncated, like converting 4.7 gives 4
+                Err(_) => value
+                    .as_f64()
+                    .filter(|v| *v >= i64::MIN as f64 && *v <= i64::MAX as f64)
+                    .map(|v| Value::Integer(v.trunc() as i64)),
+            },
+            (CastType::Real, value) => value.as_f64().map(Value::Real),
+            (CastType::Date, Value::String(text)) => Self::parse_date(text),
+            (CastType::Date, _) => None,
+        }
+    }
+
+    /// Given a date as ```YYYY-MM-DD``` returns it with every part padded with zeros,
+    /// or None if it isn't a valid date
+    fn parse_date(text: &str) -> Option<Value> {
+        let parts = text.trim().split('-').collect::<Vec<&str>>();
+        if parts.len() != 3 || parts.iter().any(|p| p.is_empty() || p.len() > 4) {
+            return None;
+        }
+        let year = parts[0].parse::<u32>().ok()?;
+        let month = parts[1].parse::<u32>().ok()?;
+        let day = parts[2].parse::<u32>().ok()?;
+
+        let leap_year =
+            year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap_year => 29,
+            2 => 28,
+            _ => return None,
+        };
+        if day == 0 || day > days_in_month {
+            return None;
+        }
+        Some(Value::String(format!(
+            "{:04}-{:02}-{:02}",
+            year, month, day
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[test]
+    fn functions_over_utf8_text() {
+        let cases = vec![
+            (
+                ScalarFunction::Length,
+                vec![text("Lucía")],
+                Value::Integer(5),
+            ),
+            (
+                ScalarFunction::Substr,
+                vec![text("Sánchez"), Value::Integer(2), Value::Integer(3)],
+                text("ánc"),
+            ),
+            (
+                ScalarFunction::Instr,
+                vec![text("Martínez"), text("nez")],
+                Value::Integer(6),
+            ),
+            (
+                ScalarFunction::Lpad,
+                vec![text("Lucía"), Value::Integer(8), text("*")],
+                text("***Lucía"),
+            ),
+            (
+                ScalarFunction::Rpad,
+                vec![text("Domínguez"), Value::Integer(3)],
+                text("Dom"),
+            ),
+            (
+                ScalarFunction::Trim,
+                vec![text("..Ana.."), text(".")],
+                text("Ana"),
+            ),
+            (
+                ScalarFunction::Replace,
+                vec![text("Fernández"), text("á"), text("a")],
+                text("Fernandez"),
+            ),
+        ];
+
+        for (function, arguments, expected) in cases {
+            assert_eq!(function.apply(&arguments).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn functions_with_null_arguments() {
+        assert_eq!(
+            ScalarFunction::Upper.apply(&[Value::Null]).unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            ScalarFunction::Concat
+                .apply(&[text("Ana"), Value::Null, Value::Integer(36)])
+                .unwrap(),
+            text("Ana36")
+        );
+    }
+
+    #[test]
+    fn functions_that_handle_null() {
+        let arguments = vec![Value::Null, Value::Null, text("sin correo")];
+        assert_eq!(
+            ScalarFunction::Coalesce.apply(&arguments).unwrap(),
+            text("sin correo")
+        );
+        assert_eq!(
+            ScalarFunction::Nullif
+                .apply(&[Value::Integer(32), text("32")])
+                .unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            ScalarFunction::Ifnull
+                .apply(&[Value::Integer(32), Value::Integer(0)])
+                .unwrap(),
+            Value::Integer(32)
+        );
+    }
+
+    #[test]
+    fn cast_values() {
+        assert_eq!(
+            CastType::Integer.cast(&Value::Real(4.7)),
+            Some(Value::Integer(4))
+        );
+        assert_eq!(CastType::Real.cast(&text("31.5")), Some(Value::Real(31.5)));
+        assert_eq!(CastType::Text.cast(&Value::Integer(7)), Some(text("7")));
+        assert_eq!(
+            CastType::Date.cast(&text("2024-2-29")),
+            Some(text("2024-02-29"))
+        );
+        assert_eq!(CastType::Date.cast(&text("2023-02-29")), None);
+        assert_eq!(CastType::Real.cast(&text("treinta")), None);
+    }
+
+    #[test]
+    fn functions_with_invalid_arguments_fails() {
+        assert!(ScalarFunction::Replace.check_arguments(2).is_err());
+        assert!(ScalarFunction::Concat.check_arguments(0).is_err());
+        assert!(ScalarFunction::Substr
+            .apply(&[text("Ana"), text("uno")])
+            .is_err());
+    }
+
+    #[test]
+    fn pad_longer_than_the_maximum_fails() {
+        let result =
+            ScalarFunction::Lpad.apply(&[text("Juan"), Value::Integer(99_999_999_999), text("x")]);
+        assert!(matches!(result, Err(Tperrors::Syntax(message)) if message.contains("LPAD")));
+
+        let padded = ScalarFunction::Rpad
+            .apply(&[text("Juan"), Value::Integer(MAX_PAD_LENGTH), text("x")])
+            .unwrap();
+        assert_eq!(padded.to_string().chars().count(), MAX_PAD_LENGTH as usize);
+    }
+}
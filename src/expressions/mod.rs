@@ -0,0 +1,9 @@
+pub mod aggregate;
+pub mod expression;
+pub mod functions;
+pub mod parser;
+pub mod pattern;
+pub mod projection;
+pub mod subquery;
+pub mod tokenizer;
+pub mod window;
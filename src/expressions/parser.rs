@@ -0,0 +1,951 @@
+use std::collections::HashMap;
+
+use crate::{conditions::value::Value, errors::tperrors::Tperrors};
+
+use super::{
+    aggregate::AggregateFunction,
+    expression::{BinaryOperator, Expression, UnaryOperator},
+    functions::{CastType, ScalarFunction},
+    pattern::Pattern,
+    subquery::Subquery,
+    tokenizer::{is_reserved, Token, Tokenizer},
+    window::{FrameBound, WindowFrame, WindowFunction, WindowSpec},
+};
+
+/// Recursive descent parser of expressions
+///
+/// Precedence, from the lowest to the highest:
+///
+/// ```OR```, ```AND```, ```NOT```, comparisons, ```||```, ```+ -```, ```* / %```, unary ```-```
+pub struct ExpressionParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl ExpressionParser {
+    pub fn new(tokens: Vec<Token>) -> ExpressionParser {
+        ExpressionParser {
+            tokens,
+            position: 0,
+        }
+    }
+
+    /// Given an expression as str, returns it parsed
+    ///
+    /// The whole text must be a single expression, else a Syntax error is returned
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tp_individual::expressions::parser::ExpressionParser;
+    ///
+    /// assert!(ExpressionParser::parse("Edad * 12").is_ok());
+    /// assert!(ExpressionParser::parse("Edad = 45 AND").is_err());
+    /// ```
+    pub fn parse(expression: &str) -> Result<Expression, Tperrors> {
+        let tokens = Tokenizer::new().tokenize(expression)?;
+        let mut parser = ExpressionParser::new(tokens);
+        let parsed = parser.parse_expression()?;
+        parser.expect_end()?;
+        Ok(parsed)
+    }
+
+    /// Parses the next expression, leaving the cursor on the first token that isn't part of it
+    pub fn parse_expression(&mut self) -> Result<Expression, Tperrors> {
+        self.parse_or()
+    }
+
+    /// Returns the next token without consuming it
+    pub fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Consumes and returns the next token
+    pub fn next_token(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        if token.is_some() {
+            self.position += 1;
+        }
+        token
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    /// Returns an error if there are tokens left
+    pub fn expect_end(&self) -> Result<(), Tperrors> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => Err(Tperrors::Syntax(format!(
+                "Unexpected {} inside the expression",
+                Self::describe(token)
+            ))),
+        }
+    }
+
+    /// Consumes the next token if it is the keyword given
+    ///
+    /// Returns true if it was consumed
+    pub fn consume_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(token) if token.is_keyword(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, Tperrors> {
+        let mut left = self.parse_and()?;
+        while self.consume_keyword("OR") {
+            let right = self.parse_and()?;
+            left = Self::binary(left, BinaryOperator::Or, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, Tperrors> {
+        let mut left = self.parse_not()?;
+        while self.consume_keyword("AND") {
+            let right = self.parse_not()?;
+            left = Self::binary(left, BinaryOperator::And, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expression, Tperrors> {
+        if self.consume_keyword("NOT") {
+            let operand = self.parse_not()?;
+            return Ok(Expression::Unary {
+                operator: UnaryOperator::Not,
+                operand: Box::new(operand),
+            });
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expression, Tperrors> {
+        let left = self.parse_concat()?;
+
+        // NOT LIKE / NOT IN / NOT BETWEEN, the NOT belongs to the operator and not to the next condition
+        let negated = self.peek().is_some_and(|t| t.is_keyword("NOT"))
+            && self.tokens.get(self.position + 1).is_some_and(|t| {
+                ["LIKE", "ILIKE", "IN", "BETWEEN"]
+                    .iter()
+                    .any(|keyword| t.is_keyword(keyword))
+            });
+        if negated {
+            self.position += 1;
+        }
+        if self.consume_keyword("LIKE") {
+            return self.parse_like(left, false, negated);
+        }
+        if self.consume_keyword("ILIKE") {
+            return self.parse_like(left, true, negated);
+        }
+        if self.consume_keyword("IN") {
+            return self.parse_in_list(left, negated);
+        }
+        if !negated && self.consume_keyword("IS") {
+            let negated = self.consume_keyword("NOT");
+            if !self.consume_keyword("NULL") {
+                return Err(Tperrors::Syntax(
+                    "IS must be followed by NULL or NOT NULL".to_string(),
+                ));
+            }
+            return Ok(Expression::IsNull {
+                operand: Box::new(left),
+                negated,
+            });
+        }
+        if self.consume_keyword("BETWEEN") {
+            // the AND here belongs to the BETWEEN, so limits can't be conditions
+            let low = self.parse_concat()?;
+            if !self.consume_keyword("AND") {
+                return Err(Tperrors::Syntax(
+                    "BETWEEN must be followed by <value> AND <value>".to_string(),
+                ));
+            }
+            let high = self.parse_concat()?;
+            return Ok(Expression::Between {
+                operand: Box::new(left),
+                low: Box::new(low),
+                high: Box::new(high),
+                negated,
+            });
+        }
+
+        let operator = match self.peek() {
+            Some(Token::Operator(op)) => match op.as_str() {
+                "=" => BinaryOperator::Equal,
+                "!=" | "<>" => BinaryOperator::NotEqual,
+                "<" => BinaryOperator::Less,
+                ">" => BinaryOperator::Greater,
+                "<=" => BinaryOperator::LessOrEqual,
+                ">=" => BinaryOperator::GreaterOrEqual,
+                _ => return Ok(left),
+            },
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.parse_concat()?;
+        Ok(Self::binary(left, operator, right))
+    }
+
+    /// Parses the pattern (and the ESCAPE character) after a LIKE / ILIKE
+    ///
+    /// Literal patterns are compiled here, so it's done only once per query
+    fn parse_like(
+        &mut self,
+        operand: Expression,
+        case_insensitive: bool,
+        negated: bool,
+    ) -> Result<Expression, Tperrors> {
+        let pattern = self.parse_concat()?;
+
+        let escape = if self.consume_keyword("ESCAPE") {
+            match self.next_token() {
+                Some(Token::Text(text)) if text.chars().count() == 1 => text.chars().next(),
+                _ => {
+                    return Err(Tperrors::Syntax(
+                        "ESCAPE must be followed by a single character between quotes".to_string(),
+                    ));
+                }
+            }
+        } else {
+            None
+        };
+
+        let compiled = match &pattern {
+            Expression::Literal(Value::String(text)) => {
+                Some(Pattern::compile(text, escape, case_insensitive)?)
+            }
+            _ => None,
+        };
+
+        Ok(Expression::Like {
+            operand: Box::new(operand),
+            pattern: Box::new(pattern),
+            compiled,
+            escape,
+            case_insensitive,
+            negated,
+        })
+    }
+
+    /// Parses the list of values after an IN
+    ///
+    /// If all of them are literals, a hash set is built to check them
+    fn parse_in_list(
+        &mut self,
+        operand: Expression,
+        negated: bool,
+    ) -> Result<Expression, Tperrors> {
+        if self.next_token() != Some(Token::OpenParenthesis) {
+            return Err(Tperrors::Syntax(
+                "IN must be followed by a list between parenthesis".to_string(),
+            ));
+        }
+        if self.starts_subquery() {
+            return Ok(Expression::InSubquery {
+                operand: Box::new(operand),
+                subquery: self.parse_subquery()?,
+                negated,
+            });
+        }
+        let list = self.parse_list()?;
+        if list.is_empty() {
+            return Err(Tperrors::Syntax("IN list can't be empty".to_string()));
+        }
+
+        let lookup = if list.iter().all(|e| matches!(e, Expression::Literal(_))) {
+            let mut lookup: HashMap<String, Vec<usize>> = HashMap::new();
+            for (i, e) in list.iter().enumerate() {
+                if let Expression::Literal(value) = e {
+                    if let Some(key) = value.match_key() {
+                        lookup.entry(key).or_default().push(i);
+                    }
+                }
+            }
+            Some(lookup)
+        } else {
+            None
+        };
+
+        Ok(Expression::InList {
+            operand: Box::new(operand),
+            list,
+            lookup,
+            negated,
+        })
+    }
+
+    /// Parses expressions separated by commas until the closing parenthesis (consumed too)
+    fn parse_list(&mut self) -> Result<Vec<Expression>, Tperrors> {
+        let mut list: Vec<Expression> = Vec::new();
+        if self.peek() == Some(&Token::CloseParenthesis) {
+            self.position += 1;
+            return Ok(list);
+        }
+        loop {
+            list.push(self.parse_expression()?);
+            match self.next_token() {
+                Some(Token::Comma) => {}
+                Some(Token::CloseParenthesis) => return Ok(list),
+                _ => return Err(Tperrors::Syntax("Missing closing parenthesis".to_string())),
+            }
+        }
+    }
+
+    fn parse_concat(&mut self) -> Result<Expression, Tperrors> {
+        let mut left = self.parse_additive()?;
+        while self.consume_operator(&["||"]).is_some() {
+            let right = self.parse_additive()?;
+            left = Self::binary(left, BinaryOperator::Concat, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expression, Tperrors> {
+        let mut left = self.parse_multiplicative()?;
+        while let Some(op) = self.consume_operator(&["+", "-"]) {
+            let operator = match op.as_str() {
+                "+" => BinaryOperator::Plus,
+                _ => BinaryOperator::Minus,
+            };
+            let right = self.parse_multiplicative()?;
+            left = Self::binary(left, operator, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expression, Tperrors> {
+        let mut left = self.parse_unary()?;
+        while let Some(op) = self.consume_operator(&["*", "/", "%"]) {
+            let operator = match op.as_str() {
+                "*" => BinaryOperator::Multiply,
+                "/" => BinaryOperator::Divide,
+                _ => BinaryOperator::Modulo,
+            };
+            let right = self.parse_unary()?;
+            left = Self::binary(left, operator, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, Tperrors> {
+        match self.consume_operator(&["-", "+"]) {
+            Some(op) if op == "-" => {
+                let operand = self.parse_unary()?;
+                Ok(Expression::Unary {
+                    operator: UnaryOperator::Minus,
+                    operand: Box::new(operand),
+                })
+            }
+            Some(_) => self.parse_unary(),
+            None => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, Tperrors> {
+        let token = match self.next_token() {
+            Some(token) => token,
+            None => return Err(Tperrors::Syntax("Condition incomplete".to_string())),
+        };
+
+        match token {
+            Token::Number(number) => match number.parse::<i64>() {
+                Ok(v) => Ok(Expression::Literal(Value::Integer(v))),
+                Err(_) => match number.parse::<f64>() {
+                    Ok(v) => Ok(Expression::Literal(Value::Real(v))),
+                    Err(_) => Err(Tperrors::Syntax(format!("Invalid number {}", number))),
+                },
+            },
+            Token::Text(text) => Ok(Expression::Literal(Value::String(text))),
+            Token::QuotedIdentifier(name) => Ok(Expression::Column(name)),
+            Token::OpenParenthesis if self.starts_subquery() => {
+                Ok(Expression::ScalarSubquery(self.parse_subquery()?))
+            }
+            Token::OpenParenthesis => {
+                let inner = self.parse_expression()?;
+                match self.next_token() {
+                    Some(Token::CloseParenthesis) => Ok(inner),
+                    _ => Err(Tperrors::Syntax("Missing closing parenthesis".to_string())),
+                }
+            }
+            Token::Word(word) => self.parse_word(word),
+            other => Err(Tperrors::Syntax(format!(
+                "Unexpected {} inside the expression",
+                Self::describe(&other)
+            ))),
+        }
+    }
+
+    /// Parses a literal keyword (NULL, TRUE, FALSE), a function call or a column name
+    fn parse_word(&mut self, word: String) -> Result<Expression, Tperrors> {
+        match word.as_str() {
+            "NULL" => return Ok(Expression::Literal(Value::Null)),
+            "TRUE" => return Ok(Expression::Literal(Value::Boolean(true))),
+            "FALSE" => return Ok(Expression::Literal(Value::Boolean(false))),
+            "CASE" => return self.parse_case(),
+            "EXISTS" => {
+                if self.next_token() != Some(Token::OpenParenthesis) || !self.starts_subquery() {
+                    return Err(Tperrors::Syntax(
+                        "EXISTS must be followed by a SELECT between parenthesis".to_string(),
+                    ));
+                }
+                return Ok(Expression::Exists(self.parse_subquery()?));
+            }
+            _ => {}
+        }
+        if is_reserved(&word) {
+            return Err(Tperrors::Syntax(format!(
+                "Unexpected {} inside the expression",
+                word
+            )));
+        }
+
+        if self.peek() == Some(&Token::OpenParenthesis) {
+            self.position += 1;
+            return self.parse_function(&word);
+        }
+
+        // column names can have spaces (Correo electronico)
+        let mut name = word;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Word(next) if token.is_plain_word() => {
+                    name = format!("{} {}", name, next);
+                    self.position += 1;
+                }
+                _ => break,
+            }
+        }
+        Ok(Expression::Column(name))
+    }
+
+    /// Returns true if the next token starts a query (SELECT, or WITH before it)
+    fn starts_subquery(&self) -> bool {
+        self.peek()
+            .is_some_and(|t| t.is_keyword("SELECT") || t.is_keyword("WITH"))
+    }
+
+    /// Takes the SELECT of a subquery until its closing parenthesis (consumed too),
+    /// the open parenthesis is already consumed
+    ///
+    /// The query is kept as text, it's parsed when it's run
+    fn parse_subquery(&mut self) -> Result<Subquery, Tperrors> {
+        let mut depth = 0;
+        let mut words: Vec<String> = Vec::new();
+        loop {
+            match self.next_token() {
+                Some(Token::CloseParenthesis) if depth == 0 => break,
+                Some(token) => {
+                    match token {
+                        Token::OpenParenthesis => depth += 1,
+                        Token::CloseParenthesis => depth -= 1,
+                        _ => {}
+                    }
+                    words.push(token.to_sql());
+                }
+                None => {
+                    return Err(Tperrors::Syntax(
+                        "Missing closing parenthesis after the subquery".to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(Subquery::new(format!("{};", words.join(" "))))
+    }
+
+    /// Parses the arguments of a function, the open parenthesis is already consumed
+    fn parse_function(&mut self, name: &str) -> Result<Expression, Tperrors> {
+        if name.eq_ignore_ascii_case("CAST") {
+            return self.parse_cast();
+        }
+        if let Some(function) = AggregateFunction::from_name(name) {
+            let aggregate = self.parse_aggregate(function)?;
+            if self.consume_keyword("OVER") {
+                return self.parse_aggregate_window(aggregate);
+            }
+            return Ok(aggregate);
+        }
+        if let Some(function) = WindowFunction::from_name(name) {
+            let arguments = self.parse_list()?;
+            function.check_arguments(arguments.len())?;
+            if !self.consume_keyword("OVER") {
+                return Err(Tperrors::Syntax(format!(
+                    "Window function {} must be followed by OVER (...)",
+                    function.name()
+                )));
+            }
+            return self.parse_window(function, arguments);
+        }
+        let function = match ScalarFunction::from_name(name) {
+            Some(function) => function,
+            None => {
+                return Err(Tperrors::Syntax(format!(
+                    "Unknown function {} inside the expression",
+                    name
+                )));
+            }
+        };
+        let arguments = self.parse_list()?;
+        function.check_arguments(arguments.len())?;
+
+        Ok(Expression::Function {
+            function,
+            arguments,
+        })
+    }
+
+    /// Parses ```FUNCTION([DISTINCT] argument)``` or ```COUNT(*)```, the open parenthesis
+    /// is already consumed
+    fn parse_aggregate(&mut self, function: AggregateFunction) -> Result<Expression, Tperrors> {
+        let distinct = self.consume_keyword("DISTINCT");
+
+        let argument = match self.peek() {
+            Some(Token::Operator(op)) if op == "*" => {
+                if function != AggregateFunction::Count || distinct {
+                    return Err(Tperrors::Syntax(format!(
+                        "{}(*) is not valid, only COUNT(*) is",
+                        function.name()
+                    )));
+                }
+                self.position += 1;
+                None
+            }
+            _ => Some(Box::new(self.parse_expression()?)),
+        };
+        match self.next_token() {
+            Some(Token::CloseParenthesis) => Ok(Expression::Aggregate {
+                function,
+                argument,
+                distinct,
+            }),
+            _ => Err(Tperrors::Syntax(format!(
+                "{} takes a single argument",
+                function.name()
+            ))),
+        }
+    }
+
+    /// Turns an aggregate function followed by OVER into a window function
+    fn parse_aggregate_window(&mut self, aggregate: Expression) -> Result<Expression, Tperrors> {
+        match aggregate {
+            Expression::Aggregate {
+                function,
+                distinct: true,
+                ..
+            } => Err(Tperrors::Syntax(format!(
+                "DISTINCT can't be used on {} with OVER",
+                function.name()
+            ))),
+            Expression::Aggregate {
+                function, argument, ..
+            } => {
+                let arguments = argument.map(|a| vec![*a]).unwrap_or_default();
+                self.parse_window(WindowFunction::Aggregate(function), arguments)
+            }
+            other => Ok(other),
+        }
+    }
+
+    /// Parses ```([PARTITION BY ...] [ORDER BY ...] [ROWS ...])```, OVER is already consumed
+    fn parse_window(
+        &mut self,
+        function: WindowFunction,
+        arguments: Vec<Expression>,
+    ) -> Result<Expression, Tperrors> {
+        if self.next_token() != Some(Token::OpenParenthesis) {
+            return Err(Tperrors::Syntax(format!(
+                "OVER of {} must be followed by parenthesis",
+                function.name()
+            )));
+        }
+        let mut window = WindowSpec::default();
+
+        if self.consume_keyword("PARTITION") {
+            self.expect_keyword("BY", "PARTITION")?;
+            loop {
+                window.partition_by.push(self.parse_expression()?);
+                if self.peek() != Some(&Token::Comma) {
+                    break;
+                }
+                self.position += 1;
+            }
+        }
+
+        if self.consume_keyword("ORDER") {
+            self.expect_keyword("BY", "ORDER")?;
+            loop {
+                let expression = self.parse_expression()?;
+                let ascending = !self.consume_keyword("DESC");
+                if ascending {
+                    self.consume_keyword("ASC");
+                }
+                window.order_by.push((expression, ascending));
+                if self.peek() != Some(&Token::Comma) {
+                    break;
+                }
+                self.position += 1;
+            }
+        }
+
+        if self.consume_keyword("ROWS") {
+            if !matches!(function, WindowFunction::Aggregate(_)) {
+                return Err(Tperrors::Syntax(format!(
+                    "ROWS can't be used on {}, only on aggregate functions",
+                    function.name()
+                )));
+            }
+            window.frame = Some(if self.consume_keyword("BETWEEN") {
+                let start = self.parse_frame_bound()?;
+                self.expect_keyword("AND", "BETWEEN")?;
+                WindowFrame::new(start, self.parse_frame_bound()?)?
+            } else {
+                WindowFrame::new(self.parse_frame_bound()?, FrameBound::CurrentRow)?
+            });
+        }
+
+        match self.next_token() {
+            Some(Token::CloseParenthesis) => Ok(Expression::Window {
+                function,
+                arguments,
+                window,
+            }),
+            _ => Err(Tperrors::Syntax(format!(
+                "Missing closing parenthesis after OVER of {}",
+                function.name()
+            ))),
+        }
+    }
+
+    /// Parses a limit of a frame, Example: ```2 PRECEDING``` or ```CURRENT ROW```
+    fn parse_frame_bound(&mut self) -> Result<FrameBound, Tperrors> {
+        let bound = match self.next_token() {
+            Some(Token::Word(word)) if word == "UNBOUNDED" => {
+                if self.consume_keyword("PRECEDING") {
+                    Some(FrameBound::UnboundedPreceding)
+                } else if self.consume_keyword("FOLLOWING") {
+                    Some(FrameBound::UnboundedFollowing)
+                } else {
+                    None
+                }
+            }
+            Some(Token::Word(word)) if word == "CURRENT" && self.consume_keyword("ROW") => {
+                Some(FrameBound::CurrentRow)
+            }
+            Some(Token::Number(number)) => match number.parse::<usize>() {
+                Ok(n) if self.consume_keyword("PRECEDING") => Some(FrameBound::Preceding(n)),
+                Ok(n) if self.consume_keyword("FOLLOWING") => Some(FrameBound::Following(n)),
+                _ => None,
+            },
+            _ => None,
+        };
+        match bound {
+            Some(bound) => Ok(bound),
+            None => Err(Tperrors::Syntax(
+                "Invalid frame, use UNBOUNDED PRECEDING, n PRECEDING, CURRENT ROW, n FOLLOWING or UNBOUNDED FOLLOWING".to_string(),
+            )),
+        }
+    }
+
+    /// Consumes a keyword that must come after another one, Example: BY after ORDER
+    fn expect_keyword(&mut self, keyword: &str, after: &str) -> Result<(), Tperrors> {
+        match self.consume_keyword(keyword) {
+            true => Ok(()),
+            false => Err(Tperrors::Syntax(format!(
+                "Missing {} after {} inside the expression",
+                keyword, after
+            ))),
+        }
+    }
+
+    /// Parses ```CAST(operand AS type)```, the open parenthesis is already consumed
+    fn parse_cast(&mut self) -> Result<Expression, Tperrors> {
+        let operand = self.parse_expression()?;
+        if !self.consume_keyword("AS") {
+            return Err(Tperrors::Syntax(
+                "CAST must be written as CAST(<value> AS <type>)".to_string(),
+            ));
+        }
+        let target = match self.next_token() {
+            Some(Token::Word(name)) => match CastType::from_name(&name) {
+                Some(target) => target,
+                None => {
+                    return Err(Tperrors::Syntax(format!(
+                        "Invalid type {} on CAST, use INTEGER, REAL, TEXT or DATE",
+                        name
+                    )));
+                }
+            },
+            _ => {
+                return Err(Tperrors::Syntax(
+                    "CAST must be written as CAST(<value> AS <type>)".to_string(),
+                ));
+            }
+        };
+        match self.next_token() {
+            Some(Token::CloseParenthesis) => Ok(Expression::Cast {
+                operand: Box::new(operand),
+                target,
+            }),
+            _ => Err(Tperrors::Syntax("Missing closing parenthesis".to_string())),
+        }
+    }
+
+    /// Parses ```CASE [operand] WHEN ... THEN ... [ELSE ...] END```, CASE is already consumed
+    fn parse_case(&mut self) -> Result<Expression, Tperrors> {
+        let operand = if self.peek().is_some_and(|t| t.is_keyword("WHEN")) {
+            None
+        } else {
+            Some(Box::new(self.parse_expression()?))
+        };
+
+        let mut branches: Vec<(Expression, Expression)> = Vec::new();
+        while self.consume_keyword("WHEN") {
+            let condition = self.parse_expression()?;
+            if !self.consume_keyword("THEN") {
+                return Err(Tperrors::Syntax(
+                    "WHEN must be followed by THEN inside CASE".to_string(),
+                ));
+            }
+            let result = self.parse_expression()?;
+            branches.push((condition, result));
+        }
+        if branches.is_empty() {
+            return Err(Tperrors::Syntax(
+                "CASE must have at least one WHEN".to_string(),
+            ));
+        }
+
+        let otherwise = if self.consume_keyword("ELSE") {
+            Some(Box::new(self.parse_expression()?))
+        } else {
+            None
+        };
+        if !self.consume_keyword("END") {
+            return Err(Tperrors::Syntax("CASE must finish with END".to_string()));
+        }
+
+        Ok(Expression::Case {
+            operand,
+            branches,
+            otherwise,
+        })
+    }
+
+    /// Consumes the next token if it is one of the operators given
+    fn consume_operator(&mut self, operators: &[&str]) -> Option<String> {
+        match self.peek() {
+            Some(Token::Operator(op)) if operators.contains(&op.as_str()) => {
+                let op = op.to_string();
+                self.position += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn binary(left: Expression, operator: BinaryOperator, right: Expression) -> Expression {
+        Expression::Binary {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        }
+    }
+
+    /// Text representation of a token for error messages
+    fn describe(token: &Token) -> String {
+        match token {
+            Token::Word(w) | Token::Number(w) | Token::Operator(w) => w.to_string(),
+            Token::QuotedIdentifier(w) => format!("\"{}\"", w),
+            Token::Text(w) => format!("'{}'", w),
+            Token::Comma => ",".to_string(),
+            Token::OpenParenthesis => "(".to_string(),
+            Token::CloseParenthesis => ")".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_respects_precedence() {
+        let parsed = ExpressionParser::parse("1 + 2 * 3").unwrap();
+
+        let expected = ExpressionParser::binary(
+            Expression::Literal(Value::Integer(1)),
+            BinaryOperator::Plus,
+            ExpressionParser::binary(
+                Expression::Literal(Value::Integer(2)),
+                BinaryOperator::Multiply,
+                Expression::Literal(Value::Integer(3)),
+            ),
+        );
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn parse_column_with_spaces() {
+        let parsed = ExpressionParser::parse("Correo electronico || '!'").unwrap();
+
+        let expected = ExpressionParser::binary(
+            Expression::Column("Correo electronico".to_string()),
+            BinaryOperator::Concat,
+            Expression::Literal(Value::String("!".to_string())),
+        );
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn parse_not_like_compiles_the_pattern() {
+        let parsed = ExpressionParser::parse("Correo electronico NOT LIKE '%@gmail.com'").unwrap();
+
+        match parsed {
+            Expression::Like {
+                operand,
+                compiled,
+                negated,
+                ..
+            } => {
+                assert_eq!(
+                    *operand,
+                    Expression::Column("Correo electronico".to_string())
+                );
+                assert!(compiled.is_some());
+                assert!(negated);
+            }
+            other => panic!("Expected a LIKE expression, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_function_calls() {
+        let parsed = ExpressionParser::parse("upper(SUBSTR(Nombre, 1, 3))").unwrap();
+
+        let expected = Expression::Function {
+            function: ScalarFunction::Upper,
+            arguments: vec![Expression::Function {
+                function: ScalarFunction::Substr,
+                arguments: vec![
+                    Expression::Column("Nombre".to_string()),
+                    Expression::Literal(Value::Integer(1)),
+                    Expression::Literal(Value::Integer(3)),
+                ],
+            }],
+        };
+        assert_eq!(parsed, expected);
+        assert!(ExpressionParser::parse("LENGTH(Nombre, Apellido)").is_err());
+        assert!(ExpressionParser::parse("SIZE(Nombre)").is_err());
+    }
+
+    #[test]
+    fn parse_window_functions() {
+        let parsed = ExpressionParser::parse(
+            "SUM(cantidad) OVER (PARTITION BY id_cliente ORDER BY id DESC ROWS 2 PRECEDING)",
+        )
+        .unwrap();
+        let expected = Expression::Window {
+            function: WindowFunction::Aggregate(AggregateFunction::Sum),
+            arguments: vec![Expression::Column("cantidad".to_string())],
+            window: WindowSpec {
+                partition_by: vec![Expression::Column("id_cliente".to_string())],
+                order_by: vec![(Expression::Column("id".to_string()), false)],
+                frame: Some(WindowFrame {
+                    start: FrameBound::Preceding(2),
+                    end: FrameBound::CurrentRow,
+                }),
+            },
+        };
+        assert_eq!(parsed, expected);
+
+        let valid = vec![
+            "ROW_NUMBER() OVER ()",
+            "RANK() OVER (ORDER BY Edad) + 1",
+            "LAG(Edad, 1, 0) OVER (ORDER BY Id)",
+            "COUNT(*) OVER (ROWS BETWEEN CURRENT ROW AND UNBOUNDED FOLLOWING)",
+        ];
+        for expression in valid {
+            assert!(
+                ExpressionParser::parse(expression).is_ok(),
+                "{}",
+                expression
+            );
+        }
+
+        let invalid = vec![
+            "RANK()",
+            "RANK(Edad) OVER ()",
+            "LAG() OVER ()",
+            "RANK() OVER (ORDER BY Edad ROWS 1 PRECEDING)",
+            "COUNT(DISTINCT Edad) OVER ()",
+            "SUM(Edad) OVER (ROWS BETWEEN 1 FOLLOWING AND CURRENT ROW)",
+            "SUM(Edad) OVER (PARTITION Id)",
+            "SUM(Edad) OVER (ORDER BY Id",
+        ];
+        for expression in invalid {
+            assert!(
+                ExpressionParser::parse(expression).is_err(),
+                "{}",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn parse_subqueries_keeps_their_text() {
+        let parsed = ExpressionParser::parse(
+            "Id NOT IN (SELECT id_cliente FROM ordenes WHERE producto = 'Mouse')",
+        )
+        .unwrap();
+        let expected = Expression::InSubquery {
+            operand: Box::new(Expression::Column("Id".to_string())),
+            subquery: Subquery::new(
+                "SELECT id_cliente FROM ordenes WHERE producto = 'Mouse';".to_string(),
+            ),
+            negated: true,
+        };
+        assert_eq!(parsed, expected);
+
+        let parsed = ExpressionParser::parse("Edad > (SELECT AVG(Edad) FROM clientes)").unwrap();
+        let expected = Expression::Binary {
+            left: Box::new(Expression::Column("Edad".to_string())),
+            operator: BinaryOperator::Greater,
+            right: Box::new(Expression::ScalarSubquery(Subquery::new(
+                "SELECT AVG ( Edad ) FROM clientes;".to_string(),
+            ))),
+        };
+        assert_eq!(parsed, expected);
+
+        let parsed = ExpressionParser::parse("EXISTS (SELECT * FROM ordenes)").unwrap();
+        let expected = Expression::Exists(Subquery::new("SELECT * FROM ordenes;".to_string()));
+        assert_eq!(parsed, expected);
+
+        assert!(ExpressionParser::parse("EXISTS (SELECT * FROM ordenes").is_err());
+        assert!(ExpressionParser::parse("EXISTS 1").is_err());
+    }
+
+    #[test]
+    fn parse_incomplete_expressions_fails() {
+        let invalid = vec![
+            "Edad >",
+            "(Edad > 3",
+            "Edad > 3)",
+            "Edad * * 2",
+            "CASE WHEN Edad > 3 THEN 1",
+            "CAST(Edad AS NUMBER)",
+            "SUM(*)",
+            "COUNT(Edad, Nombre)",
+        ];
+
+        for expression in invalid {
+            assert!(ExpressionParser::parse(expression).is_err());
+        }
+    }
+}
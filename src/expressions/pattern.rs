@@ -0,0 +1,163 @@
+use crate::errors::tperrors::Tperrors;
+
+/// A piece of a LIKE pattern
+#[derive(Debug, Clone, PartialEq)]
+enum PatternPart {
+    Char(char),
+    /// ```_``` matches exactly one character
+    AnyChar,
+    /// ```%``` matches any sequence of characters, even an empty one
+    AnySequence,
+}
+
+/// A LIKE / ILIKE pattern, compiled once per query and then matched against every row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    parts: Vec<PatternPart>,
+    case_insensitive: bool,
+}
+
+impl Pattern {
+    /// Compiles a LIKE pattern
+    ///
+    /// * `pattern` - The pattern, Example: ```%@gmail.com```
+    ///
+    /// * `escape` - The character that makes the next ```%``` or ```_``` a normal character
+    ///
+    /// * `case_insensitive` - True for ILIKE
+    ///
+    /// If the pattern ends with the escape character a Syntax error is returned
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tp_individual::expressions::pattern::Pattern;
+    ///
+    /// let pattern = Pattern::compile("%@gmail.com", None, false).unwrap();
+    /// assert!(pattern.matches("jperez@gmail.com"));
+    /// assert!(!pattern.matches("jperez@fi.uba.ar"));
+    /// ```
+    pub fn compile(
+        pattern: &str,
+        escape: Option<char>,
+        case_insensitive: bool,
+    ) -> Result<Pattern, Tperrors> {
+        let mut parts: Vec<PatternPart> = Vec::new();
+        let mut chars = pattern.chars();
+
+        while let Some(c) = chars.next() {
+            if Some(c) == escape {
+                match chars.next() {
+                    Some(escaped) => Self::push_char(&mut parts, escaped, case_insensitive),
+                    None => {
+                        return Err(Tperrors::Syntax(format!(
+                            "LIKE pattern {} can't end with the escape character",
+                            pattern
+                        )));
+                    }
+                }
+            } else if c == '%' {
+                // many % together are the same as a single one
+                if parts.last() != Some(&PatternPart::AnySequence) {
+                    parts.push(PatternPart::AnySequence);
+                }
+            } else if c == '_' {
+                parts.push(PatternPart::AnyChar);
+            } else {
+                Self::push_char(&mut parts, c, case_insensitive);
+            }
+        }
+        Ok(Pattern {
+            parts,
+            case_insensitive,
+        })
+    }
+
+    /// Returns true if the whole text matches the pattern
+    ///
+    /// Works over characters (not bytes) so ```_``` matches a single ```í``` on ```Lucía```
+    pub fn matches(&self, text: &str) -> bool {
+        let text = if self.case_insensitive {
+            text.chars()
+                .flat_map(char::to_lowercase)
+                .collect::<Vec<char>>()
+        } else {
+            text.chars().collect::<Vec<char>>()
+        };
+
+        let mut p = 0;
+        let mut t = 0;
+        // last % seen and the position of the text where we tried it,
+        // if something doesn't match we go back there and let % take one more character
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while t < text.len() {
+            match self.parts.get(p) {
+                Some(PatternPart::AnySequence) => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                }
+                Some(PatternPart::AnyChar) => {
+                    p += 1;
+                    t += 1;
+                }
+                Some(PatternPart::Char(c)) if *c == text[t] => {
+                    p += 1;
+                    t += 1;
+                }
+                _ => match backtrack {
+                    Some((star_p, star_t)) => {
+                        p = star_p + 1;
+                        t = star_t + 1;
+                        backtrack = Some((star_p, star_t + 1));
+                    }
+                    None => return false,
+                },
+            }
+        }
+        self.parts[p..]
+            .iter()
+            .all(|part| *part == PatternPart::AnySequence)
+    }
+
+    fn push_char(parts: &mut Vec<PatternPart>, c: char, case_insensitive: bool) {
+        if case_insensitive {
+            c.to_lowercase()
+                .for_each(|lower| parts.push(PatternPart::Char(lower)));
+        } else {
+            parts.push(PatternPart::Char(c));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_with_wildcards() {
+        let pattern = Pattern::compile("L_c%a", None, false).unwrap();
+
+        assert!(pattern.matches("Lucía"));
+        assert!(pattern.matches("Lucia"));
+        assert!(!pattern.matches("Laura"));
+        assert!(!pattern.matches("lucía"));
+    }
+
+    #[test]
+    fn pattern_case_insensitive() {
+        let pattern = Pattern::compile("%SÁNCHEZ", None, true).unwrap();
+
+        assert!(pattern.matches("Sánchez"));
+        assert!(!pattern.matches("Sanchez"));
+    }
+
+    #[test]
+    fn pattern_with_escape_character() {
+        let pattern = Pattern::compile("100!%%", Some('!'), false).unwrap();
+
+        assert!(pattern.matches("100% seguro"));
+        assert!(!pattern.matches("1000"));
+        assert!(Pattern::compile("100!", Some('!'), false).is_err());
+    }
+}
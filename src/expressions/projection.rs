@@ -0,0 +1,163 @@
+use crate::errors::tperrors::Tperrors;
+
+use super::{
+    expression::Expression,
+    parser::ExpressionParser,
+    tokenizer::{Token, Tokenizer},
+};
+
+/// An element of the SELECT list
+///
+/// # Fields
+///
+/// * `expression` - What has to be computed for every row.
+///
+/// * `name` - The name shown on the header (the alias if there is one).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Projection {
+    pub expression: Expression,
+    pub name: String,
+}
+
+impl Projection {
+    /// Given the elements of a SELECT list and the columns of the table
+    ///
+    /// returns the projections to compute, expanding ```*``` into every column.
+    ///
+    /// Example: ```["Nombre || ' ' || Apellido AS completo", "Edad"]```
+    ///
+    /// If an element uses a column that doesn't exist, returns a Column error
+    pub fn from_select_list(
+        items: &[String],
+        columns: &[String],
+    ) -> Result<Vec<Projection>, Tperrors> {
+        let mut projections: Vec<Projection> = Vec::new();
+
+        for item in items {
+            let item = item.trim();
+            if item == "*" {
+                columns.iter().for_each(|c| {
+                    projections.push(Projection {
+                        expression: Expression::Column(c.to_string()),
+                        name: Self::unqualified(c).to_string(),
+                    })
+                });
+                continue;
+            }
+            projections.push(Self::parse(item, columns)?);
+        }
+        Ok(projections)
+    }
+
+    /// Parses a single element of the SELECT list
+    ///
+    /// A quoted name (```'Correo electronico'```) is still treated as a column, like before
+    /// expressions were supported.
+    fn parse(item: &str, columns: &[String]) -> Result<Projection, Tperrors> {
+        let unquoted = item.trim_matches('\'').trim_matches('\"');
+        if columns.iter().any(|c| c == unquoted) {
+            return Ok(Projection {
+                expression: Expression::Column(unquoted.to_string()),
+                name: Self::unqualified(unquoted).to_string(),
+            });
+        }
+
+        let tokens = Tokenizer::new().tokenize(item)?;
+        let alias_position = Self::alias_position(&tokens);
+
+        let (expression_tokens, alias) = match alias_position {
+            Some(position) => {
+                let alias = match &tokens[position + 1..] {
+                    [Token::Word(alias)]
+                    | [Token::QuotedIdentifier(alias)]
+                    | [Token::Text(alias)] => alias.to_string(),
+                    _ => {
+                        return Err(Tperrors::Syntax(format!(
+                            "Invalid alias inside the query near {}",
+                            item
+                        )));
+                    }
+                };
+                (tokens[..position].to_vec(), Some(alias))
+            }
+            None => (tokens, None),
+        };
+
+        let mut parser = ExpressionParser::new(expression_tokens);
+        let expression = parser.parse_expression()?;
+        parser.expect_end()?;
+        expression.validate_columns(columns)?;
+
+        let name = match (alias, &expression) {
+            (Some(alias), _) => alias,
+            (None, Expression::Column(column)) => Self::unqualified(column).to_string(),
+            (None, _) => item.to_string(),
+        };
+        Ok(Projection { expression, name })
+    }
+
+    /// The name of a column without its table, Example: ```c.Nombre``` -> ```Nombre```
+    fn unqualified(column: &str) -> &str {
+        match column.rsplit_once('.') {
+            Some((_, name)) => name,
+            None => column,
+        }
+    }
+
+    /// Position of the AS that gives the alias, the ones between parenthesis
+    /// belong to a CAST (```CAST(Edad AS TEXT) AS edad```)
+    fn alias_position(tokens: &[Token]) -> Option<usize> {
+        let mut depth = 0;
+        let mut position = None;
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                Token::OpenParenthesis => depth += 1,
+                Token::CloseParenthesis => depth -= 1,
+                _ if depth == 0 && token.is_keyword("AS") => position = Some(i),
+                _ => {}
+            }
+        }
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> Vec<String> {
+        vec![
+            "Nombre".to_string(),
+            "Apellido".to_string(),
+            "Edad".to_string(),
+        ]
+    }
+
+    #[test]
+    fn projection_with_aliases() {
+        let items = vec![
+            "Nombre || ' ' || Apellido AS completo".to_string(),
+            "Edad * 12 AS meses".to_string(),
+            "Edad".to_string(),
+            "CAST(Edad AS TEXT) AS texto".to_string(),
+            "CAST(Edad AS REAL)".to_string(),
+        ];
+        let projections = Projection::from_select_list(&items, &columns()).unwrap();
+
+        let names = projections
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            names,
+            vec!["completo", "meses", "Edad", "texto", "CAST(Edad AS REAL)"]
+        );
+    }
+
+    #[test]
+    fn projection_with_unknown_column_fails() {
+        let items = vec!["Sueldo * 12 AS anual".to_string()];
+
+        assert!(Projection::from_select_list(&items, &columns()).is_err());
+    }
+}
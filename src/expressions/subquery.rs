@@ -0,0 +1,303 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Debug, rc::Rc};
+
+use crate::{conditions::value::Value, errors::tperrors::Tperrors};
+
+use super::expression::Row;
+
+/// Runs the SELECT of a subquery, the expressions don't know where the tables are
+pub trait SubqueryRunner {
+    /// Runs the query and returns the names of its columns and its rows
+    ///
+    /// `outer` is the current row of the query that contains the subquery,
+    /// only given if the subquery is correlated (it uses columns of that row)
+    fn run(
+        &self,
+        query: &str,
+        outer: Option<&Row>,
+    ) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors>;
+
+    /// Returns true if the query uses any of the columns of the outer query
+    /// (that aren't columns of its own tables)
+    fn is_correlated(&self, query: &str, outer_columns: &[String]) -> Result<bool, Tperrors>;
+
+    /// Returns the columns and rows of a table defined on a WITH (if there is one with that name)
+    fn common_table(&self, _name: &str) -> Option<(Vec<String>, Vec<Vec<Value>>)> {
+        None
+    }
+
+    /// Runs the query of a view of the folder
+    ///
+    /// Returns an error if the view is already being run (it reads itself)
+    fn run_view(
+        &self,
+        _name: &str,
+        query: &str,
+    ) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors> {
+        self.run(query, None)
+    }
+}
+
+/// The rows returned by a subquery
+///
+/// With a single column, their values are also on a hash map, so ```IN``` doesn't
+/// go through all of them for every row
+pub struct SubqueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    keys: HashMap<String, Vec<usize>>,
+    has_null: bool,
+}
+
+impl SubqueryResult {
+    pub fn new(columns: Vec<String>, rows: Vec<Vec<Value>>) -> SubqueryResult {
+        let mut keys: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut has_null = false;
+        if columns.len() == 1 {
+            for (i, row) in rows.iter().enumerate() {
+                match row.first().and_then(|value| value.match_key()) {
+                    Some(key) => keys.entry(key).or_default().push(i),
+                    None => has_null = true,
+                }
+            }
+        }
+        SubqueryResult {
+            columns,
+            rows,
+            keys,
+            has_null,
+        }
+    }
+
+    /// Checks if the value is on the single column of the rows
+    ///
+    /// Like an IN list, it's unknown (None) if it isn't there but there is a NULL
+    pub fn contains(&self, value: &Value) -> Result<Option<bool>, Tperrors> {
+        if self.columns.len() != 1 {
+            return Err(Tperrors::Syntax(format!(
+                "Subquery used with IN must return a single column, it returns {}",
+                self.columns.len()
+            )));
+        }
+        if self.rows.is_empty() {
+            return Ok(Some(false));
+        }
+        // the rows with the same key are only candidates ('007' and '7')
+        let found = value
+            .match_key()
+            .and_then(|key| self.keys.get(&key))
+            .is_some_and(|rows| {
+                rows.iter()
+                    .any(|i| self.rows[*i].first().is_some_and(|row| value.equals(row)))
+            });
+        match value.match_key() {
+            Some(_) if found => Ok(Some(true)),
+            Some(_) if !self.has_null => Ok(Some(false)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The value of a subquery used as a value, NULL if it has no rows
+    ///
+    /// Returns an error if it has more than one column or more than one row
+    pub fn scalar(&self) -> Result<Value, Tperrors> {
+        if self.columns.len() != 1 {
+            return Err(Tperrors::Syntax(format!(
+                "Subquery used as a value must return a single column, it returns {}",
+                self.columns.len()
+            )));
+        }
+        match self.rows.as_slice() {
+            [] => Ok(Value::Null),
+            [row] => Ok(row[0].clone()),
+            _ => Err(Tperrors::Generic(format!(
+                "Subquery used as a value must return a single row, it returns {}",
+                self.rows.len()
+            ))),
+        }
+    }
+}
+
+/// A SELECT inside an expression, Example: ```Id IN (SELECT id_cliente FROM ordenes)```
+///
+/// Before evaluating it, the query that contains it must give it a runner (see bind).
+///
+/// If it isn't correlated, it's run the first time it's needed and its result is kept
+/// for the rest of the rows (it is the same for every row).
+#[derive(Clone)]
+pub struct Subquery {
+    query: String,
+    runner: Option<Rc<dyn SubqueryRunner>>,
+    correlated: bool,
+    cache: Rc<RefCell<Option<Rc<SubqueryResult>>>>,
+}
+
+impl Subquery {
+    /// Given the text of the query, Example: ```SELECT id_cliente FROM ordenes;```
+    pub fn new(query: String) -> Subquery {
+        Subquery {
+            query,
+            runner: None,
+            correlated: false,
+            cache: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Gives the subquery the runner of the query that contains it
+    ///
+    /// `outer_columns` are the columns of the rows it will be evaluated with
+    pub fn bind(
+        &mut self,
+        runner: &Rc<dyn SubqueryRunner>,
+        outer_columns: &[String],
+    ) -> Result<(), Tperrors> {
+        self.correlated = runner.is_correlated(&self.query, outer_columns)?;
+        self.runner = Some(Rc::clone(runner));
+        self.cache.replace(None);
+        Ok(())
+    }
+
+    /// Returns the rows of the subquery for the given row
+    pub fn result(&self, row: &Row) -> Result<Rc<SubqueryResult>, Tperrors> {
+        if let Some(cached) = self.cache.borrow().as_ref() {
+            return Ok(Rc::clone(cached));
+        }
+        let runner = match &self.runner {
+            Some(runner) => runner,
+            None => {
+                return Err(Tperrors::Generic(format!(
+                    "Subquery ({}) can't be used here",
+                    self.query.trim_end_matches(';')
+                )));
+            }
+        };
+
+        let outer = match self.correlated {
+            true => Some(row),
+            false => None,
+        };
+        let (columns, rows) = runner.run(&self.query, outer)?;
+        let result = Rc::new(SubqueryResult::new(columns, rows));
+        if !self.correlated {
+            self.cache.replace(Some(Rc::clone(&result)));
+        }
+        Ok(result)
+    }
+}
+
+impl Debug for Subquery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subquery")
+            .field("query", &self.query)
+            .field("correlated", &self.correlated)
+            .finish()
+    }
+}
+
+/// Two subqueries are the same if they have the same query
+impl PartialEq for Subquery {
+    fn eq(&self, other: &Self) -> bool {
+        self.query == other.query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    /// Counts how many times the query is run, it's correlated if it uses ```c.Id```
+    struct CountingRunner {
+        runs: Cell<usize>,
+    }
+
+    impl SubqueryRunner for CountingRunner {
+        fn run(
+            &self,
+            _query: &str,
+            _outer: Option<&Row>,
+        ) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors> {
+            self.runs.set(self.runs.get() + 1);
+            Ok((vec!["id".to_string()], vec![vec![Value::Integer(1)]]))
+        }
+
+        fn is_correlated(&self, query: &str, _outer_columns: &[String]) -> Result<bool, Tperrors> {
+            Ok(query.contains("c.Id"))
+        }
+    }
+
+    #[test]
+    fn subquery_result_contains_like_an_in_list() {
+        let rows = vec![vec![Value::Integer(1)], vec![Value::Integer(4)]];
+        let result = SubqueryResult::new(vec!["id_cliente".to_string()], rows);
+
+        assert_eq!(result.contains(&Value::Integer(4)).unwrap(), Some(true));
+        assert_eq!(
+            result.contains(&Value::String("1".to_string())).unwrap(),
+            Some(true)
+        );
+        assert_eq!(result.contains(&Value::Integer(2)).unwrap(), Some(false));
+
+        let with_null = SubqueryResult::new(vec!["id".to_string()], vec![vec![Value::Null]]);
+        assert_eq!(with_null.contains(&Value::Integer(2)).unwrap(), None);
+
+        let two_columns = SubqueryResult::new(
+            vec!["a".to_string(), "b".to_string()],
+            vec![vec![Value::Integer(1), Value::Integer(2)]],
+        );
+        assert!(two_columns.contains(&Value::Integer(1)).is_err());
+    }
+
+    #[test]
+    fn subquery_result_as_a_single_value() {
+        let columns = vec!["AVG(Edad)".to_string()];
+        let single = SubqueryResult::new(columns.clone(), vec![vec![Value::Real(31.5)]]);
+        assert_eq!(single.scalar().unwrap(), Value::Real(31.5));
+
+        let empty = SubqueryResult::new(columns.clone(), Vec::new());
+        assert_eq!(empty.scalar().unwrap(), Value::Null);
+
+        let many = SubqueryResult::new(
+            columns,
+            vec![vec![Value::Integer(1)], vec![Value::Integer(2)]],
+        );
+        assert!(many.scalar().is_err());
+    }
+
+    #[test]
+    fn subquery_without_runner_fails() {
+        let subquery = Subquery::new("SELECT id FROM ordenes;".to_string());
+        let columns: Vec<String> = Vec::new();
+        let values: Vec<Value> = Vec::new();
+
+        assert!(subquery.result(&Row::new(&columns, &values)).is_err());
+    }
+
+    #[test]
+    fn uncorrelated_subquery_runs_once() {
+        let counting = Rc::new(CountingRunner { runs: Cell::new(0) });
+        let runner: Rc<dyn SubqueryRunner> = counting.clone();
+        let columns = vec!["c.Id".to_string()];
+        let values = vec![Value::Integer(1)];
+        let row = Row::new(&columns, &values);
+
+        let mut subquery = Subquery::new("SELECT id FROM ordenes;".to_string());
+        subquery.bind(&runner, &columns).unwrap();
+        for _ in 0..3 {
+            subquery.result(&row).unwrap();
+        }
+        assert_eq!(counting.runs.get(), 1);
+
+        let mut correlated =
+            Subquery::new("SELECT id FROM ordenes o WHERE o.id_cliente = c.Id;".to_string());
+        correlated.bind(&runner, &columns).unwrap();
+        for _ in 0..3 {
+            correlated.result(&row).unwrap();
+        }
+        assert_eq!(counting.runs.get(), 4);
+    }
+}
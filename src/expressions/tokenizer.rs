@@ -0,0 +1,252 @@
+use crate::errors::tperrors::Tperrors;
+
+/// Words that can't be used as a column name (or part of one) inside an expression
+const KEYWORDS: [&str; 30] = [
+    "AND",
+    "OR",
+    "NOT",
+    "AS",
+    "NULL",
+    "TRUE",
+    "FALSE",
+    "FROM",
+    "WHERE",
+    "ORDER",
+    "BY",
+    "ASC",
+    "DESC",
+    "LIKE",
+    "ILIKE",
+    "ESCAPE",
+    "IN",
+    "BETWEEN",
+    "CASE",
+    "WHEN",
+    "THEN",
+    "ELSE",
+    "END",
+    "DISTINCT",
+    "IS",
+    "SELECT",
+    "EXISTS",
+    "OVER",
+    "PARTITION",
+    "ROWS",
+];
+
+/// Characters that can form an operator
+const OPERATOR_CHARS: [char; 10] = ['=', '<', '>', '!', '+', '-', '*', '/', '%', '|'];
+
+/// Representation of a token of an expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// A bare word, either a keyword or (part of) a column name
+    Word(String),
+    /// A name between double quotes, ```"Correo electronico"```
+    QuotedIdentifier(String),
+    /// A text between single quotes, ```'Juan'```
+    Text(String),
+    Number(String),
+    Operator(String),
+    Comma,
+    OpenParenthesis,
+    CloseParenthesis,
+}
+
+impl Token {
+    /// Returns true if the token is the given keyword
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        match self {
+            Token::Word(word) => word == keyword,
+            _ => false,
+        }
+    }
+
+    /// Writes the token back as it would be on a query (quotes are escaped)
+    pub fn to_sql(&self) -> String {
+        match self {
+            Token::Word(w) | Token::Number(w) | Token::Operator(w) => w.to_string(),
+            Token::QuotedIdentifier(w) => format!("\"{}\"", w.replace('\"', "\"\"")),
+            Token::Text(w) => format!("'{}'", w.replace('\'', "''")),
+            Token::Comma => ",".to_string(),
+            Token::OpenParenthesis => "(".to_string(),
+            Token::CloseParenthesis => ")".to_string(),
+        }
+    }
+
+    /// Returns true if the token is a word that is not reserved
+    pub fn is_plain_word(&self) -> bool {
+        match self {
+            Token::Word(word) => !is_reserved(word),
+            _ => false,
+        }
+    }
+}
+
+/// Returns true if the word is a keyword that can't be used as a column name
+pub fn is_reserved(word: &str) -> bool {
+    KEYWORDS.contains(&word)
+}
+
+/// Splits an expression into tokens
+pub struct Tokenizer;
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Tokenizer::new()
+    }
+}
+
+impl Tokenizer {
+    pub fn new() -> Tokenizer {
+        Tokenizer
+    }
+
+    /// Given an expression as str, returns its tokens
+    ///
+    /// Example
+    ///
+    /// ```Edad>=45 AND Nombre = 'Juan'```
+    ///
+    /// Returns ```[Edad, >=, 45, AND, Nombre, =, 'Juan']```
+    ///
+    /// If a quote is never closed, returns a Syntax error
+    pub fn tokenize(&self, expression: &str) -> Result<Vec<Token>, Tperrors> {
+        let chars = expression.chars().collect::<Vec<char>>();
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            match c {
+                _ if c.is_whitespace() || c == ';' => {
+                    i += 1;
+                }
+                '(' => {
+                    tokens.push(Token::OpenParenthesis);
+                    i += 1;
+                }
+                ')' => {
+                    tokens.push(Token::CloseParenthesis);
+                    i += 1;
+                }
+                ',' => {
+                    tokens.push(Token::Comma);
+                    i += 1;
+                }
+                '\'' | '\"' => {
+                    let (content, next) = self.read_quoted(&chars, i)?;
+                    if c == '\'' {
+                        tokens.push(Token::Text(content));
+                    } else {
+                        tokens.push(Token::QuotedIdentifier(content));
+                    }
+                    i = next;
+                }
+                _ if OPERATOR_CHARS.contains(&c) => {
+                    let operator = self.read_operator(&chars, i)?;
+                    i += operator.chars().count();
+                    tokens.push(Token::Operator(operator));
+                }
+                _ => {
+                    let start = i;
+                    while i < chars.len() && self.is_word_char(chars[i]) {
+                        i += 1;
+                    }
+                    let word = chars[start..i].iter().collect::<String>();
+                    if word.parse::<f64>().is_ok() && word.starts_with(|c: char| c.is_ascii_digit())
+                    {
+                        tokens.push(Token::Number(word));
+                    } else {
+                        tokens.push(Token::Word(word));
+                    }
+                }
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// Reads a quoted text starting at position i
+    ///
+    /// Two quotes together (```'O''Brien'```) are an escaped quote
+    ///
+    /// Returns the content and the position after the closing quote
+    fn read_quoted(&self, chars: &[char], i: usize) -> Result<(String, usize), Tperrors> {
+        let quote = chars[i];
+        let mut content = String::new();
+        let mut j = i + 1;
+
+        while j < chars.len() {
+            if chars[j] == quote {
+                if j + 1 < chars.len() && chars[j + 1] == quote {
+                    content.push(quote);
+                    j += 2;
+                    continue;
+                }
+                return Ok((content, j + 1));
+            }
+            content.push(chars[j]);
+            j += 1;
+        }
+        Err(Tperrors::Syntax(format!(
+            "Missing closing {} near {}",
+            quote, content
+        )))
+    }
+
+    /// Reads an operator of one or two characters starting at position i
+    fn read_operator(&self, chars: &[char], i: usize) -> Result<String, Tperrors> {
+        let two_chars = chars[i..chars.len().min(i + 2)].iter().collect::<String>();
+
+        match two_chars.as_str() {
+            "=>" | "=<" => Err(Tperrors::Syntax(
+                "Invalid operator, use >= or <= (SQL: Near '<')".to_string(),
+            )),
+            "<=" | ">=" | "!=" | "<>" | "||" => Ok(two_chars),
+            _ => match chars[i] {
+                '!' | '|' => Err(Tperrors::Syntax(format!(
+                    "Invalid operator near {}",
+                    chars[i]
+                ))),
+                c => Ok(c.to_string()),
+            },
+        }
+    }
+
+    fn is_word_char(&self, c: char) -> bool {
+        !(c.is_whitespace()
+            || OPERATOR_CHARS.contains(&c)
+            || ['(', ')', ',', ';', '\'', '\"'].contains(&c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_attached_conditions() {
+        let tokens = Tokenizer::new()
+            .tokenize("Edad>=45 AND Nombre='Lucía'")
+            .unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Word("Edad".to_string()),
+                Token::Operator(">=".to_string()),
+                Token::Number("45".to_string()),
+                Token::Word("AND".to_string()),
+                Token::Word("Nombre".to_string()),
+                Token::Operator("=".to_string()),
+                Token::Text("Lucía".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_unbalanced_quote_fails() {
+        assert!(Tokenizer::new().tokenize("Nombre = 'Juan").is_err());
+        assert!(Tokenizer::new().tokenize("Edad => 45").is_err());
+    }
+}
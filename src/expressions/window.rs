@@ -0,0 +1,521 @@
+use std::{cmp::Ordering, collections::HashMap, rc::Rc};
+
+use crate::{conditions::value::Value, errors::tperrors::Tperrors, sorter::sort::SortMethod};
+
+use super::{
+    aggregate::{Accumulator, AggregateFunction},
+    expression::{Expression, Row},
+    subquery::SubqueryRunner,
+};
+
+/// Functions computed over the rows of a window, Example: ```RANK() OVER (ORDER BY Edad)```
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowFunction {
+    /// Position of the row inside its partition, starting at 1
+    RowNumber,
+    /// Position of the first row with the same ORDER BY values (there are gaps after a tie)
+    Rank,
+    /// Like RANK, without gaps after a tie
+    DenseRank,
+    /// ```LAG(value [, offset [, default]])```, the value of a row before this one
+    Lag,
+    /// ```LEAD(value [, offset [, default]])```, the value of a row after this one
+    Lead,
+    /// An aggregate function computed over the rows of the frame, Example: a running total
+    Aggregate(AggregateFunction),
+}
+
+impl WindowFunction {
+    /// Given the name of a function, returns it if it is a window function
+    /// (the aggregate functions are window functions only when followed by OVER)
+    pub fn from_name(name: &str) -> Option<WindowFunction> {
+        match name.to_uppercase().as_str() {
+            "ROW_NUMBER" => Some(WindowFunction::RowNumber),
+            "RANK" => Some(WindowFunction::Rank),
+            "DENSE_RANK" => Some(WindowFunction::DenseRank),
+            "LAG" => Some(WindowFunction::Lag),
+            "LEAD" => Some(WindowFunction::Lead),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            WindowFunction::RowNumber => "ROW_NUMBER",
+            WindowFunction::Rank => "RANK",
+            WindowFunction::DenseRank => "DENSE_RANK",
+            WindowFunction::Lag => "LAG",
+            WindowFunction::Lead => "LEAD",
+            WindowFunction::Aggregate(function) => function.name(),
+        }
+    }
+
+    /// Checks the amount of arguments of the function
+    pub fn check_arguments(&self, amount: usize) -> Result<(), Tperrors> {
+        let (min, max) = match self {
+            WindowFunction::RowNumber | WindowFunction::Rank | WindowFunction::DenseRank => (0, 0),
+            WindowFunction::Lag | WindowFunction::Lead => (1, 3),
+            WindowFunction::Aggregate(AggregateFunction::Count) => (0, 1),
+            WindowFunction::Aggregate(_) => (1, 1),
+        };
+        if amount < min || amount > max {
+            return Err(Tperrors::Syntax(format!(
+                "Function {} takes between {} and {} arguments, {} given",
+                self.name(),
+                min,
+                max,
+                amount
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A limit of the frame of a window, relative to the current row
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(usize),
+    CurrentRow,
+    Following(usize),
+    UnboundedFollowing,
+}
+
+/// The rows of the partition an aggregate function is computed over
+///
+/// Example: ```ROWS BETWEEN 2 PRECEDING AND CURRENT ROW```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowFrame {
+    pub start: FrameBound,
+    pub end: FrameBound,
+}
+
+impl WindowFrame {
+    /// Creates a frame, the start can't be after the end
+    pub fn new(start: FrameBound, end: FrameBound) -> Result<WindowFrame, Tperrors> {
+        let offset = |bound: &FrameBound| -> i64 {
+            match bound {
+                FrameBound::UnboundedPreceding => i64::MIN,
+                FrameBound::Preceding(n) => -(*n as i64),
+                FrameBound::CurrentRow => 0,
+                FrameBound::Following(n) => *n as i64,
+                FrameBound::UnboundedFollowing => i64::MAX,
+            }
+        };
+        if start == FrameBound::UnboundedFollowing
+            || end == FrameBound::UnboundedPreceding
+            || offset(&start) > offset(&end)
+        {
+            return Err(Tperrors::Syntax(
+                "Invalid frame, its start can't be after its end".to_string(),
+            ));
+        }
+        Ok(WindowFrame { start, end })
+    }
+}
+
+/// What goes between the parenthesis of ```OVER (...)```
+///
+/// # Fields
+///
+/// * `partition_by` - The rows are splitted by these values, each function only sees
+///   the rows of the same partition.
+///
+/// * `order_by` - How the rows of a partition are sorted (true means ascending).
+///
+/// * `frame` - The rows used by an aggregate function. Without it, they are every row
+///   until the current one (and the ones with its same ORDER BY values), or the whole
+///   partition if there is no ORDER BY.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WindowSpec {
+    pub partition_by: Vec<Expression>,
+    pub order_by: Vec<(Expression, bool)>,
+    pub frame: Option<WindowFrame>,
+}
+
+/// The window functions used by a query
+///
+/// Like the aggregate functions, they are taken out of the expressions and replaced by a
+/// column. They are computed once every row of the query is known (after WHERE and
+/// GROUP BY), and then the expressions can be evaluated as usual.
+pub struct Windowing {
+    calls: Vec<WindowCall>,
+}
+
+/// A call to a window function found on the query
+#[derive(PartialEq)]
+struct WindowCall {
+    function: WindowFunction,
+    arguments: Vec<Expression>,
+    window: WindowSpec,
+}
+
+impl Default for Windowing {
+    fn default() -> Self {
+        Windowing::new()
+    }
+}
+
+impl Windowing {
+    pub fn new() -> Windowing {
+        Windowing { calls: Vec::new() }
+    }
+
+    /// Replaces the window functions inside the expression by the columns that will hold
+    /// their result
+    pub fn extract(&mut self, expression: &mut Expression) -> Result<(), Tperrors> {
+        if let Expression::Window {
+            function,
+            arguments,
+            window,
+        } = expression
+        {
+            let inner = arguments
+                .iter()
+                .chain(window.partition_by.iter())
+                .chain(window.order_by.iter().map(|(e, _)| e));
+            for child in inner {
+                if child.contains_window() {
+                    return Err(Tperrors::Syntax(format!(
+                        "Window function {} can't be used inside another one",
+                        function.name()
+                    )));
+                }
+            }
+            let call = WindowCall {
+                function: function.clone(),
+                arguments: arguments.clone(),
+                window: window.clone(),
+            };
+            let position = match self.calls.iter().position(|c| *c == call) {
+                Some(position) => position,
+                None => {
+                    self.calls.push(call);
+                    self.calls.len() - 1
+                }
+            };
+            *expression = Expression::Column(self.column(position));
+            return Ok(());
+        }
+
+        for child in expression.children_mut() {
+            self.extract(child)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Names of the columns that hold the result of every window function
+    pub fn columns(&self) -> Vec<String> {
+        (0..self.calls.len()).map(|i| self.column(i)).collect()
+    }
+
+    /// The expressions used by the window functions (their arguments, PARTITION BY and ORDER BY)
+    pub fn expressions(&self) -> Vec<&Expression> {
+        self.calls
+            .iter()
+            .flat_map(|call| {
+                call.arguments
+                    .iter()
+                    .chain(call.window.partition_by.iter())
+                    .chain(call.window.order_by.iter().map(|(e, _)| e))
+            })
+            .collect()
+    }
+
+    /// Tells the subqueries inside the window functions which columns they can use
+    pub fn bind_subqueries(
+        &mut self,
+        runner: &Rc<dyn SubqueryRunner>,
+        columns: &[String],
+    ) -> Result<(), Tperrors> {
+        for call in self.calls.iter_mut() {
+            let expressions = call
+                .arguments
+                .iter_mut()
+                .chain(call.window.partition_by.iter_mut())
+                .chain(call.window.order_by.iter_mut().map(|(e, _)| e));
+            for expression in expressions {
+                expression.bind_subqueries(runner, columns)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes every window function for every row
+    ///
+    /// Returns, for each row, the result of each function (in the order of `columns`)
+    pub fn compute(
+        &self,
+        rows: &[Vec<Value>],
+        columns: &[String],
+    ) -> Result<Vec<Vec<Value>>, Tperrors> {
+        let mut results = vec![Vec::with_capacity(self.calls.len()); rows.len()];
+        for call in &self.calls {
+            for (row, value) in call.compute(rows, columns)?.into_iter().enumerate() {
+                results[row].push(value);
+            }
+        }
+        Ok(results)
+    }
+
+    fn column(&self, position: usize) -> String {
+        format!("{}#W{}", self.calls[position].function.name(), position)
+    }
+}
+
+impl WindowCall {
+    /// Computes the function for every row, returns a value for each one
+    fn compute(&self, rows: &[Vec<Value>], columns: &[String]) -> Result<Vec<Value>, Tperrors> {
+        // the partitions keep the order in which they are found
+        let mut partitions: Vec<Vec<usize>> = Vec::new();
+        let mut positions: HashMap<Vec<Option<String>>, usize> = HashMap::new();
+        let mut sorting_keys: Vec<Vec<Value>> = Vec::with_capacity(rows.len());
+
+        for (i, values) in rows.iter().enumerate() {
+            let row = Row::new(columns, values);
+            let key = self
+                .window
+                .partition_by
+                .iter()
+                .map(|e| Ok(e.evaluate(&row)?.hash_key()))
+                .collect::<Result<Vec<Option<String>>, Tperrors>>()?;
+            match positions.get(&key) {
+                Some(position) => partitions[*position].push(i),
+                None => {
+                    positions.insert(key, partitions.len());
+                    partitions.push(vec![i]);
+                }
+            }
+            sorting_keys.push(
+                self.window
+                    .order_by
+                    .iter()
+                    .map(|(e, _)| e.evaluate(&row))
+                    .collect::<Result<Vec<Value>, Tperrors>>()?,
+            );
+        }
+
+        let sorting = self
+            .window
+            .order_by
+            .iter()
+            .map(|(_, ascending)| SortMethod {
+                by_column: String::new(),
+                ascending: *ascending,
+            })
+            .collect::<Vec<SortMethod>>();
+        let compare = |a: &usize, b: &usize| -> Ordering {
+            sorting
+                .iter()
+                .enumerate()
+                .map(|(k, method)| method.compare(&sorting_keys[*a][k], &sorting_keys[*b][k]))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        };
+
+        let mut results = vec![Value::Null; rows.len()];
+        for mut partition in partitions {
+            // stable, so rows with the same values keep the order in which they were read
+            partition.sort_by(&compare);
+
+            // rows with the same ORDER BY values are peers, they share RANK and frame end
+            let mut peer_start = vec![0; partition.len()];
+            let mut peer_end = vec![0; partition.len()];
+            let mut dense = vec![0; partition.len()];
+            for i in 0..partition.len() {
+                let new_group = i == 0 || compare(&partition[i - 1], &partition[i]).is_ne();
+                peer_start[i] = if new_group { i } else { peer_start[i - 1] };
+                dense[i] = match (i, new_group) {
+                    (0, _) => 1,
+                    (_, true) => dense[i - 1] + 1,
+                    (_, false) => dense[i - 1],
+                };
+            }
+            for i in (0..partition.len()).rev() {
+                let last_of_group =
+                    i + 1 == partition.len() || compare(&partition[i], &partition[i + 1]).is_ne();
+                peer_end[i] = if last_of_group { i } else { peer_end[i + 1] };
+            }
+
+            for (i, row_position) in partition.iter().enumerate() {
+                let row = Row::new(columns, &rows[*row_position]);
+                results[*row_position] = match &self.function {
+                    WindowFunction::RowNumber => Value::Integer(i as i64 + 1),
+                    WindowFunction::Rank => Value::Integer(peer_start[i] as i64 + 1),
+                    WindowFunction::DenseRank => Value::Integer(dense[i]),
+                    WindowFunction::Lag | WindowFunction::Lead => {
+                        self.shifted(&partition, i, rows, columns, &row)?
+                    }
+                    WindowFunction::Aggregate(function) => {
+                        let (start, end) = self.frame(i, peer_end[i], partition.len());
+                        let mut accumulator = Accumulator::new(function.clone(), false);
+                        for position in partition.iter().take(end).skip(start) {
+                            let value = match self.arguments.first() {
+                                Some(argument) => {
+                                    argument.evaluate(&Row::new(columns, &rows[*position]))?
+                                }
+                                // COUNT(*) counts every row
+                                None => Value::Boolean(true),
+                            };
+                            accumulator.update(value)?;
+                        }
+                        accumulator.result()
+                    }
+                };
+            }
+        }
+        Ok(results)
+    }
+
+    /// The value of LAG or LEAD for the row on position `i` of the partition
+    fn shifted(
+        &self,
+        partition: &[usize],
+        i: usize,
+        rows: &[Vec<Value>],
+        columns: &[String],
+        row: &Row,
+    ) -> Result<Value, Tperrors> {
+        let offset = match self.arguments.get(1) {
+            Some(offset) => match offset.evaluate(row)? {
+                Value::Integer(offset) if offset >= 0 => offset as usize,
+                other => {
+                    return Err(Tperrors::Syntax(format!(
+                        "The offset of {} must be a positive integer, found {}",
+                        self.function.name(),
+                        other
+                    )));
+                }
+            },
+            None => 1,
+        };
+        let target = match self.function {
+            WindowFunction::Lag => i.checked_sub(offset),
+            _ => i.checked_add(offset).filter(|t| *t < partition.len()),
+        };
+        match (target, self.arguments.get(2)) {
+            (Some(target), _) => {
+                self.arguments[0].evaluate(&Row::new(columns, &rows[partition[target]]))
+            }
+            (None, Some(default)) => default.evaluate(row),
+            (None, None) => Ok(Value::Null),
+        }
+    }
+
+    /// The positions of the frame of the row on position `i` (from start, until end excluded)
+    ///
+    /// `last_peer` is the position of the last row with the same ORDER BY values
+    fn frame(&self, i: usize, last_peer: usize, length: usize) -> (usize, usize) {
+        let frame = match &self.window.frame {
+            Some(frame) => frame,
+            None if self.window.order_by.is_empty() => return (0, length),
+            None => return (0, last_peer + 1),
+        };
+        let position = |bound: &FrameBound| -> usize {
+            match bound {
+                FrameBound::UnboundedPreceding => 0,
+                FrameBound::Preceding(n) => i.saturating_sub(*n),
+                FrameBound::CurrentRow => i,
+                FrameBound::Following(n) => i.saturating_add(*n),
+                FrameBound::UnboundedFollowing => length,
+            }
+        };
+        let start = position(&frame.start).min(length);
+        let end = match frame.end {
+            FrameBound::UnboundedFollowing => length,
+            _ => position(&frame.end).saturating_add(1).min(length),
+        };
+        (start, end.max(start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expressions::parser::ExpressionParser;
+
+    /// Computes a window function over the rows of ```(cliente, cantidad)```
+    fn compute(expression: &str, rows: &[(i64, i64)]) -> Vec<Value> {
+        let columns = vec!["cliente".to_string(), "cantidad".to_string()];
+        let rows = rows
+            .iter()
+            .map(|(c, q)| vec![Value::Integer(*c), Value::Integer(*q)])
+            .collect::<Vec<Vec<Value>>>();
+
+        let mut expression = ExpressionParser::parse(expression).unwrap();
+        let mut windowing = Windowing::new();
+        windowing.extract(&mut expression).unwrap();
+        windowing
+            .compute(&rows, &columns)
+            .unwrap()
+            .into_iter()
+            .map(|mut values| values.remove(0))
+            .collect()
+    }
+
+    fn integers(values: &[i64]) -> Vec<Value> {
+        values.iter().map(|v| Value::Integer(*v)).collect()
+    }
+
+    #[test]
+    fn ranking_functions() {
+        let rows = [(1, 5), (1, 3), (2, 4), (1, 5), (2, 1)];
+
+        let row_number = compute(
+            "ROW_NUMBER() OVER (PARTITION BY cliente ORDER BY cantidad DESC)",
+            &rows,
+        );
+        assert_eq!(row_number, integers(&[1, 3, 1, 2, 2]));
+
+        let rank = compute("RANK() OVER (ORDER BY cantidad DESC)", &rows);
+        assert_eq!(rank, integers(&[1, 4, 3, 1, 5]));
+
+        let dense_rank = compute("DENSE_RANK() OVER (ORDER BY cantidad DESC)", &rows);
+        assert_eq!(dense_rank, integers(&[1, 3, 2, 1, 4]));
+    }
+
+    #[test]
+    fn lag_and_lead() {
+        let rows = [(1, 10), (1, 20), (1, 30)];
+
+        let lag = compute("LAG(cantidad) OVER (ORDER BY cantidad)", &rows);
+        assert_eq!(
+            lag,
+            vec![Value::Null, Value::Integer(10), Value::Integer(20)]
+        );
+
+        let lead = compute("LEAD(cantidad, 2, 0) OVER (ORDER BY cantidad)", &rows);
+        assert_eq!(lead, integers(&[30, 0, 0]));
+    }
+
+    #[test]
+    fn running_aggregates_with_frames() {
+        let rows = [(1, 1), (1, 2), (1, 3), (1, 4)];
+
+        let running = compute("SUM(cantidad) OVER (ORDER BY cantidad)", &rows);
+        assert_eq!(running, integers(&[1, 3, 6, 10]));
+
+        let moving = compute(
+            "SUM(cantidad) OVER (ORDER BY cantidad ROWS BETWEEN 1 PRECEDING AND CURRENT ROW)",
+            &rows,
+        );
+        assert_eq!(moving, integers(&[1, 3, 5, 7]));
+
+        let following = compute(
+            "COUNT(*) OVER (ORDER BY cantidad ROWS BETWEEN CURRENT ROW AND UNBOUNDED FOLLOWING)",
+            &rows,
+        );
+        assert_eq!(following, integers(&[4, 3, 2, 1]));
+
+        let whole = compute("AVG(cantidad) OVER (PARTITION BY cliente)", &rows);
+        assert_eq!(whole, vec![Value::Real(2.5); 4]);
+
+        // rows with the same values are on the frame of each other
+        let peers = compute("SUM(cantidad) OVER (ORDER BY cliente)", &rows);
+        assert_eq!(peers, integers(&[10, 10, 10, 10]));
+    }
+}
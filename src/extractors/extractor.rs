@@ -1,7 +1,30 @@
-use crate::{errors::tperrors::Tperrors, sorter::sort::SortMethod};
+use crate::{
+    errors::tperrors::Tperrors,
+    handler_tables::{
+        common_table::{CommonTable, WithClause},
+        folder_tables::FolderFile,
+        join::{FromClause, JoinClause, JoinKind, TableReference},
+        merge::{MergeAction, MergeCase, MergeClause, MergeQuery},
+        on_conflict::{ConflictAction, OnConflict},
+        returning::Returning,
+        set_operation::{CompoundQuery, SetOperator},
+    },
+    sorter::sort::SortMethod,
+};
 
 use super::sqlcommand::SQLCommand;
 
+/// Clauses that can follow the table name of a SELECT, in the order they must be written
+const SELECT_CLAUSES: [&str; 7] = [
+    "WHERE", "GROUP BY", "HAVING", "ORDER BY", "LIMIT", "OFFSET", "FETCH",
+];
+
+/// Clauses at the end of a SELECT that apply to the rows combined by a UNION
+const FINAL_CLAUSES: [&str; 4] = ["ORDER BY", "LIMIT", "OFFSET", "FETCH"];
+
+/// Operators that combine the rows of two SELECT
+const SET_OPERATORS: [&str; 3] = ["UNION", "INTERSECT", "EXCEPT"];
+
 pub struct Extractor;
 
 impl Default for Extractor {
@@ -18,10 +41,15 @@ impl Extractor {
     /// Example
     /// SELECT name, age FROM table;
     /// Returns ["name", "age"]
+    ///
+    /// Columns can be expressions with an alias, commas inside parenthesis or quotes are kept
+    ///
+    /// SELECT Nombre || ', ' || Apellido AS completo, Edad * 12 AS meses FROM table;
+    /// Returns ["Nombre || ', ' || Apellido AS completo", "Edad * 12 AS meses"]
     pub fn extract_columns_for_select(&self, query: &str) -> Result<Vec<String>, Tperrors> {
         let query = query.trim();
 
-        let where_pos = query.find("FROM");
+        let where_pos = self.find_keyword(query, "FROM");
 
         let start = "SELECT".len(); // at this point we know that the first element is SELECT since we validated before.
 
@@ -29,13 +57,17 @@ impl Extractor {
             Some(position_where) => {
                 let column_data = &query[start..position_where];
                 let column_data = column_data.trim();
-
-                let mut columns: Vec<String> = Vec::new();
-                let iterator_columns = column_data.split(",").collect::<Vec<&str>>();
-
-                iterator_columns.into_iter().for_each(|c| {
-                    columns.push(c.trim().trim_matches('\'').trim_matches('\"').to_string())
-                });
+                // DISTINCT is not a column
+                let column_data = match self.is_distinct_select(query) {
+                    true => column_data["DISTINCT".len()..].trim(),
+                    false => column_data,
+                };
+
+                let columns = self
+                    .split_outside_quotes_and_parenthesis(column_data, ',')
+                    .iter()
+                    .map(|c| c.trim().to_string())
+                    .collect::<Vec<String>>();
                 Ok(columns)
             }
             None => Err(Tperrors::Syntax(
@@ -44,6 +76,14 @@ impl Extractor {
         }
     }
 
+    /// Returns true if the query is a ```SELECT DISTINCT```
+    pub fn is_distinct_select(&self, query: &str) -> bool {
+        match query.trim().strip_prefix("SELECT") {
+            Some(rest) => self.find_keyword(rest.trim_start(), "DISTINCT") == Some(0),
+            None => false,
+        }
+    }
+
     /// Given a SQL Consult, we extract the columns and values for an INSERT INTO query
     ///
     /// Example
@@ -71,7 +111,7 @@ impl Extractor {
 
         let count_single_quotes = query.matches('\'').count();
 
-        if count_single_quotes % 2 != 0 {
+        if !count_single_quotes.is_multiple_of(2) {
             return Err(Tperrors::Syntax(
                 "Invalid INSERT query (Unbalanced single quotes)".to_string(),
             ));
@@ -167,73 +207,77 @@ impl Extractor {
         Ok((columns, values))
     }
 
-    /// This one is tricky.
-    ///
     /// Given a SQL Consult, we extract the columns and values for an UPDATE query
     ///
     /// but since update can miss the where condition, we need to handle that case.
     ///
-    /// Example: UPDATE users SET name = 'John', age = 20 WHERE id = 3;
+    /// Each value is the expression written after the =, as is (it's computed later
+    /// for every row), so quotes are kept and ```'a=b'``` or ```Edad + 1``` are valid values.
+    ///
+    /// Example: UPDATE users SET name = 'John', age = age + 1 WHERE id = 3;
     ///
-    /// This would return ```(["name", "age"], ["John", "20"])```
+    /// This would return ```(["name", "age"], ["'John'", "age + 1"])```
     ///
     pub fn extract_columns_and_values_for_update(
         &self,
         query: &str,
     ) -> Result<(Vec<String>, Vec<String>), Tperrors> {
-        let (start_columns, end_columns) = match (query.find("SET"), query.find("WHERE")) {
-            (Some(start), Some(end)) => (start, end),
-            (Some(start), None) => (start, query.len() - 1), // no WHERE, it means ALL tables.., risky..
-            _ => {
-                return Err(Tperrors::Syntax(
-                    "Invalid UPDATE query (Missing SET or WHERE)".to_string(),
-                ));
-            }
-        };
+        let query = query.trim();
 
         // lets check unbalanced '
         let count_single_quotes = query.matches('\'').count();
 
-        if count_single_quotes % 2 != 0 {
+        if !count_single_quotes.is_multiple_of(2) {
             return Err(Tperrors::Syntax(
                 "Invalid UPDATE query (Unbalanced single quotes)".to_string(),
             ));
         }
 
-        let columns_str = &query[start_columns + "SET".len()..end_columns].trim();
+        let start_columns = match self.find_keyword(query, "SET") {
+            Some(start) => start + "SET".len(),
+            None => {
+                return Err(Tperrors::Syntax(
+                    "Invalid UPDATE query (Missing SET or WHERE)".to_string(),
+                ));
+            }
+        };
+        // no WHERE, it means ALL rows (rejected by the safe updates mode of the binary)
+        let end_columns = match self.find_keyword(query, "WHERE") {
+            Some(end) if end > start_columns => end,
+            _ => query.rfind(';').unwrap_or(query.len()),
+        };
 
-        let tmp_what_to_update: Vec<String> = columns_str
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .collect();
+        self.parser_assignments(&query[start_columns..end_columns])
+    }
 
+    /// Given the assignments of a SET (```name = 'John', age = age + 1```)
+    ///
+    /// returns the columns and the expressions of their values
+    fn parser_assignments(&self, text: &str) -> Result<(Vec<String>, Vec<String>), Tperrors> {
         let mut columns: Vec<String> = Vec::new();
         let mut values: Vec<String> = Vec::new();
 
         // we need to split by , and = to get the columns and values
-        for what_to_update in tmp_what_to_update {
-            let what_to_update = what_to_update.trim();
-            let what_to_update = what_to_update.split('=').collect::<Vec<&str>>();
-
-            if what_to_update.len() != 2 {
-                return Err(Tperrors::Syntax(
-                    "Invalid UPDATE query (Missing =)".to_string(),
-                ));
+        // (the ones inside quotes or parenthesis are part of a value)
+        for what_to_update in self.split_outside_quotes_and_parenthesis(text, ',') {
+            let (column, value) = match self.find_outside_quotes(what_to_update, '=') {
+                Some(position) => (
+                    what_to_update[..position].trim(),
+                    what_to_update[position + 1..].trim(),
+                ),
+                None => {
+                    return Err(Tperrors::Syntax("Invalid SET (Missing =)".to_string()));
+                }
+            };
+            if column.is_empty() || value.is_empty() {
+                return Err(Tperrors::Syntax(format!(
+                    "Invalid SET (Missing column or value near {})",
+                    what_to_update.trim()
+                )));
             }
 
-            let column = what_to_update[0]
-                .trim()
-                .trim_matches('\'')
-                .trim_matches('\"')
-                .to_string();
-            let value = what_to_update[1]
-                .trim()
-                .trim_matches('\'')
-                .trim_matches('\"')
-                .to_string();
-
-            columns.push(column);
-            values.push(value);
+            columns.push(column.trim_matches('\'').trim_matches('\"').to_string());
+            values.push(value.to_string());
         }
         Ok((columns, values))
     }
@@ -276,6 +320,10 @@ impl Extractor {
     ///
     /// ```DELETE FROM users WHERE id = 3;``` -> gets DELETE as start and FROM as end, offset will be the length of DELETE
     ///
+    /// ```MERGE INTO users USING export ON ...;``` -> gets INTO as start and USING as end, offset will be the length of INTO
+    ///
+    /// ```TRUNCATE TABLE users;``` -> gets TABLE as start and ; as end, offset will be the length of TABLE
+    ///
     fn extract_positions(&self, query: &str, consult: SQLCommand) -> (usize, usize, usize) {
         let query = query.trim();
 
@@ -284,7 +332,7 @@ impl Extractor {
                 let start = query.find("FROM").unwrap_or(0);
                 (start, "FROM".len())
             }
-            SQLCommand::Insert => {
+            SQLCommand::Insert | SQLCommand::Merge => {
                 let start = query.find("INTO").unwrap_or(0);
                 (start, "INTO".len())
             }
@@ -292,29 +340,39 @@ impl Extractor {
                 let start = query.find("UPDATE").unwrap_or(0);
                 (start, "UPDATE".len())
             }
+            // TABLE is optional, ```TRUNCATE users;``` is also valid
+            SQLCommand::Truncate => match self.find_keyword(query, "TABLE") {
+                Some(start) => (start, "TABLE".len()),
+                None => (query.find("TRUNCATE").unwrap_or(0), "TRUNCATE".len()),
+            },
+            // the name of a view is read by parser_create_view and parser_drop_view
+            SQLCommand::CreateView | SQLCommand::DropView | SQLCommand::DropMaterializedView => {
+                (0, 0)
+            }
         };
 
         let end = match consult {
-            SQLCommand::Select => match query.find("WHERE").or(query.find("ORDER")) {
-                Some(pos) => pos,
-                None => query.find(";").unwrap_or(0),
-            },
-            SQLCommand::Insert => {
-                //query.find("(").unwrap_or(0)
-                let possible_end = query.find("(").unwrap_or(0);
-                let values_start = query.find("VALUES").unwrap_or(0);
-
-                if possible_end < values_start {
-                    possible_end
-                } else {
-                    values_start
-                }
-            }
+            SQLCommand::Select => self.clause_end(query, start + offset).unwrap_or(0),
+            // the table ends on its columns, on VALUES or on the SELECT that gives the rows
+            SQLCommand::Insert => query
+                .find("(")
+                .into_iter()
+                .chain(
+                    ["VALUES", "SELECT", "WITH"]
+                        .iter()
+                        .filter_map(|keyword| self.find_keyword(query, keyword)),
+                )
+                .min()
+                .unwrap_or(0),
             SQLCommand::Update => query.find("SET").unwrap_or(0),
             SQLCommand::Delete => match query.find("WHERE") {
                 Some(pos) => pos,
                 None => query.find(";").unwrap_or(0),
             },
+            // the target (with its alias, if it has one) ends on the source
+            SQLCommand::Merge => self.find_keyword(query, "USING").unwrap_or(0),
+            SQLCommand::Truncate => query.find(";").unwrap_or(0),
+            SQLCommand::CreateView | SQLCommand::DropView | SQLCommand::DropMaterializedView => 0,
         };
         (start, offset, end)
     }
@@ -326,42 +384,774 @@ impl Extractor {
     /// ```SELECT * FROM users WHERE id = 3;```
     /// Returns ```id = 3```
     pub fn extract_as_str_conditions<'a>(&self, query: &'a str) -> Option<&'a str> {
+        self.extract_clause(query, "WHERE")
+    }
+
+    /// Given a query, we extract the ORDER BY columns and if they are ASC or DESC
+    pub fn extract_orderby_as_str<'a>(&self, query: &'a str) -> Option<&'a str> {
+        self.extract_clause(query, "ORDER BY")
+    }
+
+    /// Given a query, we extract the GROUP BY expressions as string.
+    ///
+    /// Example
+    ///
+    /// ```SELECT id_cliente, SUM(cantidad) FROM ordenes GROUP BY id_cliente;```
+    /// Returns ```id_cliente```
+    pub fn extract_groupby_as_str<'a>(&self, query: &'a str) -> Option<&'a str> {
+        self.extract_clause(query, "GROUP BY")
+    }
+
+    /// Given a query, we extract the HAVING condition as string.
+    ///
+    /// Example
+    ///
+    /// ```SELECT id_cliente FROM ordenes GROUP BY id_cliente HAVING SUM(cantidad) > 1;```
+    /// Returns ```SUM(cantidad) > 1```
+    pub fn extract_having_as_str<'a>(&self, query: &'a str) -> Option<&'a str> {
+        self.extract_clause(query, "HAVING")
+    }
+
+    /// Given a query, we extract how many rows to return and how many to skip.
+    ///
+    /// Both ```LIMIT n``` and ```FETCH FIRST n ROWS ONLY``` are accepted (but not together),
+    /// ```OFFSET m``` may be followed by ```ROWS```
+    ///
+    /// Example
+    ///
+    /// ```SELECT * FROM clientes ORDER BY Edad LIMIT 3 OFFSET 2;```
+    /// Returns ```(Some(3), 2)```
+    pub fn extract_limit_and_offset(
+        &self,
+        query: &str,
+    ) -> Result<(Option<usize>, usize), Tperrors> {
+        let limit = match self.extract_clause(query, "LIMIT") {
+            Some(limit) => Some(self.parse_amount_of_rows(limit, "LIMIT")?),
+            None => None,
+        };
+        let fetch = match self.extract_clause(query, "FETCH") {
+            Some(fetch) => Some(self.parse_fetch(fetch)?),
+            None => None,
+        };
+        let offset = match self.extract_clause(query, "OFFSET") {
+            Some(offset) => {
+                let offset = offset
+                    .strip_suffix("ROWS")
+                    .or_else(|| offset.strip_suffix("ROW"))
+                    .unwrap_or(offset);
+                self.parse_amount_of_rows(offset, "OFFSET")?
+            }
+            None => 0,
+        };
+
+        match (limit, fetch) {
+            (Some(_), Some(_)) => Err(Tperrors::Syntax(
+                "LIMIT and FETCH can't be used on the same query".to_string(),
+            )),
+            (limit, fetch) => Ok((limit.or(fetch), offset)),
+        }
+    }
+
+    /// Given a query, we extract the FROM clause as string (the tables and their joins).
+    ///
+    /// Example
+    ///
+    /// ```SELECT * FROM ordenes o JOIN clientes c ON o.id_cliente = c.Id WHERE c.Edad > 30;```
+    /// Returns ```ordenes o JOIN clientes c ON o.id_cliente = c.Id```
+    pub fn extract_from_as_str<'a>(&self, query: &'a str) -> Option<&'a str> {
+        self.extract_clause(query, "FROM")
+    }
+
+    /// Given a FROM clause (previously filtered with extract_from_as_str)
+    ///
+    /// Returns the first table and the tables joined to it, with their aliases
+    /// and the conditions of the joins
+    pub fn parser_from_clause(&self, str_from: &str) -> Result<FromClause, Tperrors> {
+        let mut positions: Vec<usize> = Vec::new();
+        let mut offset = 0;
+        while let Some(position) = self.find_keyword(&str_from[offset..], "JOIN") {
+            positions.push(offset + position);
+            offset += position + "JOIN".len();
+        }
+
+        // each JOIN ends the text of the previous table, including the kind of the join
+        let mut segments: Vec<&str> = Vec::new();
+        let mut kinds: Vec<JoinKind> = Vec::new();
+        let mut start = 0;
+        for position in positions {
+            let (segment, kind) = self.split_join_kind(&str_from[start..position]);
+            segments.push(segment);
+            kinds.push(kind);
+            start = position + "JOIN".len();
+        }
+        segments.push(&str_from[start..]);
+
+        let table = self.parser_table_reference(segments[0])?;
+        let mut joins: Vec<JoinClause> = Vec::new();
+        for (segment, kind) in segments[1..].iter().zip(kinds) {
+            let (table, condition) = match (self.find_keyword(segment, "ON"), &kind) {
+                (None, JoinKind::Cross) => (*segment, None),
+                (Some(_), JoinKind::Cross) => {
+                    return Err(Tperrors::Syntax(format!(
+                        "Invalid CROSS JOIN, it can't have a condition near {}",
+                        segment.trim()
+                    )));
+                }
+                (Some(position), _) => (
+                    &segment[..position],
+                    Some(segment[position + "ON".len()..].trim()),
+                ),
+                (None, _) => {
+                    return Err(Tperrors::Syntax(format!(
+                        "Invalid JOIN, missing ON near {}",
+                        segment.trim()
+                    )));
+                }
+            };
+            if condition.is_some_and(|c| c.is_empty()) {
+                return Err(Tperrors::Syntax("incomplete input".to_string()));
+            }
+            joins.push(JoinClause {
+                kind,
+                table: self.parser_table_reference(table)?,
+                condition: condition.map(|c| c.to_string()),
+            });
+        }
+        Ok(FromClause { table, joins })
+    }
+
+    /// Given a query, splits it on its UNION, INTERSECT and EXCEPT
+    ///
+    /// Returns None if the query is a single SELECT. Otherwise, every SELECT ends with ;
+    /// and the ORDER BY, LIMIT and OFFSET at the end of the last one are applied to the
+    /// combined rows.
+    ///
+    /// Example
+    ///
+    /// ```SELECT Nombre FROM clientes UNION ALL SELECT producto FROM ordenes ORDER BY Nombre;```
+    pub fn parser_set_operations(&self, query: &str) -> Result<Option<CompoundQuery>, Tperrors> {
         let query = query.trim();
 
-        if let Some(pos) = query.find("WHERE") {
-            // we need to concat the vector to that position
-            let end = match query.find("ORDER").or(query.find(";")) {
-                Some(end) => end,
-                None => {
-                    return None;
+        let mut selects: Vec<&str> = Vec::new();
+        let mut operators: Vec<SetOperator> = Vec::new();
+        let mut start = 0;
+        while let Some((position, keyword)) = SET_OPERATORS
+            .iter()
+            .filter_map(|keyword| Some((self.find_keyword(&query[start..], keyword)?, *keyword)))
+            .min()
+        {
+            selects.push(&query[start..start + position]);
+            start += position + keyword.len();
+
+            // ALL is the next word after the operator
+            let rest = query[start..].trim_start();
+            let all = self.find_keyword(rest, "ALL") == Some(0);
+            if all {
+                start = query.len() - rest.len() + "ALL".len();
+            }
+            let operator = match (keyword, all) {
+                ("UNION", true) => SetOperator::UnionAll,
+                ("UNION", false) => SetOperator::Union,
+                ("INTERSECT", false) => SetOperator::Intersect,
+                ("EXCEPT", false) => SetOperator::Except,
+                _ => {
+                    return Err(Tperrors::Syntax(format!(
+                        "{} ALL is not supported",
+                        keyword
+                    )));
                 }
             };
-            let conditions = &query[pos + "WHERE".len()..end].trim();
+            operators.push(operator);
+        }
+        if selects.is_empty() {
+            return Ok(None);
+        }
 
-            Some(conditions)
-        } else {
-            // no conditions, but maybe ordered by..
-            None
+        // the clauses at the end belong to the combined rows, not to the last SELECT
+        let last = query[start..].trim().trim_end_matches(';');
+        let final_clauses = FINAL_CLAUSES
+            .iter()
+            .filter_map(|clause| self.find_keyword(last, clause))
+            .min()
+            .unwrap_or(last.len());
+        selects.push(&last[..final_clauses]);
+        let final_clauses = format!("{};", &last[final_clauses..]);
+
+        let mut selects = selects
+            .iter()
+            .map(|select| {
+                let select = select.trim();
+                if FINAL_CLAUSES
+                    .iter()
+                    .any(|clause| self.find_keyword(select, clause).is_some())
+                {
+                    return Err(Tperrors::Syntax(
+                        "ORDER BY, LIMIT and OFFSET can only be used at the end of the query"
+                            .to_string(),
+                    ));
+                }
+                if !select.starts_with("SELECT") {
+                    return Err(Tperrors::Syntax(format!(
+                        "Invalid query near {}, expected a SELECT",
+                        select
+                    )));
+                }
+                Ok(format!("{};", select))
+            })
+            .collect::<Result<Vec<String>, Tperrors>>()?;
+
+        let sorting = self
+            .extract_orderby_as_str(&final_clauses)
+            .map(|sorting| self.parser_orderby_from_str_to_vec(sorting));
+        let (limit, offset) = self.extract_limit_and_offset(&final_clauses)?;
+
+        let first = selects.remove(0);
+        Ok(Some(CompoundQuery {
+            first,
+            operations: operators.into_iter().zip(selects).collect(),
+            sorting,
+            limit,
+            offset,
+        }))
+    }
+
+    /// Given a query that starts with WITH, returns the tables defined and the query that follows
+    ///
+    /// Returns None if the query doesn't start with WITH
+    ///
+    /// Example
+    ///
+    /// ```WITH RECURSIVE t(n) AS (SELECT ...), u AS (SELECT ...) SELECT * FROM t;```
+    pub fn parser_with_clause(&self, query: &str) -> Result<Option<WithClause>, Tperrors> {
+        let query = query.trim();
+        let rest = match query.strip_prefix("WITH") {
+            Some(rest) if self.find_keyword(query, "WITH") == Some(0) => rest.trim_start(),
+            _ => return Ok(None),
+        };
+        let (recursive, rest) = match rest.strip_prefix("RECURSIVE") {
+            Some(after) if self.find_keyword(rest, "RECURSIVE") == Some(0) => (true, after),
+            _ => (false, rest),
+        };
+
+        // the queries of the tables are between parenthesis, so the first SELECT outside
+        // of them is the one that reads the tables
+        let body = match self.find_keyword(rest, "SELECT") {
+            Some(body) => body,
+            None => {
+                return Err(Tperrors::Syntax(
+                    "Invalid WITH, missing the SELECT after its tables".to_string(),
+                ));
+            }
+        };
+
+        let tables = self
+            .split_outside_quotes_and_parenthesis(&rest[..body], ',')
+            .iter()
+            .map(|definition| self.parser_common_table(definition))
+            .collect::<Result<Vec<CommonTable>, Tperrors>>()?;
+
+        Ok(Some(WithClause {
+            recursive,
+            tables,
+            body: rest[body..].to_string(),
+        }))
+    }
+
+    /// Parses a table of a WITH: ```name AS (SELECT ...)``` or ```name(a, b) AS (SELECT ...)```
+    fn parser_common_table(&self, definition: &str) -> Result<CommonTable, Tperrors> {
+        let definition = definition.trim();
+        let invalid = || {
+            Tperrors::Syntax(format!(
+                "Invalid WITH near {}, expected name AS (SELECT ...)",
+                definition
+            ))
+        };
+
+        let position_as = self.find_keyword(definition, "AS").ok_or_else(invalid)?;
+        let query = definition[position_as + "AS".len()..]
+            .trim()
+            .strip_prefix('(')
+            .and_then(|query| query.strip_suffix(')'))
+            .map(|query| query.trim().trim_end_matches(';').trim_end())
+            .ok_or_else(invalid)?;
+        if !query.starts_with("SELECT") {
+            return Err(invalid());
+        }
+
+        // the names of the columns are optional
+        let name = definition[..position_as].trim();
+        let (name, columns) = match name.split_once('(') {
+            Some((name, columns)) => match columns.strip_suffix(')') {
+                Some(columns) => (
+                    name.trim(),
+                    columns
+                        .split(',')
+                        .map(|column| column.trim().to_string())
+                        .collect::<Vec<String>>(),
+                ),
+                None => return Err(invalid()),
+            },
+            None => (name, Vec::new()),
+        };
+        if name.is_empty()
+            || !name.chars().all(Self::is_identifier_char)
+            || columns.iter().any(|column| column.is_empty())
+        {
+            return Err(invalid());
         }
+
+        Ok(CommonTable {
+            name: name.to_string(),
+            columns,
+            query: format!("{};", query),
+        })
     }
 
-    /// Given a query, we extract the ORDER BY columns and if they are ASC or DESC
-    pub fn extract_orderby_as_str<'a>(&self, query: &'a str) -> Option<&'a str> {
+    /// Given an ```INSERT INTO table [(columns)] SELECT ...;``` query, returns the columns
+    /// (empty if they are not given) and the query that gives the rows
+    ///
+    /// Returns None if the rows are given with VALUES
+    ///
+    /// Example
+    ///
+    /// ```INSERT INTO archivo (Id, Nombre) SELECT Id, Nombre FROM clientes WHERE Edad > 60;```
+    ///
+    /// Returns (["Id", "Nombre"], "SELECT Id, Nombre FROM clientes WHERE Edad > 60;")
+    pub fn extract_insert_select(
+        &self,
+        query: &str,
+    ) -> Result<Option<(Vec<String>, String)>, Tperrors> {
         let query = query.trim();
+        if self.find_keyword(query, "VALUES").is_some() {
+            return Ok(None);
+        }
+        let start_select = match ["SELECT", "WITH"]
+            .iter()
+            .filter_map(|keyword| self.find_keyword(query, keyword))
+            .min()
+        {
+            Some(start) => start,
+            None => return Ok(None),
+        };
 
-        if let Some(pos) = query.find("ORDER BY") {
-            let semicolon_end = match query.find(";") {
-                Some(end) => end,
+        // the columns are optional, between the table and the SELECT
+        let before_select = query[..start_select].trim_end();
+        let columns = match before_select.strip_suffix(')') {
+            Some(before) => match before.find('(') {
+                Some(start) => before[start + 1..]
+                    .split(',')
+                    .map(|s| s.trim().trim_matches('\'').trim_matches('\"').to_string())
+                    .collect::<Vec<String>>(),
                 None => {
-                    return None;
+                    return Err(Tperrors::Syntax(
+                        "Invalid INSERT query (Mismatched parentheses in columns)".to_string(),
+                    ));
                 }
-            };
+            },
+            None => Vec::new(),
+        };
+        if columns.iter().any(|column| column.is_empty()) {
+            return Err(Tperrors::Syntax(
+                "Invalid INSERT query (Missing columns)".to_string(),
+            ));
+        }
+        Ok(Some((columns, query[start_select..].to_string())))
+    }
 
-            let orderby = &query[pos + "ORDER BY".len()..semicolon_end].trim();
-            Some(orderby)
+    /// Given an INSERT, UPDATE or DELETE query, splits its RETURNING clause (if it exists)
+    ///
+    /// Returns the query without the clause (still ending with ;) and its elements
+    ///
+    /// Example
+    ///
+    /// ```DELETE FROM clientes WHERE Id = 1 RETURNING Id, Nombre;```
+    ///
+    /// Returns ("DELETE FROM clientes WHERE Id = 1;", Some(["Id", "Nombre"]))
+    pub fn extract_returning(&self, query: &str) -> Result<(String, Option<Returning>), Tperrors> {
+        let query = query.trim();
+        let start = match self.find_keyword(query, "RETURNING") {
+            Some(start) => start,
+            None => return Ok((query.to_string(), None)),
+        };
+
+        let items = query[start + "RETURNING".len()..]
+            .trim()
+            .trim_end_matches(';')
+            .trim();
+        let items = self
+            .split_outside_quotes_and_parenthesis(items, ',')
+            .into_iter()
+            .map(|item| item.trim().to_string())
+            .collect::<Vec<String>>();
+        if items.iter().any(|item| item.is_empty()) {
+            return Err(Tperrors::Syntax(
+                "Invalid RETURNING clause (Missing columns)".to_string(),
+            ));
+        }
+        Ok((
+            format!("{};", query[..start].trim_end()),
+            Some(Returning::new(items)),
+        ))
+    }
+
+    /// Given an INSERT query, splits its ON CONFLICT clause (if it exists)
+    ///
+    /// Returns the query without the clause (still ending with ;) and the clause
+    ///
+    /// Example
+    ///
+    /// ```INSERT INTO clientes VALUES (1, Juan) ON CONFLICT (Id) DO UPDATE SET Nombre = EXCLUDED.Nombre;```
+    ///
+    /// Returns ("INSERT INTO clientes VALUES (1, Juan);", Some(key ["Id"], Update ["Nombre"] ["EXCLUDED.Nombre"]))
+    pub fn extract_on_conflict(
+        &self,
+        query: &str,
+    ) -> Result<(String, Option<OnConflict>), Tperrors> {
+        let query = query.trim();
+        let invalid = || {
+            Tperrors::Syntax(
+                "Invalid ON CONFLICT, expected ON CONFLICT (columns) DO NOTHING or DO UPDATE SET ..."
+                    .to_string(),
+            )
+        };
+        let start = match self.find_keyword(query, "ON CONFLICT") {
+            Some(start) => start,
+            None => return Ok((query.to_string(), None)),
+        };
+        let clause = query[start + "ON CONFLICT".len()..]
+            .trim()
+            .trim_end_matches(';')
+            .trim();
+
+        // the key is between parenthesis
+        let key_end = match (clause.starts_with('('), clause.find(')')) {
+            (true, Some(end)) => end,
+            _ => return Err(invalid()),
+        };
+        let key = clause[1..key_end]
+            .split(',')
+            .map(|column| {
+                column
+                    .trim()
+                    .trim_matches('\'')
+                    .trim_matches('\"')
+                    .to_string()
+            })
+            .collect::<Vec<String>>();
+        if key.iter().any(|column| column.is_empty()) {
+            return Err(invalid());
+        }
+
+        let action = clause[key_end + 1..].trim();
+        let action = if action == "DO NOTHING" {
+            ConflictAction::Nothing
         } else {
-            None
+            let assignments = self
+                .strip_keywords(action, &["DO", "UPDATE", "SET"])
+                .ok_or_else(invalid)?;
+            let (columns, values) = self.parser_assignments(assignments)?;
+            ConflictAction::Update { columns, values }
+        };
+        Ok((
+            format!("{};", query[..start].trim_end()),
+            Some(OnConflict::new(key, action)),
+        ))
+    }
+
+    /// Given a MERGE query, returns its target, its source, its ON condition and its WHEN clauses
+    ///
+    /// Example
+    ///
+    /// ```MERGE INTO clientes c USING exportacion e ON c.Id = e.Id WHEN MATCHED AND e.Edad > c.Edad THEN UPDATE SET Edad = e.Edad WHEN NOT MATCHED THEN INSERT (Id, Nombre) VALUES (e.Id, e.Nombre) WHEN NOT MATCHED BY SOURCE THEN DELETE;```
+    pub fn parser_merge(&self, query: &str) -> Result<MergeQuery, Tperrors> {
+        let invalid = |near: &str| {
+            Tperrors::Syntax(format!(
+                "Invalid MERGE, expected MERGE INTO target USING source ON condition WHEN ... near {}",
+                near.trim()
+            ))
+        };
+        let query = query.trim();
+        let body = match query.strip_suffix(';') {
+            Some(body) => body,
+            None => return Err(invalid(query)),
+        };
+        let body = self
+            .strip_keywords(body, &["MERGE", "INTO"])
+            .ok_or_else(|| invalid(body))?;
+
+        let using = self
+            .find_keyword(body, "USING")
+            .ok_or_else(|| invalid(body))?;
+        let target = self.parser_table_reference(&body[..using])?;
+        if target.subquery.is_some() {
+            return Err(invalid(&body[..using]));
+        }
+
+        let rest = &body[using + "USING".len()..];
+        let on = self.find_keyword(rest, "ON").ok_or_else(|| invalid(rest))?;
+        let source = self.parser_table_reference(&rest[..on])?;
+
+        // every WHEN starts a clause, the condition ends on the first one
+        let rest = &rest[on + "ON".len()..];
+        let mut whens: Vec<usize> = Vec::new();
+        let mut offset = 0;
+        while let Some(position) = self.find_keyword(&rest[offset..], "WHEN") {
+            whens.push(offset + position);
+            offset += position + "WHEN".len();
+        }
+        let condition = match whens.first() {
+            Some(first) => rest[..*first].trim(),
+            None => return Err(invalid(rest)),
+        };
+        if condition.is_empty() {
+            return Err(invalid(rest));
+        }
+
+        let mut clauses: Vec<MergeClause> = Vec::new();
+        for (i, start) in whens.iter().enumerate() {
+            let end = whens.get(i + 1).copied().unwrap_or(rest.len());
+            clauses.push(self.parser_merge_clause(&rest[start + "WHEN".len()..end])?);
+        }
+
+        Ok(MergeQuery {
+            target,
+            source,
+            on: condition.to_string(),
+            clauses,
+        })
+    }
+
+    /// Parses a WHEN clause of a MERGE (without the WHEN)
+    ///
+    /// Example: ```MATCHED AND e.Edad > c.Edad THEN UPDATE SET Edad = e.Edad```
+    fn parser_merge_clause(&self, clause: &str) -> Result<MergeClause, Tperrors> {
+        let invalid = || Tperrors::Syntax(format!("Invalid WHEN clause near {}", clause.trim()));
+
+        let then = self.find_keyword(clause, "THEN").ok_or_else(invalid)?;
+        // a space at the end so the last keyword is found like the others
+        let head = format!("{} ", clause[..then].trim());
+        let (case, rest) = [
+            (
+                MergeCase::NotMatchedBySource,
+                &["NOT", "MATCHED", "BY", "SOURCE"][..],
+            ),
+            (
+                MergeCase::NotMatchedByTarget,
+                &["NOT", "MATCHED", "BY", "TARGET"][..],
+            ),
+            (MergeCase::NotMatchedByTarget, &["NOT", "MATCHED"][..]),
+            (MergeCase::Matched, &["MATCHED"][..]),
+        ]
+        .into_iter()
+        .find_map(|(case, keywords)| {
+            self.strip_keywords(&head, keywords)
+                .map(|rest| (case, rest.trim()))
+        })
+        .ok_or_else(invalid)?;
+
+        let condition = match rest {
+            "" => None,
+            _ => match self.strip_keywords(&format!("{} ", rest), &["AND"]) {
+                Some(condition) if !condition.trim().is_empty() => {
+                    Some(condition.trim().to_string())
+                }
+                _ => return Err(invalid()),
+            },
+        };
+
+        let action = clause[then + "THEN".len()..].trim();
+        let action = match action {
+            "DELETE" => MergeAction::Delete,
+            "DO NOTHING" => MergeAction::Nothing,
+            _ => match (
+                self.strip_keywords(action, &["UPDATE", "SET"]),
+                self.strip_keywords(action, &["INSERT"]),
+            ) {
+                (Some(assignments), _) => {
+                    let (columns, values) = self.parser_assignments(assignments)?;
+                    MergeAction::Update { columns, values }
+                }
+                (None, Some(insert)) => self.parser_merge_insert(insert)?,
+                _ => return Err(invalid()),
+            },
+        };
+
+        // an INSERT needs a row of the source, UPDATE and DELETE a row of the target
+        let valid = match (&case, &action) {
+            (_, MergeAction::Nothing) => true,
+            (MergeCase::NotMatchedByTarget, action) => matches!(action, MergeAction::Insert { .. }),
+            (_, action) => !matches!(action, MergeAction::Insert { .. }),
+        };
+        if !valid {
+            return Err(Tperrors::Syntax(format!(
+                "Invalid WHEN clause, only WHEN NOT MATCHED can INSERT and it can't UPDATE or DELETE, near {}",
+                clause.trim()
+            )));
+        }
+
+        Ok(MergeClause {
+            case,
+            condition,
+            action,
+        })
+    }
+
+    /// Parses the INSERT of a WHEN NOT MATCHED clause (without the INSERT)
+    ///
+    /// Example: ```(Id, Nombre) VALUES (e.Id, e.Nombre)```
+    fn parser_merge_insert(&self, insert: &str) -> Result<MergeAction, Tperrors> {
+        let invalid = || Tperrors::Syntax(format!("Invalid INSERT near {}", insert.trim()));
+
+        let values_start = self.find_keyword(insert, "VALUES").ok_or_else(invalid)?;
+        let between_parenthesis = |text: &str| -> Result<Vec<String>, Tperrors> {
+            let inner = text
+                .trim()
+                .strip_prefix('(')
+                .and_then(|text| text.strip_suffix(')'))
+                .ok_or_else(invalid)?;
+            let parts = self
+                .split_outside_quotes_and_parenthesis(inner, ',')
+                .into_iter()
+                .map(|part| part.trim().to_string())
+                .collect::<Vec<String>>();
+            match parts.iter().any(|part| part.is_empty()) {
+                true => Err(invalid()),
+                false => Ok(parts),
+            }
+        };
+
+        let columns = match insert[..values_start].trim() {
+            "" => Vec::new(),
+            columns => between_parenthesis(columns)?
+                .into_iter()
+                .map(|column| column.trim_matches('\'').trim_matches('\"').to_string())
+                .collect(),
+        };
+        let values = between_parenthesis(&insert[values_start + "VALUES".len()..])?;
+        Ok(MergeAction::Insert { columns, values })
+    }
+
+    /// Given a ```CREATE TABLE name AS SELECT ...;``` query, returns the name of the table
+    /// and its query
+    pub fn parser_create_table(&self, query: &str) -> Result<(String, String), Tperrors> {
+        let invalid = || {
+            Tperrors::Syntax(
+                "Invalid CREATE TABLE, expected CREATE TABLE name AS SELECT ...;".to_string(),
+            )
+        };
+        let definition = self
+            .strip_keywords(query.trim(), &["CREATE", "TABLE"])
+            .ok_or_else(invalid)?;
+
+        let position_as = self.find_keyword(definition, "AS").ok_or_else(invalid)?;
+        let name = definition[..position_as].trim();
+        let select = definition[position_as + "AS".len()..].trim();
+        if !FolderFile::valid_name(name)
+            || !(select.starts_with("SELECT") || select.starts_with("WITH"))
+            || !select.ends_with(';')
+        {
+            return Err(invalid());
+        }
+        Ok((name.to_string(), select.to_string()))
+    }
+
+    /// Given a CREATE [MATERIALIZED] VIEW query, returns the name of the view and its query
+    ///
+    /// Example
+    ///
+    /// ```CREATE VIEW mayores AS SELECT * FROM clientes WHERE Edad > 40;```
+    ///
+    /// Returns ("mayores", "SELECT * FROM clientes WHERE Edad > 40;")
+    pub fn parser_create_view(&self, query: &str) -> Result<(String, String), Tperrors> {
+        let invalid = || {
+            Tperrors::Syntax(
+                "Invalid CREATE VIEW, expected CREATE [MATERIALIZED] VIEW name AS SELECT ...;"
+                    .to_string(),
+            )
+        };
+        let query = query.trim();
+        let definition = self
+            .strip_keywords(query, &["CREATE", "VIEW"])
+            .or_else(|| self.strip_keywords(query, &["CREATE", "MATERIALIZED", "VIEW"]))
+            .ok_or_else(invalid)?;
+
+        let position_as = self.find_keyword(definition, "AS").ok_or_else(invalid)?;
+        let name = definition[..position_as].trim();
+        let select = definition[position_as + "AS".len()..].trim();
+        if !FolderFile::valid_name(name)
+            || !(select.starts_with("SELECT") || select.starts_with("WITH"))
+            || !select.ends_with(';')
+        {
+            return Err(invalid());
+        }
+        Ok((name.to_string(), select.to_string()))
+    }
+
+    /// Given a DROP [MATERIALIZED] VIEW query, returns the name of the view and if IF EXISTS was used
+    ///
+    /// Example: ```DROP VIEW IF EXISTS mayores;``` returns ("mayores", true)
+    pub fn parser_drop_view(&self, query: &str) -> Result<(String, bool), Tperrors> {
+        let invalid = || {
+            Tperrors::Syntax(
+                "Invalid DROP VIEW, expected DROP [MATERIALIZED] VIEW [IF EXISTS] name;"
+                    .to_string(),
+            )
+        };
+        let query = query.trim();
+        let rest = self
+            .strip_keywords(query, &["DROP", "VIEW"])
+            .or_else(|| self.strip_keywords(query, &["DROP", "MATERIALIZED", "VIEW"]))
+            .ok_or_else(invalid)?;
+        let (if_exists, rest) = match self.strip_keywords(rest, &["IF", "EXISTS"]) {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        let name = rest.strip_suffix(';').ok_or_else(invalid)?.trim();
+        if !FolderFile::valid_name(name) {
+            return Err(invalid());
+        }
+        Ok((name.to_string(), if_exists))
+    }
+
+    /// Given a REFRESH MATERIALIZED VIEW query, returns the name of the view
+    ///
+    /// Example: ```REFRESH MATERIALIZED VIEW totales;``` returns "totales"
+    pub fn parser_refresh_view(&self, query: &str) -> Result<String, Tperrors> {
+        let name = self
+            .strip_keywords(query.trim(), &["REFRESH", "MATERIALIZED", "VIEW"])
+            .and_then(|rest| rest.strip_suffix(';'))
+            .map(|name| name.trim())
+            .filter(|name| FolderFile::valid_name(name));
+        match name {
+            Some(name) => Ok(name.to_string()),
+            None => Err(Tperrors::Syntax(
+                "Invalid REFRESH, expected REFRESH MATERIALIZED VIEW name;".to_string(),
+            )),
+        }
+    }
+
+    /// Removes the given keywords from the start of the query (as whole words)
+    ///
+    /// Returns what follows them, or None if the query doesn't start with them
+    fn strip_keywords<'a>(&self, query: &'a str, keywords: &[&str]) -> Option<&'a str> {
+        let mut rest = query;
+        for keyword in keywords {
+            rest = match rest.strip_prefix(keyword) {
+                Some(after) if after.starts_with(char::is_whitespace) => after.trim_start(),
+                _ => return None,
+            };
         }
+        Some(rest)
+    }
+
+    /// Given a GROUP BY clause (previously filtered with extract_groupby_as_str)
+    ///
+    /// Returns each expression to group by
+    pub fn parser_groupby_from_str_to_vec(&self, str_groupby: &str) -> Vec<String> {
+        self.split_outside_quotes_and_parenthesis(str_groupby, ',')
+            .iter()
+            .map(|part| part.trim().to_string())
+            .collect()
     }
 
     /// Given a parsed ORDER by clause (previously filtered with extract_orderby_as_str)
@@ -371,21 +1161,254 @@ impl Extractor {
     /// True means its gonna be ASC, False means its gonna be DESC
     ///
     pub fn parser_orderby_from_str_to_vec(&self, str_orderby: &str) -> Vec<SortMethod> {
-        str_orderby
-            .split(',')
+        self.split_outside_quotes_and_parenthesis(str_orderby, ',')
+            .iter()
             .map(|part| {
-                let parts: Vec<&str> = part.split_whitespace().collect();
-                let column = parts[0].to_string();
+                let part = part.trim();
                 // Default to ascending order if no direction is specified
-                // clippy witch.
-                let asc = !(parts.len() == 2 && parts[1].eq("DESC"));
+                let (column, asc) = if let Some(column) = part.strip_suffix(" DESC") {
+                    (column, false)
+                } else if let Some(column) = part.strip_suffix(" ASC") {
+                    (column, true)
+                } else {
+                    (part, true)
+                };
                 SortMethod {
-                    by_column: column,
+                    by_column: column.trim().to_string(),
                     ascending: asc,
                 }
             })
             .collect()
     }
+
+    /// Finds the position of a keyword on the query
+    ///
+    /// Only whole words outside quotes and parenthesis are considered, so
+    /// ```SELECT * FROM t WHERE Nombre = 'FROM';``` finds the first FROM only.
+    pub fn find_keyword(&self, query: &str, keyword: &str) -> Option<usize> {
+        let mut depth = 0;
+        let mut quote: Option<char> = None;
+        let mut previous: Option<char> = None;
+
+        for (i, c) in query.char_indices() {
+            match (quote, c) {
+                (Some(q), _) if q == c => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '\"') => quote = Some(c),
+                (None, '(') => depth += 1,
+                (None, ')') => depth -= 1,
+                (None, _) => {
+                    let starts_word = previous.is_none_or(|p| !Self::is_identifier_char(p));
+                    if depth == 0 && starts_word && query[i..].starts_with(keyword) {
+                        let after = query[i + keyword.len()..].chars().next();
+                        if after.is_none_or(|a| !Self::is_identifier_char(a)) {
+                            return Some(i);
+                        }
+                    }
+                }
+            }
+            previous = Some(c);
+        }
+        None
+    }
+
+    /// Parses a table of the FROM clause: ```name```, ```name alias``` or ```name AS alias```
+    ///
+    /// A derived table is a query between parenthesis, it must have an alias:
+    /// ```(SELECT ...) AS t```
+    fn parser_table_reference(&self, str_table: &str) -> Result<TableReference, Tperrors> {
+        let str_table = str_table.trim();
+        if str_table.starts_with('(') {
+            return self.parser_derived_table(str_table);
+        }
+        let words = str_table.split_whitespace().collect::<Vec<&str>>();
+        let (name, alias) = match words.as_slice() {
+            [name] => (name, None),
+            [name, "AS", alias] | [name, alias] => (name, Some(alias.to_string())),
+            _ => {
+                return Err(Tperrors::Syntax(format!(
+                    "Invalid table near {}",
+                    str_table.trim()
+                )));
+            }
+        };
+        Ok(TableReference {
+            name: name.to_string(),
+            alias,
+            subquery: None,
+        })
+    }
+
+    /// Parses a derived table of the FROM clause: ```(SELECT ...) AS t``` or ```(SELECT ...) t```
+    fn parser_derived_table(&self, str_table: &str) -> Result<TableReference, Tperrors> {
+        // the query is everything until the first space outside the parenthesis
+        let between_parenthesis = self.split_outside_quotes_and_parenthesis(str_table, ' ')[0];
+        let query = match between_parenthesis
+            .strip_prefix('(')
+            .and_then(|query| query.strip_suffix(')'))
+        {
+            Some(query) => query.trim().trim_end_matches(';').trim_end(),
+            None => {
+                return Err(Tperrors::Syntax(format!(
+                    "Invalid derived table, missing closing parenthesis near {}",
+                    str_table
+                )));
+            }
+        };
+        if !query.starts_with("SELECT") && !query.starts_with("WITH") {
+            return Err(Tperrors::Syntax(format!(
+                "Invalid derived table, expected a SELECT near {}",
+                str_table
+            )));
+        }
+
+        let words = str_table[between_parenthesis.len()..]
+            .split_whitespace()
+            .collect::<Vec<&str>>();
+        match words.as_slice() {
+            ["AS", alias] | [alias] => Ok(TableReference {
+                name: alias.to_string(),
+                alias: Some(alias.to_string()),
+                subquery: Some(format!("{};", query)),
+            }),
+            _ => Err(Tperrors::Syntax(format!(
+                "A derived table must have an alias, Example: (SELECT ...) AS t near {}",
+                str_table
+            ))),
+        }
+    }
+
+    /// Given the text before a JOIN, returns it without the kind of the join and the kind
+    ///
+    /// Example: ```clientes c LEFT OUTER``` -> ```(clientes c, Left)```
+    ///
+    /// Without a kind, it's an INNER JOIN
+    fn split_join_kind<'a>(&self, text: &'a str) -> (&'a str, JoinKind) {
+        let text = text.trim_end();
+        let (rest, last) = match text.rsplit_once(char::is_whitespace) {
+            Some((rest, last)) => (rest.trim_end(), last),
+            None => return (text, JoinKind::Inner),
+        };
+        // OUTER is optional after LEFT, RIGHT and FULL
+        let (rest, last) = match (last, rest.rsplit_once(char::is_whitespace)) {
+            ("OUTER", Some((before, kind))) if ["LEFT", "RIGHT", "FULL"].contains(&kind) => {
+                (before, kind)
+            }
+            _ => (rest, last),
+        };
+
+        match last {
+            "INNER" => (rest, JoinKind::Inner),
+            "LEFT" => (rest, JoinKind::Left),
+            "RIGHT" => (rest, JoinKind::Right),
+            "FULL" => (rest, JoinKind::Full),
+            "CROSS" => (rest, JoinKind::Cross),
+            _ => (text, JoinKind::Inner),
+        }
+    }
+
+    /// Parses the amount of rows of a LIMIT, OFFSET or FETCH clause
+    fn parse_amount_of_rows(&self, amount: &str, clause: &str) -> Result<usize, Tperrors> {
+        match amount.trim().parse::<usize>() {
+            Ok(amount) => Ok(amount),
+            Err(_) => Err(Tperrors::Syntax(format!(
+                "Invalid {}, expected a number of rows but found '{}'",
+                clause,
+                amount.trim()
+            ))),
+        }
+    }
+
+    /// Parses the clause ```FETCH {FIRST | NEXT} [n] {ROW | ROWS} ONLY```
+    ///
+    /// Without a number, a single row is fetched
+    fn parse_fetch(&self, fetch: &str) -> Result<usize, Tperrors> {
+        let words = fetch.split_whitespace().collect::<Vec<&str>>();
+
+        match words.as_slice() {
+            ["FIRST" | "NEXT", "ROW" | "ROWS", "ONLY"] => Ok(1),
+            ["FIRST" | "NEXT", amount, "ROW" | "ROWS", "ONLY"] => {
+                self.parse_amount_of_rows(amount, "FETCH")
+            }
+            _ => Err(Tperrors::Syntax(
+                "Invalid FETCH, expected FETCH FIRST n ROWS ONLY".to_string(),
+            )),
+        }
+    }
+
+    /// Returns the text of a clause, from its keyword to the next clause (or the end of the query)
+    ///
+    /// Returns None if the clause isn't on the query or the query doesn't end with ;
+    fn extract_clause<'a>(&self, query: &'a str, clause: &str) -> Option<&'a str> {
+        let query = query.trim();
+        let start = self.find_keyword(query, clause)? + clause.len();
+        let end = self.clause_end(query, start)?;
+        Some(query[start..end].trim())
+    }
+
+    /// Returns the position where the clause that starts on `start` ends
+    ///
+    /// That is the next clause of the SELECT or the ; at the end
+    fn clause_end(&self, query: &str, start: usize) -> Option<usize> {
+        let rest = &query[start..];
+        SELECT_CLAUSES
+            .iter()
+            .filter_map(|clause| self.find_keyword(rest, clause))
+            .chain(rest.rfind(';'))
+            .min()
+            .map(|position| start + position)
+    }
+
+    /// Returns the position of the first character outside quotes that is the given one
+    fn find_outside_quotes(&self, text: &str, target: char) -> Option<usize> {
+        let mut quote: Option<char> = None;
+
+        for (i, c) in text.char_indices() {
+            match (quote, c) {
+                (Some(q), _) if q == c => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '\"') => quote = Some(c),
+                (None, _) if c == target => return Some(i),
+                (None, _) => {}
+            }
+        }
+        None
+    }
+
+    /// Splits a text by a separator, ignoring the ones inside quotes or parenthesis
+    ///
+    /// Example: ```CONCAT(Nombre, Apellido), 'a,b'``` -> ```["CONCAT(Nombre, Apellido)", " 'a,b'"]```
+    fn split_outside_quotes_and_parenthesis<'a>(
+        &self,
+        text: &'a str,
+        separator: char,
+    ) -> Vec<&'a str> {
+        let mut parts: Vec<&str> = Vec::new();
+        let mut depth = 0;
+        let mut quote: Option<char> = None;
+        let mut start = 0;
+
+        for (i, c) in text.char_indices() {
+            match (quote, c) {
+                (Some(q), _) if q == c => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '\"') => quote = Some(c),
+                (None, '(') => depth += 1,
+                (None, ')') => depth -= 1,
+                (None, _) if c == separator && depth == 0 => {
+                    parts.push(&text[start..i]);
+                    start = i + c.len_utf8();
+                }
+                _ => {}
+            }
+        }
+        parts.push(&text[start..]);
+        parts
+    }
+
+    fn is_identifier_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
 }
 
 #[cfg(test)]
@@ -401,6 +1424,36 @@ mod tests {
         assert_eq!(columns, vec!["name".to_string(), "age".to_string()]);
     }
 
+    #[test]
+    fn extract_columns_with_expressions_and_aliases() {
+        let extractor = Extractor::new();
+        let consult: &str =
+            "SELECT Nombre || ', ' || Apellido AS completo, Edad * 12 AS meses FROM table;";
+        let columns = extractor.extract_columns_for_select(consult).unwrap();
+
+        assert_eq!(
+            columns,
+            vec![
+                "Nombre || ', ' || Apellido AS completo".to_string(),
+                "Edad * 12 AS meses".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_columns_of_select_distinct() {
+        let extractor = Extractor::new();
+
+        let query = "SELECT DISTINCT Profesion, Edad FROM clientes;";
+
+        assert!(extractor.is_distinct_select(query));
+        assert!(!extractor.is_distinct_select("SELECT DISTINCTIVO FROM clientes;"));
+        assert_eq!(
+            extractor.extract_columns_for_select(query).unwrap(),
+            vec!["Profesion".to_string(), "Edad".to_string()]
+        );
+    }
+
     #[test]
     fn extract_table() {
         let extractor = Extractor::new();
@@ -453,6 +1506,410 @@ mod tests {
         }
     }
 
+    #[test]
+    fn groupby_and_having_clauses() {
+        let extractor = Extractor::new();
+
+        let query = "SELECT id_cliente, SUM(cantidad) FROM ordenes WHERE id > 1 GROUP BY id_cliente, producto HAVING SUM(cantidad) > 1 ORDER BY id_cliente;";
+
+        assert_eq!(extractor.extract_as_str_conditions(query), Some("id > 1"));
+        assert_eq!(
+            extractor.extract_groupby_as_str(query),
+            Some("id_cliente, producto")
+        );
+        assert_eq!(
+            extractor.extract_having_as_str(query),
+            Some("SUM(cantidad) > 1")
+        );
+        assert_eq!(extractor.extract_orderby_as_str(query), Some("id_cliente"));
+        assert_eq!(
+            extractor.extract_table(query, SQLCommand::Select).unwrap(),
+            "ordenes"
+        );
+        assert_eq!(
+            extractor.parser_groupby_from_str_to_vec("id_cliente, producto"),
+            vec!["id_cliente".to_string(), "producto".to_string()]
+        );
+    }
+
+    #[test]
+    fn from_clause_with_joins() {
+        let extractor = Extractor::new();
+
+        let query = "SELECT c.Nombre, o.producto FROM ordenes AS o JOIN clientes c ON o.id_cliente = c.Id INNER JOIN ordenes x ON x.id = o.id WHERE c.Edad > 30;";
+        let from = extractor.extract_from_as_str(query).unwrap();
+        let from = extractor.parser_from_clause(from).unwrap();
+
+        assert_eq!(
+            from.table,
+            TableReference {
+                name: "ordenes".to_string(),
+                alias: Some("o".to_string()),
+                subquery: None,
+            }
+        );
+        assert_eq!(from.joins.len(), 2);
+        assert_eq!(from.joins[0].table.qualifier(), "c");
+        assert_eq!(
+            from.joins[0].condition,
+            Some("o.id_cliente = c.Id".to_string())
+        );
+        assert_eq!(from.joins[1].kind, JoinKind::Inner);
+        assert_eq!(from.joins[1].condition, Some("x.id = o.id".to_string()));
+        assert_eq!(
+            extractor.extract_as_str_conditions(query),
+            Some("c.Edad > 30")
+        );
+
+        let from = extractor
+            .parser_from_clause("clientes c LEFT OUTER JOIN ordenes o ON o.id_cliente = c.Id FULL JOIN ordenes x ON x.id = o.id CROSS JOIN clientes")
+            .unwrap();
+        let kinds = from.joins.iter().map(|j| &j.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![&JoinKind::Left, &JoinKind::Full, &JoinKind::Cross]
+        );
+        assert_eq!(from.table.qualifier(), "c");
+        assert_eq!(from.joins[2].condition, None);
+
+        let from = extractor
+            .parser_from_clause("(SELECT producto, SUM(cantidad) AS total FROM ordenes GROUP BY producto) AS t JOIN ordenes o ON o.producto = t.producto")
+            .unwrap();
+        assert_eq!(
+            from.table,
+            TableReference {
+                name: "t".to_string(),
+                alias: Some("t".to_string()),
+                subquery: Some(
+                    "SELECT producto, SUM(cantidad) AS total FROM ordenes GROUP BY producto;"
+                        .to_string()
+                ),
+            }
+        );
+        assert_eq!(from.joins[0].table.qualifier(), "o");
+
+        let invalid_clauses = vec![
+            "ordenes o JOIN clientes c",
+            "ordenes o JOIN clientes c ON",
+            "ordenes o CROSS JOIN clientes c ON o.id_cliente = c.Id",
+            "ordenes o p JOIN clientes c ON o.id_cliente = c.Id",
+            "(SELECT * FROM ordenes)",
+            "(SELECT * FROM ordenes t",
+            "(ordenes) t",
+        ];
+        for clause in invalid_clauses {
+            assert!(extractor.parser_from_clause(clause).is_err());
+        }
+    }
+
+    #[test]
+    fn set_operations_between_selects() {
+        let extractor = Extractor::new();
+
+        let query = "SELECT Nombre FROM clientes UNION ALL SELECT producto FROM ordenes WHERE producto = 'UNION' EXCEPT SELECT Nombre FROM socios ORDER BY Nombre DESC LIMIT 2;";
+        let compound = extractor.parser_set_operations(query).unwrap().unwrap();
+        assert_eq!(compound.first, "SELECT Nombre FROM clientes;");
+        assert_eq!(
+            compound.operations,
+            vec![
+                (
+                    SetOperator::UnionAll,
+                    "SELECT producto FROM ordenes WHERE producto = 'UNION';".to_string()
+                ),
+                (
+                    SetOperator::Except,
+                    "SELECT Nombre FROM socios;".to_string()
+                ),
+            ]
+        );
+        assert_eq!(compound.limit, Some(2));
+        assert!(compound.sorting.is_some_and(|s| !s[0].is_ascending()));
+
+        let single = "SELECT Id FROM clientes WHERE Id IN (SELECT 1 FROM t UNION SELECT 2 FROM t);";
+        assert!(extractor.parser_set_operations(single).unwrap().is_none());
+
+        let invalid = vec![
+            "SELECT Id FROM clientes LIMIT 1 UNION SELECT Id FROM clientes;",
+            "SELECT Id FROM clientes INTERSECT ALL SELECT Id FROM clientes;",
+            "SELECT Id FROM clientes UNION;",
+        ];
+        for query in invalid {
+            assert!(extractor.parser_set_operations(query).is_err());
+        }
+    }
+
+    #[test]
+    fn on_conflict_is_split_from_the_query() {
+        let extractor = Extractor::new();
+
+        let (query, on_conflict) = extractor
+            .extract_on_conflict(
+                "INSERT INTO clientes (Id, Nombre) VALUES (1, Juan) ON CONFLICT (Id) DO NOTHING;",
+            )
+            .unwrap();
+        assert_eq!(query, "INSERT INTO clientes (Id, Nombre) VALUES (1, Juan);");
+        assert_eq!(
+            on_conflict,
+            Some(OnConflict::new(
+                vec!["Id".to_string()],
+                ConflictAction::Nothing
+            ))
+        );
+
+        let (_, on_conflict) = extractor
+            .extract_on_conflict("INSERT INTO clientes VALUES (1, Juan) ON CONFLICT (Id, Nombre) DO UPDATE SET Edad = EXCLUDED.Edad + 1, Profesion = 'a=b';")
+            .unwrap();
+        let expected = ConflictAction::Update {
+            columns: vec!["Edad".to_string(), "Profesion".to_string()],
+            values: vec!["EXCLUDED.Edad + 1".to_string(), "'a=b'".to_string()],
+        };
+        assert_eq!(
+            on_conflict,
+            Some(OnConflict::new(
+                vec!["Id".to_string(), "Nombre".to_string()],
+                expected
+            ))
+        );
+
+        assert!(extractor
+            .extract_on_conflict("INSERT INTO clientes VALUES (1) ON CONFLICT DO NOTHING;")
+            .is_err());
+        assert!(extractor
+            .extract_on_conflict("INSERT INTO clientes VALUES (1) ON CONFLICT (Id) DO SOMETHING;")
+            .is_err());
+    }
+
+    #[test]
+    fn merge_is_parsed_into_its_clauses() {
+        let extractor = Extractor::new();
+
+        let merge = extractor
+            .parser_merge("MERGE INTO clientes c USING (SELECT * FROM nuevos) AS n ON c.Id = n.Id WHEN MATCHED AND n.Edad > c.Edad THEN UPDATE SET Edad = n.Edad WHEN NOT MATCHED THEN INSERT (Id, Nombre) VALUES (n.Id, n.Nombre) WHEN NOT MATCHED BY SOURCE THEN DELETE;")
+            .unwrap();
+        assert_eq!(merge.target.qualifier(), "c");
+        assert_eq!(merge.source.qualifier(), "n");
+        assert_eq!(
+            merge.source.subquery,
+            Some("SELECT * FROM nuevos;".to_string())
+        );
+        assert_eq!(merge.on, "c.Id = n.Id");
+
+        let expected = vec![
+            MergeClause {
+                case: MergeCase::Matched,
+                condition: Some("n.Edad > c.Edad".to_string()),
+                action: MergeAction::Update {
+                    columns: vec!["Edad".to_string()],
+                    values: vec!["n.Edad".to_string()],
+                },
+            },
+            MergeClause {
+                case: MergeCase::NotMatchedByTarget,
+                condition: None,
+                action: MergeAction::Insert {
+                    columns: vec!["Id".to_string(), "Nombre".to_string()],
+                    values: vec!["n.Id".to_string(), "n.Nombre".to_string()],
+                },
+            },
+            MergeClause {
+                case: MergeCase::NotMatchedBySource,
+                condition: None,
+                action: MergeAction::Delete,
+            },
+        ];
+        assert_eq!(merge.clauses, expected);
+
+        // a row of the source can't be deleted, nor a row of the target inserted
+        assert!(extractor
+            .parser_merge("MERGE INTO clientes USING nuevos ON clientes.Id = nuevos.Id WHEN NOT MATCHED THEN DELETE;")
+            .is_err());
+        assert!(extractor
+            .parser_merge("MERGE INTO clientes USING nuevos ON clientes.Id = nuevos.Id WHEN MATCHED THEN INSERT VALUES (1);")
+            .is_err());
+        assert!(extractor
+            .parser_merge("MERGE INTO clientes USING nuevos ON clientes.Id = nuevos.Id;")
+            .is_err());
+    }
+
+    #[test]
+    fn returning_is_split_from_the_query() {
+        let extractor = Extractor::new();
+
+        let (query, returning) = extractor
+            .extract_returning(
+                "DELETE FROM clientes WHERE Nombre = 'RETURNING' RETURNING Id, Edad + 1 AS x;",
+            )
+            .unwrap();
+        assert_eq!(query, "DELETE FROM clientes WHERE Nombre = 'RETURNING';");
+        assert_eq!(
+            returning,
+            Some(Returning::new(vec![
+                "Id".to_string(),
+                "Edad + 1 AS x".to_string()
+            ]))
+        );
+
+        let (query, returning) = extractor
+            .extract_returning("UPDATE clientes SET Edad = 1;")
+            .unwrap();
+        assert_eq!(query, "UPDATE clientes SET Edad = 1;");
+        assert!(returning.is_none());
+
+        assert!(extractor
+            .extract_returning("DELETE FROM clientes RETURNING;")
+            .is_err());
+    }
+
+    #[test]
+    fn insert_select_and_create_table() {
+        let extractor = Extractor::new();
+
+        let query =
+            "INSERT INTO archivo (Id, Nombre) SELECT Id, Nombre FROM clientes WHERE Edad > 60;";
+        let (columns, select) = extractor.extract_insert_select(query).unwrap().unwrap();
+        assert_eq!(columns, vec!["Id", "Nombre"]);
+        assert_eq!(select, "SELECT Id, Nombre FROM clientes WHERE Edad > 60;");
+        assert_eq!(
+            extractor.extract_table(query, SQLCommand::Insert).unwrap(),
+            "archivo"
+        );
+
+        let query = "INSERT INTO archivo SELECT * FROM clientes;";
+        let (columns, _) = extractor.extract_insert_select(query).unwrap().unwrap();
+        assert!(columns.is_empty());
+        assert_eq!(
+            extractor.extract_table(query, SQLCommand::Insert).unwrap(),
+            "archivo"
+        );
+
+        let query = "INSERT INTO archivo (Nombre) VALUES ('SELECT');";
+        assert!(extractor.extract_insert_select(query).unwrap().is_none());
+
+        let (name, select) = extractor
+            .parser_create_table("CREATE TABLE viejos AS SELECT * FROM clientes;")
+            .unwrap();
+        assert_eq!(name, "viejos");
+        assert_eq!(select, "SELECT * FROM clientes;");
+        assert!(extractor
+            .parser_create_table("CREATE TABLE viejos (Id) AS SELECT * FROM clientes;")
+            .is_err());
+    }
+
+    #[test]
+    fn create_and_drop_view() {
+        let extractor = Extractor::new();
+
+        let (name, query) = extractor
+            .parser_create_view("CREATE VIEW mayores AS SELECT Nombre AS n FROM clientes;")
+            .unwrap();
+        assert_eq!(name, "mayores");
+        assert_eq!(query, "SELECT Nombre AS n FROM clientes;");
+
+        let (name, if_exists) = extractor
+            .parser_drop_view("DROP VIEW IF EXISTS v;")
+            .unwrap();
+        assert_eq!((name.as_str(), if_exists), ("v", true));
+
+        let invalid = [
+            "CREATE VIEWS v AS SELECT * FROM t;",
+            "CREATE VIEW AS SELECT * FROM t;",
+            "CREATE VIEW v AS DELETE FROM t;",
+            "CREATE VIEW v.x AS SELECT * FROM t;",
+        ];
+        for query in invalid {
+            assert!(extractor.parser_create_view(query).is_err(), "{}", query);
+        }
+        assert!(extractor.parser_drop_view("DROP VIEW v w;").is_err());
+
+        let (name, _) = extractor
+            .parser_create_view("CREATE MATERIALIZED VIEW totales AS SELECT * FROM t;")
+            .unwrap();
+        assert_eq!(name, "totales");
+        assert_eq!(
+            extractor
+                .parser_refresh_view("REFRESH MATERIALIZED VIEW totales;")
+                .unwrap(),
+            "totales"
+        );
+        assert!(extractor
+            .parser_refresh_view("REFRESH VIEW totales;")
+            .is_err());
+    }
+
+    #[test]
+    fn with_clause_tables() {
+        let extractor = Extractor::new();
+
+        let query = "WITH RECURSIVE cadena(id, nivel) AS (SELECT id, 1 FROM referidos WHERE referido_por IS NULL UNION ALL SELECT r.id, c.nivel + 1 FROM referidos r JOIN cadena c ON r.referido_por = c.id), grandes AS (SELECT * FROM ordenes WHERE cantidad > 1) SELECT * FROM cadena;";
+        let with = extractor.parser_with_clause(query).unwrap().unwrap();
+        assert!(with.recursive);
+        assert_eq!(with.tables.len(), 2);
+        assert_eq!(with.tables[0].name, "cadena");
+        assert_eq!(with.tables[0].columns, vec!["id", "nivel"]);
+        assert_eq!(
+            with.tables[1],
+            CommonTable {
+                name: "grandes".to_string(),
+                columns: Vec::new(),
+                query: "SELECT * FROM ordenes WHERE cantidad > 1;".to_string(),
+            }
+        );
+        assert_eq!(with.body, "SELECT * FROM cadena;");
+
+        assert!(extractor
+            .parser_with_clause("SELECT * FROM clientes;")
+            .unwrap()
+            .is_none());
+
+        let invalid = vec![
+            "WITH t AS (SELECT * FROM clientes);",
+            "WITH t (SELECT * FROM clientes) SELECT * FROM t;",
+            "WITH t AS SELECT * FROM clientes;",
+            "WITH t() AS (SELECT * FROM clientes) SELECT * FROM t;",
+        ];
+        for query in invalid {
+            assert!(extractor.parser_with_clause(query).is_err());
+        }
+    }
+
+    #[test]
+    fn limit_offset_and_fetch_clauses() {
+        let extractor = Extractor::new();
+
+        let query = "SELECT * FROM clientes ORDER BY Edad DESC LIMIT 3 OFFSET 2;";
+        assert_eq!(
+            extractor.extract_limit_and_offset(query).unwrap(),
+            (Some(3), 2)
+        );
+        assert_eq!(extractor.extract_orderby_as_str(query), Some("Edad DESC"));
+
+        let query = "SELECT * FROM clientes OFFSET 1 ROWS FETCH FIRST 2 ROWS ONLY;";
+        assert_eq!(
+            extractor.extract_limit_and_offset(query).unwrap(),
+            (Some(2), 1)
+        );
+        assert_eq!(
+            extractor.extract_table(query, SQLCommand::Select).unwrap(),
+            "clientes"
+        );
+
+        let query = "SELECT * FROM clientes WHERE id > 2;";
+        assert_eq!(
+            extractor.extract_limit_and_offset(query).unwrap(),
+            (None, 0)
+        );
+
+        let invalid_queries = vec![
+            "SELECT * FROM clientes LIMIT -1;",
+            "SELECT * FROM clientes LIMIT tres;",
+            "SELECT * FROM clientes FETCH FIRST 2 ROWS;",
+            "SELECT * FROM clientes LIMIT 2 FETCH FIRST 2 ROWS ONLY;",
+        ];
+        for query in invalid_queries {
+            assert!(extractor.extract_limit_and_offset(query).is_err());
+        }
+    }
+
     #[test]
     fn orderby_query_without_desc_or_asc() {
         let extractor = Extractor::new();
@@ -464,7 +1921,7 @@ mod tests {
 
         for q in vec_query {
             let orderby = extractor.extract_orderby_as_str(q);
-            assert_eq!(orderby.is_some(), true);
+            assert!(orderby.is_some());
             assert_eq!(orderby.unwrap(), "id");
         }
     }
@@ -645,7 +2102,7 @@ mod tests {
         let consult = "INSERT INTO users (name, age) VALUES ('John', 20, 30);";
 
         let result = extractor.extract_columns_and_values_for_insert(consult);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -655,7 +2112,7 @@ mod tests {
         let consult = "INSERT INTO users (name, age) VALUES ('John', 20;";
 
         let result = extractor.extract_columns_and_values_for_insert(consult);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -665,7 +2122,7 @@ mod tests {
         let consult = "INSERT INTO users (name, age) VALUES ('John');";
 
         let result = extractor.extract_columns_and_values_for_insert(consult);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -679,7 +2136,25 @@ mod tests {
             .unwrap();
 
         assert_eq!(columns, vec!["name".to_string(), "age".to_string()]);
-        assert_eq!(values, vec!["John".to_string(), "20".to_string()]);
+        assert_eq!(values, vec!["'John'".to_string(), "20".to_string()]);
+    }
+
+    #[test]
+    fn extract_columns_and_values_for_update_keeps_expressions() {
+        let extractor = Extractor::new();
+
+        let consult =
+            "UPDATE users SET name = 'a=b, c', age = COALESCE(age, 0) + 1, 'Correo electronico' = mail WHERE name = 'WHERE';";
+
+        let (columns, values) = extractor
+            .extract_columns_and_values_for_update(consult)
+            .unwrap();
+
+        assert_eq!(columns, vec!["name", "age", "Correo electronico"]);
+        assert_eq!(values, vec!["'a=b, c'", "COALESCE(age, 0) + 1", "mail"]);
+
+        let result = extractor.extract_columns_and_values_for_update("UPDATE users SET name =;");
+        assert!(result.is_err());
     }
 
     #[test]
@@ -689,7 +2164,7 @@ mod tests {
         let consult = "INSERT INTO users (name, age) VALUES ('John, 20);";
 
         let result = extractor.extract_columns_and_values_for_insert(consult);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -699,6 +2174,6 @@ mod tests {
         let consult = "UPDATE users SET name = 'John, age = 20 WHERE id = 3;";
 
         let result = extractor.extract_columns_and_values_for_update(consult);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 }
@@ -1,7 +1,40 @@
 /// Represents the SQL command type
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SQLCommand {
     Select,
     Insert,
     Update,
     Delete,
+    Merge,
+    Truncate,
+    CreateView,
+    DropView,
+    DropMaterializedView,
+}
+
+impl SQLCommand {
+    /// The keyword of the command on a query
+    pub fn name(&self) -> &str {
+        match self {
+            SQLCommand::Select => "SELECT",
+            SQLCommand::Insert => "INSERT",
+            SQLCommand::Update => "UPDATE",
+            SQLCommand::Delete => "DELETE",
+            SQLCommand::Merge => "MERGE",
+            SQLCommand::Truncate => "TRUNCATE",
+            SQLCommand::CreateView => "CREATE VIEW",
+            SQLCommand::DropView => "DROP VIEW",
+            SQLCommand::DropMaterializedView => "DROP MATERIALIZED VIEW",
+        }
+    }
+
+    /// Returns true if the command tag of the query has the amount of rows it changed
+    ///
+    /// A view has no rows of its own, so ```DROP VIEW v;``` is only ```DROP VIEW```
+    pub fn counts_rows(&self) -> bool {
+        !matches!(
+            self,
+            SQLCommand::CreateView | SQLCommand::DropView | SQLCommand::DropMaterializedView
+        )
+    }
 }
@@ -0,0 +1,79 @@
+use crate::errors::tperrors::Tperrors;
+
+/// A table defined by a query on the WITH of another query
+///
+/// Example: ```chain(id, nivel) AS (SELECT ...)```
+///
+/// # Fields
+///
+/// * `name` - The name used to read it on the FROM of the queries that follow.
+///
+/// * `columns` - The names given to its columns (empty to use the ones of the query).
+///
+/// * `query` - The SELECT that computes its rows (with its ; at the end).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommonTable {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub query: String,
+}
+
+impl CommonTable {
+    /// The names of the columns of the table, given the ones returned by its query
+    ///
+    /// Returns an error if the table has a different number of names
+    pub fn column_names(&self, header: Vec<String>) -> Result<Vec<String>, Tperrors> {
+        if self.columns.is_empty() {
+            return Ok(header);
+        }
+        if self.columns.len() != header.len() {
+            return Err(Tperrors::Syntax(format!(
+                "Table {} has {} columns but its query returns {}",
+                self.name,
+                self.columns.len(),
+                header.len()
+            )));
+        }
+        Ok(self.columns.clone())
+    }
+}
+
+/// A query with tables defined before it
+///
+/// Example: ```WITH grandes AS (SELECT * FROM ordenes WHERE cantidad > 1) SELECT producto FROM grandes;```
+///
+/// # Fields
+///
+/// * `recursive` - True for ```WITH RECURSIVE```, a table can read its own rows.
+///
+/// * `tables` - The tables defined, each one can read the ones before it.
+///
+/// * `body` - The query that reads them (with its ; at the end).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithClause {
+    pub recursive: bool,
+    pub tables: Vec<CommonTable>,
+    pub body: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_names_of_a_common_table() {
+        let header = vec!["Id".to_string(), "Nombre".to_string()];
+        let mut table = CommonTable {
+            name: "t".to_string(),
+            columns: Vec::new(),
+            query: "SELECT Id, Nombre FROM clientes;".to_string(),
+        };
+        assert_eq!(table.column_names(header.clone()).unwrap(), header);
+
+        table.columns = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(table.column_names(header.clone()).unwrap(), vec!["a", "b"]);
+
+        table.columns = vec!["a".to_string()];
+        assert!(table.column_names(header).is_err());
+    }
+}
@@ -1,12 +1,80 @@
-use std::{collections::HashMap, fs, rc::Rc};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    fs::File,
+    path::Path,
+    rc::Rc,
+    time::SystemTime,
+};
 
-use crate::errors::tperrors::Tperrors;
+use crate::{conditions::value::Value, errors::tperrors::Tperrors};
 
-/// FolderTables is a struct that contains a HashMap
+use super::table::Table;
+
+/// Extension of the files that hold a table
+const TABLE_EXTENSION: &str = ".csv";
+
+/// Extension of the files that hold the query of a view
+const VIEW_EXTENSION: &str = ".view.sql";
+
+/// Extension of the files that hold the query of a materialized view
+/// (its rows are on a table with the same name)
+const MATERIALIZED_VIEW_EXTENSION: &str = ".matview.sql";
+
+/// What a file of the folder holds, with the name it is used by on the queries
+#[derive(Debug, PartialEq)]
+pub enum FolderFile {
+    /// ```clientes.csv``` is the table clientes
+    Table(String),
+    /// ```ventas.view.sql``` is the view ventas
+    View(String),
+    /// ```totales.matview.sql``` is the query of the materialized view totales,
+    /// its rows are on ```totales.csv```
+    MaterializedView(String),
+}
+
+impl FolderFile {
+    /// Given the name of a file, returns what it holds
+    ///
+    /// Any name is kept as it is (```ordenes-2024.csv``` is the table ordenes-2024),
+    /// only the names of the new tables and views are checked
+    ///
+    /// Returns None for any other file (they are ignored)
+    pub fn from_file_name(file_name: &str) -> Option<FolderFile> {
+        let (name, folder_file): (&str, fn(String) -> FolderFile) =
+            if let Some(name) = file_name.strip_suffix(MATERIALIZED_VIEW_EXTENSION) {
+                (name, FolderFile::MaterializedView)
+            } else if let Some(name) = file_name.strip_suffix(VIEW_EXTENSION) {
+                (name, FolderFile::View)
+            } else if let Some(name) = file_name.strip_suffix(TABLE_EXTENSION) {
+                (name, FolderFile::Table)
+            } else {
+                return None;
+            };
+        if name.is_empty() {
+            return None;
+        }
+        Some(folder_file(name.to_string()))
+    }
+
+    /// The name of a new table or view can only have letters, numbers and _
+    pub fn valid_name(name: &str) -> bool {
+        !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+    }
+}
+
+/// FolderTables is a struct that contains the tables and views of a folder
+///
+/// With the table (or view) name as String, and the path to its file as String
 ///
-/// With the table name as String, and the path to the table as String
+/// A materialized view is also a table (its rows are read from its CSV), the path
+/// kept for it is the one of its query
+#[derive(Clone)]
 pub struct FolderTables {
+    path: String,
     data: HashMap<String, String>,
+    views: HashMap<String, String>,
+    materialized_views: HashMap<String, String>,
 }
 
 impl FolderTables {
@@ -19,9 +87,10 @@ impl FolderTables {
         };
 
         let mut temp_hash: HashMap<String, String> = HashMap::new();
+        let mut views: HashMap<String, String> = HashMap::new();
+        let mut materialized_views: HashMap<String, String> = HashMap::new();
 
         for file in folder {
-            // we get the file name only, without extension
             let file = match file {
                 Ok(entry) => entry,
                 Err(_) => continue,
@@ -29,15 +98,13 @@ impl FolderTables {
 
             let rc = Rc::new(file);
 
+            // tables and views are told apart by the extension, other files are skipped
             let file_name_ref = Rc::clone(&rc);
-            let file_name = match file_name_ref.file_name().into_string() {
-                Ok(name) => {
-                    let find_dot = match name.rfind(".") {
-                        Some(dot) => dot,
-                        None => continue,
-                    };
-                    name[..find_dot].to_string()
-                }
+            let folder_file = match file_name_ref.file_name().into_string() {
+                Ok(name) => match FolderFile::from_file_name(&name) {
+                    Some(folder_file) => folder_file,
+                    None => continue,
+                },
                 Err(_) => continue,
             };
 
@@ -46,9 +113,18 @@ impl FolderTables {
                 Some(path) => String::from(path),
                 None => return Err(Tperrors::Table("Invalid path".to_string())),
             };
-            temp_hash.insert(file_name, path);
+            match folder_file {
+                FolderFile::Table(name) => temp_hash.insert(name, path),
+                FolderFile::View(name) => views.insert(name, path),
+                FolderFile::MaterializedView(name) => materialized_views.insert(name, path),
+            };
         }
-        Ok(FolderTables { data: temp_hash })
+        Ok(FolderTables {
+            path: path_folder.to_string(),
+            data: temp_hash,
+            views,
+            materialized_views,
+        })
     }
 
     /// Given a key (Table name), returns the path to the table
@@ -57,6 +133,369 @@ impl FolderTables {
     pub fn get_path(&self, key: &str) -> Option<String> {
         self.data.get(key).map(|path| path.to_string())
     }
+
+    /// Returns true if there is a view with the given name
+    pub fn is_view(&self, name: &str) -> bool {
+        self.views.contains_key(name)
+    }
+
+    /// Returns true if there is a materialized view with the given name
+    pub fn is_materialized_view(&self, name: &str) -> bool {
+        self.materialized_views.contains_key(name)
+    }
+
+    /// Given a table name, opens the table
+    ///
+    /// Returns a Table error if it isn't on the folder or it can't be opened
+    pub fn open_table(&self, key: &str) -> Result<Table<File>, Tperrors> {
+        match self.get_path(key) {
+            Some(table_path) => match Table::<File>::new(table_path) {
+                Ok(table) => Ok(table),
+                Err(e) => Err(Tperrors::Table(e.to_string())),
+            },
+            None if self.is_view(key) => Err(Tperrors::Table(format!(
+                "{} is a view, it can only be read by a SELECT",
+                key
+            ))),
+            None => Err(Tperrors::Table(format!(
+                "Table {} not found in the folder",
+                key
+            ))),
+        }
+    }
+
+    /// Given a view name, returns its query
+    pub fn view_query(&self, name: &str) -> Result<String, Tperrors> {
+        match self.views.get(name) {
+            Some(path) => Self::read_query(name, path),
+            None => Err(Tperrors::Table(format!(
+                "View {} not found in the folder",
+                name
+            ))),
+        }
+    }
+
+    /// Given a materialized view name, returns its query
+    pub fn materialized_view_query(&self, name: &str) -> Result<String, Tperrors> {
+        match self.materialized_views.get(name) {
+            Some(path) => Self::read_query(name, path),
+            None => Err(Tperrors::Table(format!(
+                "Materialized view {} not found in the folder",
+                name
+            ))),
+        }
+    }
+
+    /// Writes a view on the folder, as ```name.view.sql``` with its query
+    ///
+    /// The name can't be used by another table or view
+    pub fn create_view(&mut self, name: &str, query: &str) -> Result<(), Tperrors> {
+        self.check_free_name(name)?;
+        let path = self.file_path(name, VIEW_EXTENSION)?;
+        Self::write_file(name, &path, &format!("{}\n", query.trim()))?;
+        self.views.insert(name.to_string(), path);
+        Ok(())
+    }
+
+    /// Writes an empty table on the folder, as ```name.csv``` with only its header
+    ///
+    /// The name can't be used by another table or view, and the header can't have
+    /// repeated (or empty) columns
+    pub fn create_table(&mut self, name: &str, header: &[String]) -> Result<String, Tperrors> {
+        self.check_free_name(name)?;
+        for (i, column) in header.iter().enumerate() {
+            if column.is_empty() || header[..i].contains(column) {
+                return Err(Tperrors::Column(format!(
+                    "Invalid column {:?} for table {}, every column needs its own name (use an alias)",
+                    column, name
+                )));
+            }
+        }
+        let csv = Self::to_csv(name, header, &[])?;
+
+        let table_path = self.file_path(name, TABLE_EXTENSION)?;
+        // the rows are appended as "\n" + line, so the header has no new line at the end
+        Self::write_file(name, &table_path, csv.trim_end_matches('\n'))?;
+        self.data.insert(name.to_string(), table_path.clone());
+        Ok(table_path)
+    }
+
+    /// Writes a materialized view on the folder, its rows as a table (```name.csv```)
+    /// and its query as ```name.matview.sql```
+    ///
+    /// The name can't be used by another table or view
+    pub fn create_materialized_view(
+        &mut self,
+        name: &str,
+        query: &str,
+        header: &[String],
+        rows: &[Vec<Value>],
+    ) -> Result<(), Tperrors> {
+        self.check_free_name(name)?;
+        let csv = Self::to_csv(name, header, rows)?;
+
+        let table_path = self.file_path(name, TABLE_EXTENSION)?;
+        let query_path = self.file_path(name, MATERIALIZED_VIEW_EXTENSION)?;
+        Self::write_file(name, &table_path, &csv)?;
+        Self::write_file(name, &query_path, &format!("{}\n", query.trim()))?;
+
+        self.data.insert(name.to_string(), table_path);
+        self.materialized_views.insert(name.to_string(), query_path);
+        Ok(())
+    }
+
+    /// Replaces the rows of a materialized view
+    ///
+    /// They are written on a temporal file first, so the view is never left half written
+    pub fn refresh_materialized_view(
+        &mut self,
+        name: &str,
+        header: &[String],
+        rows: &[Vec<Value>],
+    ) -> Result<(), Tperrors> {
+        if !self.is_materialized_view(name) {
+            return Err(Tperrors::Table(format!(
+                "Materialized view {} not found in the folder",
+                name
+            )));
+        }
+        let csv = Self::to_csv(name, header, rows)?;
+
+        let table_path = self.file_path(name, TABLE_EXTENSION)?;
+        let temporal_path = format!("{}.refresh", table_path);
+        Self::write_file(name, &temporal_path, &csv)?;
+        if let Err(e) = fs::rename(&temporal_path, &table_path) {
+            let _ = fs::remove_file(&temporal_path);
+            return Err(Tperrors::Table(format!(
+                "Materialized view {} can't be written: {}",
+                name, e
+            )));
+        }
+        self.data.insert(name.to_string(), table_path);
+        Ok(())
+    }
+
+    /// Returns the tables (and materialized views) read by a materialized view that were
+    /// changed after it was last written
+    ///
+    /// An empty result means the rows of the view are up to date
+    pub fn stale_sources(&self, name: &str) -> Result<Vec<String>, Tperrors> {
+        let query = self.materialized_view_query(name)?;
+        let written = match self.get_path(name) {
+            Some(path) => Self::modified(&path)?,
+            None => {
+                return Err(Tperrors::Table(format!(
+                    "Rows of materialized view {} not found in the folder",
+                    name
+                )));
+            }
+        };
+
+        let mut stale: Vec<String> = Vec::new();
+        for source in self.sources_of(&query) {
+            if source == name {
+                continue;
+            }
+            let path = match self.get_path(&source) {
+                Some(path) => path,
+                None => continue,
+            };
+            if Self::modified(&path)? > written {
+                stale.push(source);
+            }
+        }
+        Ok(stale)
+    }
+
+    /// The tables of the folder a query reads, the views it reads are replaced
+    /// by the tables they read (a materialized view is a table)
+    ///
+    /// Every word of the query that is the name of a table is considered, so a column
+    /// with the name of a table counts as a read of that table
+    pub fn sources_of(&self, query: &str) -> Vec<String> {
+        let mut sources: Vec<String> = Vec::new();
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut pending = vec![query.to_string()];
+
+        while let Some(query) = pending.pop() {
+            for word in Self::words_of(&query) {
+                if !visited.insert(word.to_string()) {
+                    continue;
+                }
+                if self.data.contains_key(word) {
+                    sources.push(word.to_string());
+                } else if let Ok(view_query) = self.view_query(word) {
+                    pending.push(view_query);
+                }
+            }
+        }
+        sources
+    }
+
+    /// Removes the file of a view from the folder
+    pub fn drop_view(&mut self, name: &str) -> Result<(), Tperrors> {
+        let path = match self.views.get(name) {
+            Some(path) => path,
+            None if self.is_materialized_view(name) => {
+                return Err(Tperrors::Table(format!(
+                    "{} is a materialized view, use DROP MATERIALIZED VIEW",
+                    name
+                )));
+            }
+            None if self.data.contains_key(name) => {
+                return Err(Tperrors::Table(format!("{} is a table, not a view", name)));
+            }
+            None => {
+                return Err(Tperrors::Table(format!(
+                    "View {} not found in the folder",
+                    name
+                )));
+            }
+        };
+        if let Err(e) = fs::remove_file(path) {
+            return Err(Tperrors::Table(format!(
+                "View {} can't be removed: {}",
+                name, e
+            )));
+        }
+        self.views.remove(name);
+        Ok(())
+    }
+
+    /// Removes the rows and the query of a materialized view from the folder
+    pub fn drop_materialized_view(&mut self, name: &str) -> Result<(), Tperrors> {
+        let query_path = match self.materialized_views.get(name) {
+            Some(path) => path.to_string(),
+            None => {
+                return Err(Tperrors::Table(format!(
+                    "Materialized view {} not found in the folder",
+                    name
+                )));
+            }
+        };
+        // without its query the CSV would be a table, so it goes first
+        if let Some(table_path) = self.get_path(name) {
+            if let Err(e) = fs::remove_file(table_path) {
+                return Err(Tperrors::Table(format!(
+                    "Materialized view {} can't be removed: {}",
+                    name, e
+                )));
+            }
+        }
+        if let Err(e) = fs::remove_file(query_path) {
+            return Err(Tperrors::Table(format!(
+                "Materialized view {} can't be removed: {}",
+                name, e
+            )));
+        }
+        self.data.remove(name);
+        self.materialized_views.remove(name);
+        Ok(())
+    }
+
+    /// Returns an error if the name is invalid or already used by a table or view
+    fn check_free_name(&self, name: &str) -> Result<(), Tperrors> {
+        if !FolderFile::valid_name(name) {
+            return Err(Tperrors::Syntax(format!(
+                "Invalid name {}, use only letters, numbers and _",
+                name
+            )));
+        }
+        if self.data.contains_key(name) || self.is_view(name) || self.is_materialized_view(name) {
+            return Err(Tperrors::Table(format!(
+                "There is already a table or view named {}",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Path of the file of the folder with the given name and extension
+    fn file_path(&self, name: &str, extension: &str) -> Result<String, Tperrors> {
+        let path = Path::new(&self.path).join(format!("{}{}", name, extension));
+        match path.to_str() {
+            Some(path) => Ok(path.to_string()),
+            None => Err(Tperrors::Table("Invalid path".to_string())),
+        }
+    }
+
+    fn read_query(name: &str, path: &str) -> Result<String, Tperrors> {
+        match fs::read_to_string(path) {
+            Ok(query) => Ok(query.trim().to_string()),
+            Err(e) => Err(Tperrors::Table(format!(
+                "View {} can't be read: {}",
+                name, e
+            ))),
+        }
+    }
+
+    fn write_file(name: &str, path: &str, content: &str) -> Result<(), Tperrors> {
+        match fs::write(path, content) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Tperrors::Table(format!("{} can't be written: {}", name, e))),
+        }
+    }
+
+    /// When the file was last changed
+    fn modified(path: &str) -> Result<SystemTime, Tperrors> {
+        match fs::metadata(path).and_then(|metadata| metadata.modified()) {
+            Ok(time) => Ok(time),
+            Err(e) => Err(Tperrors::Table(format!(
+                "Modification time of {} can't be read: {}",
+                path, e
+            ))),
+        }
+    }
+
+    /// The rows of a query as the lines of a CSV, the header first
+    ///
+    /// Values are not quoted, so a value (or a column name) with a comma can't be written
+    fn to_csv(name: &str, header: &[String], rows: &[Vec<Value>]) -> Result<String, Tperrors> {
+        let mut csv = String::new();
+        let lines = std::iter::once(header.to_vec()).chain(
+            rows.iter()
+                .map(|row| row.iter().map(|value| value.to_string()).collect()),
+        );
+        for line in lines {
+            if let Some(field) = line.iter().find(|f| f.contains(',') || f.contains('\n')) {
+                return Err(Tperrors::Generic(format!(
+                    "{} can't hold {} on a CSV (it has a comma or a new line), use an alias or REPLACE",
+                    name, field
+                )));
+            }
+            csv.push_str(&line.join(","));
+            csv.push('\n');
+        }
+        Ok(csv)
+    }
+
+    /// The words of a query that could be a name, the text between quotes is skipped
+    fn words_of(query: &str) -> Vec<&str> {
+        let mut words: Vec<&str> = Vec::new();
+        let mut quote: Option<char> = None;
+        let mut start: Option<usize> = None;
+
+        for (i, c) in query.char_indices() {
+            let is_word = quote.is_none() && (c.is_alphanumeric() || c == '_');
+            match (start, is_word) {
+                (None, true) => start = Some(i),
+                (Some(s), false) => {
+                    words.push(&query[s..i]);
+                    start = None;
+                }
+                _ => {}
+            }
+            match (quote, c) {
+                (Some(q), _) if q == c => quote = None,
+                (None, '\'') => quote = Some(c),
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            words.push(&query[s..]);
+        }
+        words
+    }
 }
 
 #[cfg(test)]
@@ -80,4 +519,40 @@ mod tests {
         let folder = FolderTables::new("./invalid_folder");
         assert!(folder.is_err());
     }
+
+    #[test]
+    fn test_folder_sources_of_a_query() {
+        let folder = FolderTables::new("./tables").unwrap();
+
+        let sources = folder.sources_of(
+            "SELECT c.Nombre FROM clientes c WHERE c.Id IN (SELECT id_cliente FROM ordenes) AND c.Nombre <> 'ventas';",
+        );
+        assert_eq!(sources, vec!["clientes", "ordenes"]);
+
+        let sources = folder.sources_of("SELECT * FROM clientes WHERE Nombre = 'ordenes';");
+        assert_eq!(sources, vec!["clientes"]);
+    }
+
+    #[test]
+    fn test_folder_files_are_told_apart() {
+        assert_eq!(
+            FolderFile::from_file_name("clientes.csv"),
+            Some(FolderFile::Table("clientes".to_string()))
+        );
+        assert_eq!(
+            FolderFile::from_file_name("ventas.view.sql"),
+            Some(FolderFile::View("ventas".to_string()))
+        );
+        assert_eq!(
+            FolderFile::from_file_name("totales.matview.sql"),
+            Some(FolderFile::MaterializedView("totales".to_string()))
+        );
+        assert_eq!(FolderFile::from_file_name("notas.txt"), None);
+        assert_eq!(FolderFile::from_file_name("clientes.csv.tmp"), None);
+        assert_eq!(FolderFile::from_file_name(".csv"), None);
+        assert_eq!(
+            FolderFile::from_file_name("ordenes-2024.csv"),
+            Some(FolderFile::Table("ordenes-2024".to_string()))
+        );
+    }
 }
@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+
+use crate::{
+    conditions::{condition::Condition, value::Value},
+    errors::tperrors::Tperrors,
+    expressions::{
+        expression::{column_position, BinaryOperator, Expression, Row},
+        subquery::SubqueryRunner,
+    },
+};
+
+use super::{
+    folder_tables::FolderTables,
+    row_source::{QualifiedSource, ResultSource, RowCallback, RowSource},
+};
+
+/// A table used on the FROM clause
+///
+/// # Fields
+///
+/// * `name` - The name of the table on the folder.
+///
+/// * `alias` - The name given on the query, Example: ```o``` on ```FROM ordenes o```
+///
+/// * `subquery` - The query of a derived table, Example: ```FROM (SELECT ...) AS t```,
+///   its name is its alias.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableReference {
+    pub name: String,
+    pub alias: Option<String>,
+    pub subquery: Option<String>,
+}
+
+impl TableReference {
+    /// The name used to qualify the columns of the table (the alias if there is one)
+    pub fn qualifier(&self) -> &str {
+        match &self.alias {
+            Some(alias) => alias,
+            None => &self.name,
+        }
+    }
+
+    /// Opens the table of the folder, or runs the query of a derived table (or a view)
+    ///
+    /// The tables defined on a WITH are used before the ones of the folder
+    pub fn open(
+        &self,
+        folder_tables: &FolderTables,
+        runner: Option<&dyn SubqueryRunner>,
+    ) -> Result<Box<dyn RowSource>, Tperrors> {
+        if self.subquery.is_none() {
+            if let Some((columns, rows)) = runner.and_then(|r| r.common_table(&self.name)) {
+                return Ok(Box::new(ResultSource::new(columns, rows)));
+            }
+        }
+        match (&self.subquery, runner) {
+            (None, Some(runner)) if folder_tables.is_view(&self.name) => {
+                let query = folder_tables.view_query(&self.name)?;
+                let (columns, rows) = runner.run_view(&self.name, &query)?;
+                Ok(Box::new(ResultSource::new(columns, rows)))
+            }
+            (None, _) => Ok(Box::new(folder_tables.open_table(&self.name)?)),
+            (Some(query), Some(runner)) => {
+                let (columns, rows) = runner.run(query, None)?;
+                Ok(Box::new(ResultSource::new(columns, rows)))
+            }
+            (Some(query), None) => Err(Tperrors::Generic(format!(
+                "Subquery ({}) can't be used here",
+                query.trim_end_matches(';')
+            ))),
+        }
+    }
+}
+
+/// How the rows of two tables are combined
+///
+/// On the outer joins, the rows of a table without any match are kept,
+/// with NULL on the columns of the other table
+#[derive(Debug, Clone, PartialEq)]
+pub enum JoinKind {
+    /// Only the rows of both tables that meet the condition
+    Inner,
+    /// Every row of the left table
+    Left,
+    /// Every row of the right table
+    Right,
+    /// Every row of both tables
+    Full,
+    /// Every row of the left table with every row of the right table (without condition)
+    Cross,
+}
+
+/// A table joined to the ones before it
+///
+/// Example: ```JOIN clientes c ON o.id_cliente = c.Id```
+///
+/// The condition is None only for a CROSS JOIN
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinClause {
+    pub kind: JoinKind,
+    pub table: TableReference,
+    pub condition: Option<String>,
+}
+
+/// The FROM clause of a SELECT, the first table and the ones joined to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromClause {
+    pub table: TableReference,
+    pub joins: Vec<JoinClause>,
+}
+
+impl FromClause {
+    /// Opens the tables of the clause, looking for them on the folder
+    ///
+    /// A single table without alias is read as is, otherwise the columns of every table
+    /// are qualified with its alias (or its name), Example: ```o.id_cliente```
+    ///
+    /// The queries of the derived tables are run with the runner (once, when the clause is opened)
+    pub fn open(
+        &self,
+        folder_tables: &FolderTables,
+        runner: Option<&dyn SubqueryRunner>,
+    ) -> Result<Box<dyn RowSource>, Tperrors> {
+        let table = self.table.open(folder_tables, runner)?;
+        if self.joins.is_empty() && self.table.alias.is_none() {
+            return Ok(table);
+        }
+
+        let mut source: Box<dyn RowSource> =
+            Box::new(QualifiedSource::new(table, self.table.qualifier()));
+        for join in &self.joins {
+            let table = join.table.open(folder_tables, runner)?;
+            let joined = Box::new(QualifiedSource::new(table, join.table.qualifier()));
+            source = Box::new(JoinSource::new(
+                source,
+                joined,
+                join.kind.clone(),
+                join.condition.as_deref(),
+            ));
+        }
+        Ok(source)
+    }
+}
+
+/// The rows of two sources combined by a join
+///
+/// The columns are the ones of the left source followed by the ones of the right source.
+///
+/// The rows of the smaller source are kept on memory, and then the other source is read once
+/// looking for the matches of every row:
+///
+/// * If the condition compares columns of both sources with ```=``` (other comparisons can
+///   be added with AND), the rows kept are on a hash map by the value of those columns,
+///   so only the ones with the same values are checked (hash join).
+///
+/// * Otherwise every row kept is checked against the condition (nested loop join).
+pub struct JoinSource {
+    left: Box<dyn RowSource>,
+    right: Box<dyn RowSource>,
+    kind: JoinKind,
+    condition: Option<String>,
+}
+
+/// The ON condition of a join, splitted in the columns compared with ```=``` and the rest
+///
+/// (also used to find the rows of the source of a MERGE)
+pub struct JoinCondition {
+    /// positions of the compared columns on the left and the right source,
+    /// empty for a nested loop join
+    pub keys: Vec<(usize, usize)>,
+    /// the rest of the condition, checked against the joined row
+    pub residual: Option<Expression>,
+}
+
+impl JoinCondition {
+    /// Parses the ON condition and splits it in the columns compared with ```=```
+    /// and the rest of it
+    ///
+    /// `columns` are the ones of the joined row, the left columns followed by the right ones
+    pub fn compile(
+        condition: Option<&str>,
+        left_columns: &[String],
+        right_columns: &[String],
+        columns: &[String],
+    ) -> Result<JoinCondition, Tperrors> {
+        let condition = match condition {
+            Some(condition) => Condition::compile_strict(condition, columns)?,
+            None => {
+                return Ok(JoinCondition {
+                    keys: Vec::new(),
+                    residual: None,
+                });
+            }
+        };
+
+        let mut keys: Vec<(usize, usize)> = Vec::new();
+        let mut residual: Option<Expression> = None;
+
+        for term in Self::conjuncts(condition) {
+            match Self::key_of(&term, left_columns, right_columns) {
+                Some(key) => keys.push(key),
+                None => {
+                    residual = match residual {
+                        Some(residual) => Some(Expression::Binary {
+                            left: Box::new(residual),
+                            operator: BinaryOperator::And,
+                            right: Box::new(term),
+                        }),
+                        None => Some(term),
+                    }
+                }
+            }
+        }
+        Ok(JoinCondition { keys, residual })
+    }
+
+    /// Splits a condition by its AND, Example: ```a = b AND c > 1``` -> ```[a = b, c > 1]```
+    fn conjuncts(expression: Expression) -> Vec<Expression> {
+        match expression {
+            Expression::Binary {
+                left,
+                operator: BinaryOperator::And,
+                right,
+            } => {
+                let mut terms = Self::conjuncts(*left);
+                terms.extend(Self::conjuncts(*right));
+                terms
+            }
+            other => vec![other],
+        }
+    }
+
+    /// If the term is ```column = column``` with a column of each source, returns their positions
+    fn key_of(
+        term: &Expression,
+        left_columns: &[String],
+        right_columns: &[String],
+    ) -> Option<(usize, usize)> {
+        let (first, second) = match term {
+            Expression::Binary {
+                left,
+                operator: BinaryOperator::Equal,
+                right,
+            } => match (left.as_ref(), right.as_ref()) {
+                (Expression::Column(first), Expression::Column(second)) => (first, second),
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        match (
+            column_position(left_columns, first),
+            column_position(right_columns, second),
+        ) {
+            (Some(left), Some(right)) => Some((left, right)),
+            _ => match (
+                column_position(left_columns, second),
+                column_position(right_columns, first),
+            ) {
+                (Some(left), Some(right)) => Some((left, right)),
+                _ => None,
+            },
+        }
+    }
+
+    /// The key of a row to look for it on the hash map, None if any of its values is NULL
+    /// (NULL is never equal to anything)
+    ///
+    /// Rows with the same key are only candidates, they must be checked with same_keys
+    pub fn hash_key(values: &[Value], positions: &[usize]) -> Option<Vec<String>> {
+        positions
+            .iter()
+            .map(|position| values.get(*position).and_then(|value| value.match_key()))
+            .collect()
+    }
+
+    /// Returns true if the compared columns of both rows are equal
+    /// (```'007'``` and ```'7'``` have the same key but are different)
+    pub fn same_keys(
+        left: &[Value],
+        left_positions: &[usize],
+        right: &[Value],
+        right_positions: &[usize],
+    ) -> bool {
+        left_positions.iter().zip(right_positions).all(|(l, r)| {
+            match (left.get(*l), right.get(*r)) {
+                (Some(left), Some(right)) => left.equals(right),
+                _ => false,
+            }
+        })
+    }
+}
+
+impl JoinSource {
+    pub fn new(
+        left: Box<dyn RowSource>,
+        right: Box<dyn RowSource>,
+        kind: JoinKind,
+        condition: Option<&str>,
+    ) -> JoinSource {
+        JoinSource {
+            left,
+            right,
+            kind,
+            condition: condition.map(|c| c.to_string()),
+        }
+    }
+
+    /// The joined row, the values of the left source followed by the ones of the right source
+    fn combine(build_left: bool, built: &[Value], probed: &[Value]) -> Vec<Value> {
+        match build_left {
+            true => built.iter().chain(probed.iter()),
+            false => probed.iter().chain(built.iter()),
+        }
+        .cloned()
+        .collect()
+    }
+}
+
+impl RowSource for JoinSource {
+    fn columns(&mut self) -> Result<Vec<String>, Tperrors> {
+        let mut columns = self.left.columns()?;
+        columns.extend(self.right.columns()?);
+        Ok(columns)
+    }
+
+    fn scan(&mut self, on_row: &mut RowCallback) -> Result<(), Tperrors> {
+        let left_columns = self.left.columns()?;
+        let right_columns = self.right.columns()?;
+        let columns = left_columns
+            .iter()
+            .chain(right_columns.iter())
+            .cloned()
+            .collect::<Vec<String>>();
+        let condition = JoinCondition::compile(
+            self.condition.as_deref(),
+            &left_columns,
+            &right_columns,
+            &columns,
+        )?;
+
+        // the smaller source is kept on memory, the other one is only read
+        let build_left = self.left.estimated_size()? <= self.right.estimated_size()?;
+        let keep_left = matches!(self.kind, JoinKind::Left | JoinKind::Full);
+        let keep_right = matches!(self.kind, JoinKind::Right | JoinKind::Full);
+        let (build, probe) = match build_left {
+            true => (&mut self.left, &mut self.right),
+            false => (&mut self.right, &mut self.left),
+        };
+        let (keep_built, keep_probed) = match build_left {
+            true => (keep_left, keep_right),
+            false => (keep_right, keep_left),
+        };
+        let (built_width, probed_width) = match build_left {
+            true => (left_columns.len(), right_columns.len()),
+            false => (right_columns.len(), left_columns.len()),
+        };
+        let (build_positions, probe_positions) = condition
+            .keys
+            .iter()
+            .map(|(left, right)| match build_left {
+                true => (*left, *right),
+                false => (*right, *left),
+            })
+            .unzip::<usize, usize, Vec<usize>, Vec<usize>>();
+        let hash_join = !condition.keys.is_empty();
+
+        // every row is kept (the unmatched ones are needed on outer joins),
+        // the hash map has the positions of the rows with each key
+        let mut built: Vec<Vec<Value>> = Vec::new();
+        let mut positions: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+        build.scan(&mut |_, values| {
+            if hash_join {
+                if let Some(key) = JoinCondition::hash_key(&values, &build_positions) {
+                    positions.entry(key).or_default().push(built.len());
+                }
+            }
+            built.push(values);
+            Ok(true)
+        })?;
+        let every_row = match hash_join {
+            true => Vec::new(),
+            false => (0..built.len()).collect::<Vec<usize>>(),
+        };
+        let mut built_matched = vec![false; built.len()];
+
+        let mut produced = 0;
+        let mut stopped = false;
+        probe.scan(&mut |_, values| {
+            let candidates = match hash_join {
+                true => match JoinCondition::hash_key(&values, &probe_positions) {
+                    Some(key) => positions.get(&key).map_or(&[][..], |c| c.as_slice()),
+                    None => &[],
+                },
+                false => every_row.as_slice(),
+            };
+
+            let mut matched = false;
+            for position in candidates {
+                if hash_join
+                    && !JoinCondition::same_keys(
+                        &built[*position],
+                        &build_positions,
+                        &values,
+                        &probe_positions,
+                    )
+                {
+                    continue;
+                }
+                let joined = Self::combine(build_left, &built[*position], &values);
+                if let Some(residual) = &condition.residual {
+                    if !residual.matches(&Row::new(&columns, &joined))? {
+                        continue;
+                    }
+                }
+                matched = true;
+                built_matched[*position] = true;
+                produced += 1;
+                if !on_row(produced, joined)? {
+                    stopped = true;
+                    return Ok(false);
+                }
+            }
+
+            if !matched && keep_probed {
+                let nulls = vec![Value::Null; built_width];
+                produced += 1;
+                if !on_row(produced, Self::combine(build_left, &nulls, &values))? {
+                    stopped = true;
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        })?;
+
+        if keep_built && !stopped {
+            let nulls = vec![Value::Null; probed_width];
+            for (values, matched) in built.iter().zip(built_matched) {
+                if matched {
+                    continue;
+                }
+                produced += 1;
+                if !on_row(produced, Self::combine(build_left, values, &nulls))? {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn estimated_size(&mut self) -> Result<u64, Tperrors> {
+        Ok(self.left.estimated_size()? + self.right.estimated_size()?)
+    }
+}
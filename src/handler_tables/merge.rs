@@ -0,0 +1,88 @@
+use super::join::TableReference;
+
+/// When a WHEN clause of a MERGE is used
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeCase {
+    /// A row of the target with a row of the source (```WHEN MATCHED```)
+    Matched,
+    /// A row of the source without a row of the target (```WHEN NOT MATCHED [BY TARGET]```)
+    NotMatchedByTarget,
+    /// A row of the target without a row of the source (```WHEN NOT MATCHED BY SOURCE```)
+    NotMatchedBySource,
+}
+
+/// What a WHEN clause of a MERGE does with the row of the target
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeAction {
+    /// ```UPDATE SET column = value, ...```, each value is an expression
+    Update {
+        columns: Vec<String>,
+        values: Vec<String>,
+    },
+    /// ```DELETE```
+    Delete,
+    /// ```INSERT [(columns)] VALUES (values)```, without columns every column is given
+    Insert {
+        columns: Vec<String>,
+        values: Vec<String>,
+    },
+    /// ```DO NOTHING```
+    Nothing,
+}
+
+/// A WHEN clause of a MERGE
+///
+/// # Fields
+///
+/// * `case` - Which rows the clause is for.
+///
+/// * `condition` - The condition after AND (if there is one), the clause is used only
+///   for the rows that meet it.
+///
+/// * `action` - What is done with those rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeClause {
+    pub case: MergeCase,
+    pub condition: Option<String>,
+    pub action: MergeAction,
+}
+
+/// A MERGE query, it changes the target with the rows of the source
+///
+/// Example:
+/// ```MERGE INTO clientes c USING exportacion e ON c.Id = e.Id WHEN MATCHED THEN UPDATE SET Edad = e.Edad WHEN NOT MATCHED THEN INSERT VALUES (e.Id, e.Nombre, ...);```
+///
+/// # Fields
+///
+/// * `target` - The table that is changed.
+///
+/// * `source` - The table (or view or derived table) whose rows are read.
+///
+/// * `on` - The condition that matches a row of the target with a row of the source.
+///
+/// * `clauses` - The WHEN clauses, for every row the first one that applies is used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeQuery {
+    pub target: TableReference,
+    pub source: TableReference,
+    pub on: String,
+    pub clauses: Vec<MergeClause>,
+}
+
+impl MergeQuery {
+    /// The columns of a table qualified with its name (or alias), the ones the
+    /// conditions and values of the query use
+    pub fn qualified(table: &TableReference, columns: &[String]) -> Vec<String> {
+        columns
+            .iter()
+            .map(|column| format!("{}.{}", table.qualifier(), column))
+            .collect()
+    }
+
+    /// Returns true if a row of the target can be changed by a WHEN MATCHED clause
+    pub fn has_matched_clause(&self) -> bool {
+        self.clauses
+            .iter()
+            .any(|clause| clause.case == MergeCase::Matched)
+    }
+}
@@ -1,2 +1,12 @@
+pub mod common_table;
 pub mod folder_tables;
+pub mod join;
+pub mod merge;
+pub mod on_conflict;
+pub mod returning;
+pub mod row_source;
+pub mod select_options;
+pub mod select_output;
+pub mod select_query;
+pub mod set_operation;
 pub mod table;
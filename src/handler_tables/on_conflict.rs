@@ -0,0 +1,94 @@
+use crate::expressions::expression::Expression;
+
+/// What an INSERT does with a row whose key is already on the table
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictAction {
+    /// The row is not inserted
+    Nothing,
+    /// The row on the table is updated, each column with the expression of its value.
+    ///
+    /// The values of the row that was not inserted are read as ```EXCLUDED.column```
+    /// (the prefix in any case)
+    Update {
+        columns: Vec<String>,
+        values: Vec<String>,
+    },
+}
+
+/// The ON CONFLICT clause of an INSERT
+///
+/// Example: ```INSERT INTO clientes VALUES (...) ON CONFLICT (Id) DO UPDATE SET Edad = EXCLUDED.Edad;```
+///
+/// # Fields
+///
+/// * `key` - The columns that identify a row, two rows with the same values on them
+///   are the same row (a key with NULL never matches another one).
+///
+/// * `action` - What is done with the rows whose key is already on the table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnConflict {
+    pub key: Vec<String>,
+    pub action: ConflictAction,
+}
+
+/// Prefix of the columns of the row that was not inserted, on ```DO UPDATE SET```
+pub const EXCLUDED: &str = "EXCLUDED";
+
+impl OnConflict {
+    pub fn new(key: Vec<String>, action: ConflictAction) -> OnConflict {
+        OnConflict { key, action }
+    }
+
+    /// The columns of the table followed by the ones of the row that was not inserted
+    /// (Example: ```["Id", "Nombre", "EXCLUDED.Id", "EXCLUDED.Nombre"]```)
+    pub fn columns_with_excluded(columns: &[String]) -> Vec<String> {
+        columns
+            .iter()
+            .cloned()
+            .chain(columns.iter().map(|c| format!("{}.{}", EXCLUDED, c)))
+            .collect()
+    }
+
+    /// Writes every column of the row that was not inserted with the prefix in
+    /// uppercase, so ```excluded.Edad``` (or ```Excluded.Edad```) is ```EXCLUDED.Edad```
+    pub fn normalize_excluded(expression: &mut Expression) {
+        if let Expression::Column(name) = expression {
+            if let Some((prefix, column)) = name.split_once('.') {
+                if prefix.eq_ignore_ascii_case(EXCLUDED) {
+                    *name = format!("{}.{}", EXCLUDED, column);
+                }
+            }
+        }
+        for child in expression.children_mut() {
+            Self::normalize_excluded(child);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excluded_columns_follow_the_ones_of_the_table() {
+        let columns = vec!["Id".to_string(), "Nombre".to_string()];
+        assert_eq!(
+            OnConflict::columns_with_excluded(&columns),
+            vec!["Id", "Nombre", "EXCLUDED.Id", "EXCLUDED.Nombre"]
+        );
+    }
+
+    #[test]
+    fn excluded_prefix_is_written_in_uppercase() {
+        let mut expression = Expression::Binary {
+            left: Box::new(Expression::Column("excluded.Edad".to_string())),
+            operator: crate::expressions::expression::BinaryOperator::Plus,
+            right: Box::new(Expression::Column("Edad".to_string())),
+        };
+        OnConflict::normalize_excluded(&mut expression);
+        assert_eq!(
+            expression.columns(),
+            vec![&"EXCLUDED.Edad".to_string(), &"Edad".to_string()]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    conditions::value::Value,
+    errors::tperrors::Tperrors,
+    expressions::{expression::Row, projection::Projection},
+};
+
+/// The RETURNING clause of an INSERT, UPDATE or DELETE
+///
+/// Example: ```UPDATE clientes SET Edad = Edad + 1 WHERE Id = 1 RETURNING Id, Edad AS nueva;```
+///
+/// Its elements are like the ones of a SELECT list (```*```, columns, expressions and aliases),
+/// computed for every row changed by the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Returning {
+    pub items: Vec<String>,
+}
+
+impl Returning {
+    pub fn new(items: Vec<String>) -> Returning {
+        Returning { items }
+    }
+
+    /// Given the columns of the table and the rows changed by the query
+    ///
+    /// returns the header and the values of every element for each row.
+    ///
+    /// Aggregate and window functions can't be used, every row is returned on its own
+    pub fn resolve(
+        &self,
+        columns: &[String],
+        rows: &[Vec<Value>],
+    ) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors> {
+        let projections = self.projections(columns)?;
+
+        let mut returned: Vec<Vec<Value>> = Vec::new();
+        for (number, values) in rows.iter().enumerate() {
+            let row = Row::new(columns, values).with_number(number + 1);
+            returned.push(
+                projections
+                    .iter()
+                    .map(|projection| projection.expression.evaluate(&row))
+                    .collect::<Result<Vec<Value>, Tperrors>>()?,
+            );
+        }
+
+        let header = projections.into_iter().map(|p| p.name).collect();
+        Ok((header, returned))
+    }
+
+    /// Checks the clause against the columns of the table, so an invalid clause is found
+    /// before any row is changed
+    pub fn validate(&self, columns: &[String]) -> Result<(), Tperrors> {
+        self.projections(columns).map(|_| ())
+    }
+
+    /// The elements of the clause, ```*``` expanded into every column
+    fn projections(&self, columns: &[String]) -> Result<Vec<Projection>, Tperrors> {
+        let projections = Projection::from_select_list(&self.items, columns)?;
+        for projection in &projections {
+            if projection.expression.contains_aggregate() || projection.expression.contains_window()
+            {
+                return Err(Tperrors::Syntax(format!(
+                    "Aggregate and window functions can't be used on RETURNING ({})",
+                    projection.name
+                )));
+            }
+            projection.expression.validate_columns(columns)?;
+        }
+        Ok(projections)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returning_computes_every_element_for_each_row() {
+        let columns = vec!["Id".to_string(), "Edad".to_string()];
+        let rows = vec![
+            vec![Value::Integer(1), Value::Integer(32)],
+            vec![Value::Integer(2), Value::Null],
+        ];
+
+        let returning = Returning::new(vec!["*".to_string(), "Edad + 1 AS siguiente".to_string()]);
+        let (header, returned) = returning.resolve(&columns, &rows).unwrap();
+        assert_eq!(header, vec!["Id", "Edad", "siguiente"]);
+        assert_eq!(
+            returned,
+            vec![
+                vec![Value::Integer(1), Value::Integer(32), Value::Integer(33)],
+                vec![Value::Integer(2), Value::Null, Value::Null],
+            ]
+        );
+
+        let returning = Returning::new(vec!["COUNT(*)".to_string()]);
+        assert!(returning.resolve(&columns, &rows).is_err());
+        let returning = Returning::new(vec!["Inventada".to_string()]);
+        assert!(returning.resolve(&columns, &rows).is_err());
+    }
+}
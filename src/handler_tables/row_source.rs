@@ -0,0 +1,94 @@
+use crate::{conditions::value::Value, errors::tperrors::Tperrors};
+
+/// Called with every row read: its number (starting at 1) and its values
+///
+/// Returns false if no more rows are needed
+pub type RowCallback<'a> = dyn FnMut(usize, Vec<Value>) -> Result<bool, Tperrors> + 'a;
+
+/// Something a SELECT can read rows from, a table of the folder, a join between tables
+/// or the result of another query
+pub trait RowSource {
+    /// Names of the columns of every row
+    fn columns(&mut self) -> Result<Vec<String>, Tperrors>;
+
+    /// Calls `on_row` with every row, stopping as soon as it returns false so
+    /// the rest of the rows aren't read
+    fn scan(&mut self, on_row: &mut RowCallback) -> Result<(), Tperrors>;
+
+    /// Approximate size of the rows (in bytes), used to choose the side of a join
+    /// that is kept on memory
+    fn estimated_size(&mut self) -> Result<u64, Tperrors>;
+}
+
+/// A source whose columns are qualified with the alias of its table
+///
+/// Example: on ```FROM clientes c``` the column ```Nombre``` is ```c.Nombre```
+pub struct QualifiedSource {
+    source: Box<dyn RowSource>,
+    alias: String,
+}
+
+impl QualifiedSource {
+    pub fn new(source: Box<dyn RowSource>, alias: &str) -> QualifiedSource {
+        QualifiedSource {
+            source,
+            alias: alias.to_string(),
+        }
+    }
+}
+
+impl RowSource for QualifiedSource {
+    fn columns(&mut self) -> Result<Vec<String>, Tperrors> {
+        Ok(self
+            .source
+            .columns()?
+            .iter()
+            .map(|column| format!("{}.{}", self.alias, column))
+            .collect())
+    }
+
+    fn scan(&mut self, on_row: &mut RowCallback) -> Result<(), Tperrors> {
+        self.source.scan(on_row)
+    }
+
+    fn estimated_size(&mut self) -> Result<u64, Tperrors> {
+        self.source.estimated_size()
+    }
+}
+
+/// The rows already computed by a query, Example: ```FROM (SELECT ...) AS t```
+pub struct ResultSource {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl ResultSource {
+    pub fn new(columns: Vec<String>, rows: Vec<Vec<Value>>) -> ResultSource {
+        ResultSource { columns, rows }
+    }
+}
+
+impl RowSource for ResultSource {
+    fn columns(&mut self) -> Result<Vec<String>, Tperrors> {
+        Ok(self.columns.clone())
+    }
+
+    fn scan(&mut self, on_row: &mut RowCallback) -> Result<(), Tperrors> {
+        for (i, values) in self.rows.iter().enumerate() {
+            if !on_row(i + 1, values.clone())? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// The length the rows would have on a CSV file
+    fn estimated_size(&mut self) -> Result<u64, Tperrors> {
+        Ok(self
+            .rows
+            .iter()
+            .flatten()
+            .map(|value| value.to_string().len() as u64 + 1)
+            .sum())
+    }
+}
@@ -0,0 +1,21 @@
+/// Clauses of a SELECT that change how the rows that meet the conditions are returned
+///
+/// # Fields
+///
+/// * `group_by` - Expressions to group the rows by, Example: ```["id_cliente"]```
+///
+/// * `having` - Condition checked against every group, Example: ```SUM(cantidad) > 1```
+///
+/// * `distinct` - True for ```SELECT DISTINCT```, repeated rows are returned once
+///
+/// * `limit` - Max amount of rows to return (```LIMIT n``` or ```FETCH FIRST n ROWS ONLY```)
+///
+/// * `offset` - Amount of rows to skip before returning them
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SelectOptions {
+    pub group_by: Vec<String>,
+    pub having: Option<String>,
+    pub distinct: bool,
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
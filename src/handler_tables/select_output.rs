@@ -0,0 +1,230 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use crate::{conditions::value::Value, sorter::sort::SortMethod};
+
+/// A row produced by a SELECT, with the values of its sorting keys
+///
+/// `position` is the order in which it was produced, rows with the same sorting keys
+/// keep that order
+struct OutputRow {
+    values: Vec<Value>,
+    sorting_keys: Vec<Value>,
+    position: usize,
+}
+
+/// Collects the rows produced by a SELECT
+///
+/// * With DISTINCT, a row that was already produced is discarded as soon as it's pushed,
+///   using a hash set.
+///
+/// * With LIMIT and without ORDER BY, it's full once it has enough rows, so the table
+///   doesn't need to be read until the end.
+///
+/// * With LIMIT and ORDER BY, only the best ```OFFSET + LIMIT``` rows are kept on a heap,
+///   instead of sorting every row of the table.
+pub struct SelectOutput {
+    /// with ORDER BY and LIMIT, it's a max heap (the worst row kept is the first one)
+    rows: Vec<OutputRow>,
+    seen: Option<HashSet<Vec<Option<String>>>>,
+    sorting: Option<Vec<SortMethod>>,
+    limit: Option<usize>,
+    offset: usize,
+    produced: usize,
+}
+
+impl SelectOutput {
+    pub fn new(
+        distinct: bool,
+        sorting: Option<Vec<SortMethod>>,
+        limit: Option<usize>,
+        offset: usize,
+    ) -> SelectOutput {
+        SelectOutput {
+            rows: Vec::new(),
+            seen: if distinct { Some(HashSet::new()) } else { None },
+            sorting,
+            limit,
+            offset,
+            produced: 0,
+        }
+    }
+
+    /// Adds a row to the output with the values of its sorting keys
+    ///
+    /// Returns false if the row is discarded (repeated with DISTINCT, or not needed
+    /// because of the LIMIT)
+    pub fn push(&mut self, values: Vec<Value>, sorting_keys: Vec<Value>) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        if let Some(seen) = &mut self.seen {
+            // NULL has no key, so two NULL are the same for DISTINCT
+            let key = values.iter().map(|v| v.hash_key()).collect::<Vec<_>>();
+            if !seen.insert(key) {
+                return false;
+            }
+        }
+        let row = OutputRow {
+            values,
+            sorting_keys,
+            position: self.produced,
+        };
+        self.produced += 1;
+
+        match (&self.sorting, self.capacity()) {
+            (Some(_), Some(capacity)) => self.push_bounded(row, capacity),
+            _ => {
+                self.rows.push(row);
+                true
+            }
+        }
+    }
+
+    /// Returns true if no more rows are needed (there is a LIMIT, no ORDER BY and
+    /// enough rows were produced)
+    pub fn is_full(&self) -> bool {
+        self.sorting.is_none()
+            && self
+                .capacity()
+                .is_some_and(|capacity| self.rows.len() >= capacity)
+    }
+
+    /// Returns the rows, sorted if a sorting method is given, applying OFFSET and LIMIT
+    ///
+    /// The first sort method is the most important one, the next ones break ties
+    pub fn finish(mut self) -> Vec<Vec<Value>> {
+        if let Some(sorting) = &self.sorting {
+            self.rows.sort_by(|a, b| Self::compare(sorting, a, b));
+        }
+        self.rows
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .map(|row| row.values)
+            .collect()
+    }
+
+    /// Amount of rows that have to be kept, None if there is no LIMIT
+    fn capacity(&self) -> Option<usize> {
+        self.limit.map(|limit| limit.saturating_add(self.offset))
+    }
+
+    /// Keeps the row only if it's one of the best `capacity` rows seen
+    fn push_bounded(&mut self, row: OutputRow, capacity: usize) -> bool {
+        if capacity == 0 {
+            return false;
+        }
+        if self.rows.len() < capacity {
+            self.rows.push(row);
+            self.sift_up(self.rows.len() - 1);
+            return true;
+        }
+        // the first row is the worst one kept, if the new one goes before it, it's replaced
+        if self.compare_rows(&row, &self.rows[0]) != Ordering::Less {
+            return false;
+        }
+        self.rows[0] = row;
+        self.sift_down(0);
+        true
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if self.compare_rows(&self.rows[i], &self.rows[parent]) != Ordering::Greater {
+                break;
+            }
+            self.rows.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let mut largest = i;
+            for child in [2 * i + 1, 2 * i + 2] {
+                if child < self.rows.len()
+                    && self.compare_rows(&self.rows[child], &self.rows[largest])
+                        == Ordering::Greater
+                {
+                    largest = child;
+                }
+            }
+            if largest == i {
+                break;
+            }
+            self.rows.swap(i, largest);
+            i = largest;
+        }
+    }
+
+    fn compare_rows(&self, a: &OutputRow, b: &OutputRow) -> Ordering {
+        match &self.sorting {
+            Some(sorting) => Self::compare(sorting, a, b),
+            None => a.position.cmp(&b.position),
+        }
+    }
+
+    fn compare(sorting: &[SortMethod], a: &OutputRow, b: &OutputRow) -> Ordering {
+        sorting
+            .iter()
+            .enumerate()
+            .map(|(i, sort_method)| sort_method.compare(&a.sorting_keys[i], &b.sorting_keys[i]))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(a.position.cmp(&b.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distinct_output_discards_repeated_rows() {
+        let mut output = SelectOutput::new(true, None, None, 0);
+
+        assert!(output.push(vec![Value::Integer(1), Value::Null], Vec::new()));
+        assert!(!output.push(vec![Value::Integer(1), Value::Null], Vec::new()));
+        assert!(output.push(vec![Value::Integer(1), Value::Integer(2)], Vec::new()));
+        assert_eq!(output.finish().len(), 2);
+    }
+
+    #[test]
+    fn output_with_limit_is_full() {
+        let mut output = SelectOutput::new(false, None, Some(2), 1);
+
+        for i in 0..3 {
+            assert!(output.push(vec![Value::Integer(i)], Vec::new()));
+        }
+        assert!(output.is_full());
+        assert!(!output.push(vec![Value::Integer(3)], Vec::new()));
+        assert_eq!(
+            output.finish(),
+            vec![vec![Value::Integer(1)], vec![Value::Integer(2)]]
+        );
+    }
+
+    #[test]
+    fn sorted_output_with_limit_keeps_the_best_rows() {
+        let sorting = vec![SortMethod {
+            by_column: "Edad".to_string(),
+            ascending: false,
+        }];
+        let mut output = SelectOutput::new(false, Some(sorting), Some(3), 0);
+
+        let ages = vec![32, 28, 45, 36, 29, 41, 33, 26, 39, 31, 45];
+        for (i, age) in ages.iter().enumerate() {
+            output.push(vec![Value::Integer(i as i64)], vec![Value::Integer(*age)]);
+        }
+        assert!(!output.is_full());
+        // ties keep the order in which rows were produced
+        assert_eq!(
+            output.finish(),
+            vec![
+                vec![Value::Integer(2)],
+                vec![Value::Integer(10)],
+                vec![Value::Integer(5)]
+            ]
+        );
+    }
+}
@@ -0,0 +1,515 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    conditions::{condition::Condition, value::Value},
+    errors::tperrors::Tperrors,
+    expressions::{
+        aggregate::{Accumulator, Aggregation},
+        expression::{Expression, Row},
+        parser::ExpressionParser,
+        projection::Projection,
+        subquery::SubqueryRunner,
+        window::Windowing,
+    },
+    sorter::sort::SortMethod,
+};
+
+use super::{
+    row_source::{RowCallback, RowSource},
+    select_options::SelectOptions,
+    select_output::SelectOutput,
+};
+
+/// A SELECT already splitted in its clauses, that can be resolved against any source of rows
+///
+/// # Fields
+///
+/// * `columns` - The elements of the SELECT list, Example: ```["Nombre", "Edad * 12 AS meses"]```
+///
+/// * `conditions` - The WHERE clause (if exists)
+///
+/// * `sorting` - The ORDER BY clause (if exists)
+///
+/// * `options` - The rest of the clauses (GROUP BY, HAVING, DISTINCT, LIMIT)
+pub struct SelectQuery {
+    pub columns: Vec<String>,
+    pub conditions: Option<String>,
+    pub sorting: Option<Vec<SortMethod>>,
+    pub options: SelectOptions,
+}
+
+/// What a query needs to know besides its source of rows
+///
+/// # Fields
+///
+/// * `runner` - Runs the subqueries of the query (without it, they can't be used).
+///
+/// * `outer` - The columns and values of the current row of the query that contains
+///   this one (only for a correlated subquery), its columns can be used as if they were
+///   columns of the source.
+#[derive(Default, Clone)]
+pub struct QueryContext {
+    pub runner: Option<Rc<dyn SubqueryRunner>>,
+    pub outer: Option<(Vec<String>, Vec<Value>)>,
+}
+
+impl SelectQuery {
+    /// Resolves the query reading the rows of the source
+    ///
+    /// Returns the header followed by the rows, as text
+    pub fn resolve(self, source: &mut dyn RowSource) -> Result<Vec<Vec<String>>, Tperrors> {
+        self.resolve_with(source, &QueryContext::default())
+    }
+
+    /// Same as resolve, with the runner of the subqueries (and the outer row, if any)
+    pub fn resolve_with(
+        self,
+        source: &mut dyn RowSource,
+        context: &QueryContext,
+    ) -> Result<Vec<Vec<String>>, Tperrors> {
+        let (header, rows) = self.resolve_values(source, context)?;
+
+        let mut result = rows
+            .into_iter()
+            .map(|line| line.iter().map(|v| v.to_string()).collect())
+            .collect::<Vec<Vec<String>>>();
+        result.insert(0, header); // we add at the head the columns of the db
+        Ok(result)
+    }
+
+    /// Resolves the query, returning the names of the columns of the output and its rows
+    ///
+    /// With GROUP BY the rows are grouped on a hash map, and the aggregate functions
+    /// are computed for every group while the source is read (rows are not stored).
+    /// HAVING is checked against every group.
+    ///
+    /// With window functions, the rows (or groups) are stored, since each function needs
+    /// the whole partition of a row before the row can be projected.
+    pub fn resolve_values(
+        self,
+        source: &mut dyn RowSource,
+        context: &QueryContext,
+    ) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors> {
+        let source_columns = source.columns()?;
+
+        // the values of the outer row are added at the end of every row of the source
+        let (outer_columns, outer_values) = match &context.outer {
+            Some((columns, values)) => (columns.to_vec(), values.to_vec()),
+            None => (Vec::new(), Vec::new()),
+        };
+        let all_columns = source_columns
+            .iter()
+            .chain(outer_columns.iter())
+            .cloned()
+            .collect::<Vec<String>>();
+
+        // every requested column is an expression to compute (* means all the columns
+        // of the source, not the ones of the outer row)
+        let select_list = self
+            .columns
+            .iter()
+            .flat_map(|item| match item.trim() {
+                "*" if !outer_columns.is_empty() => source_columns.clone(),
+                _ => vec![item.to_string()],
+            })
+            .collect::<Vec<String>>();
+        let mut projections = Projection::from_select_list(&select_list, &all_columns)?;
+
+        let mut sorting_expressions = match &self.sorting {
+            Some(vec_sort) => vec_sort
+                .iter()
+                .map(|sort_method| ExpressionParser::parse(sort_method.get_by_column()))
+                .collect::<Result<Vec<Expression>, Tperrors>>()?,
+            None => Vec::new(),
+        };
+
+        let group_by = self
+            .options
+            .group_by
+            .iter()
+            .map(|item| {
+                let expression = ExpressionParser::parse(item)?;
+                if expression.contains_aggregate() || expression.contains_window() {
+                    return Err(Tperrors::Syntax(
+                        "Aggregate and window functions can't be used on GROUP BY".to_string(),
+                    ));
+                }
+                expression.validate_columns(&all_columns)?;
+                Ok(expression)
+            })
+            .collect::<Result<Vec<Expression>, Tperrors>>()?;
+
+        let mut having = match &self.options.having {
+            Some(having) => {
+                let expression = ExpressionParser::parse(having)?;
+                if expression.contains_window() {
+                    return Err(Tperrors::Syntax(
+                        "Window functions can't be used on HAVING".to_string(),
+                    ));
+                }
+                expression.validate_columns(&all_columns)?;
+                Some(expression)
+            }
+            None => None,
+        };
+
+        // aggregate functions are replaced by columns holding their result
+        let mut aggregation = Aggregation::new();
+        for projection in projections.iter_mut() {
+            aggregation.extract(&mut projection.expression)?;
+        }
+        for expression in sorting_expressions.iter_mut() {
+            aggregation.extract(expression)?;
+        }
+        if let Some(having) = having.as_mut() {
+            aggregation.extract(having)?;
+        }
+        let grouped = !aggregation.is_empty() || !group_by.is_empty() || having.is_some();
+
+        // window functions are computed after the groups, so they can use aggregate functions
+        let mut windowing = Windowing::new();
+        for projection in projections.iter_mut() {
+            windowing.extract(&mut projection.expression)?;
+        }
+        for expression in sorting_expressions.iter_mut() {
+            windowing.extract(expression)?;
+        }
+
+        // the result of the aggregate functions (if any) followed by the row of the source
+        let base_columns = aggregation
+            .columns()
+            .into_iter()
+            .chain(all_columns.iter().map(|c| c.to_string()))
+            .collect::<Vec<String>>();
+
+        // the output is computed from the result of the window functions (if any)
+        // followed by the base row
+        let row_columns = windowing
+            .columns()
+            .into_iter()
+            .chain(base_columns.iter().cloned())
+            .collect::<Vec<String>>();
+
+        // ORDER BY can use the names of the output (aliases) or any column of the source
+        // so sorting expressions are evaluated against the output followed by the row
+        let sorting_columns = projections
+            .iter()
+            .map(|p| p.name.to_string())
+            .chain(row_columns.iter().map(|c| c.to_string()))
+            .collect::<Vec<String>>();
+        for expression in &sorting_expressions {
+            expression.validate_columns(&sorting_columns)?;
+        }
+
+        // with DISTINCT the rows are compared by the output only, so it's all ORDER BY can use
+        if self.options.distinct {
+            let output_names = projections
+                .iter()
+                .map(|p| p.name.to_string())
+                .collect::<Vec<String>>();
+            let outside = sorting_expressions
+                .iter()
+                .flat_map(|e| e.columns())
+                .find(|c| !output_names.contains(c));
+            if let Some(column) = outside {
+                return Err(Tperrors::Column(format!(
+                    "Column {} must be on the SELECT list to be used on ORDER BY with DISTINCT",
+                    column
+                )));
+            }
+        }
+
+        if grouped {
+            Self::validate_grouped(
+                &projections,
+                &sorting_expressions,
+                &having,
+                &group_by,
+                &aggregation,
+                &windowing,
+            )?;
+        }
+
+        // conditions are parsed only once, and then checked against every row
+        let mut condition = match &self.conditions {
+            Some(conditions) => Some(Condition::compile(conditions, &all_columns)?),
+            None => None,
+        };
+
+        // subqueries are told which columns they can use from the rows of this query
+        if let Some(runner) = &context.runner {
+            for projection in projections.iter_mut() {
+                projection
+                    .expression
+                    .bind_subqueries(runner, &row_columns)?;
+            }
+            if let Some(condition) = condition.as_mut() {
+                condition.bind_subqueries(runner, &all_columns)?;
+            }
+            if let Some(having) = having.as_mut() {
+                having.bind_subqueries(runner, &base_columns)?;
+            }
+            windowing.bind_subqueries(runner, &base_columns)?;
+            for expression in sorting_expressions.iter_mut() {
+                expression.bind_subqueries(runner, &sorting_columns)?;
+            }
+        }
+
+        let mut output = SelectOutput::new(
+            self.options.distinct,
+            self.sorting,
+            self.options.limit,
+            self.options.offset,
+        );
+
+        // with window functions, the base rows are kept until every one of them is known
+        let mut windowed: Vec<(Option<usize>, Vec<Value>)> = Vec::new();
+
+        if !grouped {
+            Self::scan_matching(
+                source,
+                &condition,
+                &all_columns,
+                &outer_values,
+                &mut |number, values| {
+                    if !windowing.is_empty() {
+                        windowed.push((Some(number), values));
+                        return Ok(true);
+                    }
+                    let row = Row::new(&row_columns, &values).with_number(number);
+                    let (projected, sorting_keys) = Self::project_row(
+                        &projections,
+                        &sorting_expressions,
+                        &sorting_columns,
+                        &row,
+                        &values,
+                    )?;
+                    output.push(projected, sorting_keys);
+                    // without ORDER BY, there is no need to keep reading once the LIMIT is reached
+                    Ok(!output.is_full())
+                },
+            )?;
+        } else {
+            // a single pass over the source, only the first row of each group is stored
+            // (to compute the expressions that are on the GROUP BY)
+            let mut groups: Vec<(Vec<Value>, Vec<Accumulator>)> = Vec::new();
+            let mut group_positions: HashMap<Vec<Option<String>>, usize> = HashMap::new();
+
+            Self::scan_matching(
+                source,
+                &condition,
+                &all_columns,
+                &outer_values,
+                &mut |number, values| {
+                    let row = Row::new(&all_columns, &values).with_number(number);
+                    // NULL has no hash key, so every NULL goes to the same group
+                    let key = group_by
+                        .iter()
+                        .map(|expression| Ok(expression.evaluate(&row)?.hash_key()))
+                        .collect::<Result<Vec<Option<String>>, Tperrors>>()?;
+
+                    let position = match group_positions.get(&key) {
+                        Some(position) => *position,
+                        None => {
+                            groups.push((values.clone(), aggregation.accumulators()));
+                            group_positions.insert(key, groups.len() - 1);
+                            groups.len() - 1
+                        }
+                    };
+                    aggregation.update(&mut groups[position].1, &row)?;
+                    Ok(true)
+                },
+            )?;
+
+            // without GROUP BY the whole source is a group, even if it's empty
+            if group_by.is_empty() && groups.is_empty() {
+                let values = vec![Value::Null; source_columns.len()]
+                    .into_iter()
+                    .chain(outer_values.iter().cloned())
+                    .collect::<Vec<Value>>();
+                groups.push((values, aggregation.accumulators()));
+            }
+
+            for (first_row, accumulators) in groups {
+                let values = accumulators
+                    .iter()
+                    .map(|accumulator| accumulator.result())
+                    .chain(first_row)
+                    .collect::<Vec<Value>>();
+                let row = Row::new(&base_columns, &values);
+
+                if let Some(having) = &having {
+                    if !having.matches(&row)? {
+                        continue;
+                    }
+                }
+                if !windowing.is_empty() {
+                    windowed.push((None, values));
+                    continue;
+                }
+                let (projected, sorting_keys) = Self::project_row(
+                    &projections,
+                    &sorting_expressions,
+                    &sorting_columns,
+                    &row,
+                    &values,
+                )?;
+                output.push(projected, sorting_keys);
+            }
+        }
+
+        if !windowing.is_empty() {
+            let (numbers, base_rows): (Vec<Option<usize>>, Vec<Vec<Value>>) =
+                windowed.into_iter().unzip();
+            let window_values = windowing.compute(&base_rows, &base_columns)?;
+
+            for ((number, base), window) in numbers.into_iter().zip(base_rows).zip(window_values) {
+                let values = window.into_iter().chain(base).collect::<Vec<Value>>();
+                let row = match number {
+                    Some(number) => Row::new(&row_columns, &values).with_number(number),
+                    None => Row::new(&row_columns, &values),
+                };
+                let (projected, sorting_keys) = Self::project_row(
+                    &projections,
+                    &sorting_expressions,
+                    &sorting_columns,
+                    &row,
+                    &values,
+                )?;
+                output.push(projected, sorting_keys);
+            }
+        }
+
+        // lets only now keep the headers of the columns requested
+        let header_requested = projections
+            .iter()
+            .map(|p| p.name.to_string())
+            .collect::<Vec<String>>();
+
+        // at this point, i have the result of the query, lets sort it as needed
+        Ok((header_requested, output.finish()))
+    }
+
+    /// Reads the rows of the source and calls `on_row` with the ones that meet the conditions
+    ///
+    /// The values of the outer row (if any) are added at the end of every row
+    fn scan_matching(
+        source: &mut dyn RowSource,
+        condition: &Option<Expression>,
+        columns: &[String],
+        outer_values: &[Value],
+        on_row: &mut RowCallback,
+    ) -> Result<(), Tperrors> {
+        source.scan(&mut |number, mut values| {
+            values.extend(outer_values.iter().cloned());
+            let matches = match condition {
+                Some(expression) => {
+                    expression.matches(&Row::new(columns, &values).with_number(number))?
+                }
+                None => true,
+            };
+            match matches {
+                true => on_row(number, values),
+                false => Ok(true),
+            }
+        })
+    }
+
+    /// Computes the output of a row and the values of its sorting keys
+    ///
+    /// `values` are the values of the row, the sorting keys can also use the output
+    fn project_row(
+        projections: &[Projection],
+        sorting_expressions: &[Expression],
+        sorting_columns: &[String],
+        row: &Row,
+        values: &[Value],
+    ) -> Result<(Vec<Value>, Vec<Value>), Tperrors> {
+        let projected = projections
+            .iter()
+            .map(|p| p.expression.evaluate(row))
+            .collect::<Result<Vec<Value>, Tperrors>>()?;
+
+        if sorting_expressions.is_empty() {
+            return Ok((projected, Vec::new()));
+        }
+        let sorting_values = projected
+            .iter()
+            .chain(values.iter())
+            .cloned()
+            .collect::<Vec<Value>>();
+        let sorting_row = Row::new(sorting_columns, &sorting_values);
+        let sorting_keys = sorting_expressions
+            .iter()
+            .map(|e| e.evaluate(&sorting_row))
+            .collect::<Result<Vec<Value>, Tperrors>>()?;
+        Ok((projected, sorting_keys))
+    }
+
+    /// On a query with groups, the output is a single row for each group, so a column
+    /// can only be used if it is on the GROUP BY or inside an aggregate function
+    ///
+    /// ORDER BY can also use the names of the output, and the expressions inside the
+    /// window functions follow the same rules as the SELECT list
+    fn validate_grouped(
+        projections: &[Projection],
+        sorting_expressions: &[Expression],
+        having: &Option<Expression>,
+        group_by: &[Expression],
+        aggregation: &Aggregation,
+        windowing: &Windowing,
+    ) -> Result<(), Tperrors> {
+        let aggregated = aggregation
+            .columns()
+            .into_iter()
+            .chain(windowing.columns())
+            .collect::<Vec<String>>();
+        let output = projections
+            .iter()
+            .map(|p| p.name.to_string())
+            .chain(aggregated.iter().map(|c| c.to_string()))
+            .collect::<Vec<String>>();
+
+        let expressions = projections
+            .iter()
+            .map(|p| (&p.expression, &aggregated))
+            .chain(having.iter().map(|h| (h, &aggregated)))
+            .chain(
+                windowing
+                    .expressions()
+                    .into_iter()
+                    .map(|e| (e, &aggregated)),
+            )
+            .chain(sorting_expressions.iter().map(|e| (e, &output)));
+
+        for (expression, allowed) in expressions {
+            if let Some(column) = Self::ungrouped_column(expression, group_by, allowed) {
+                return Err(Tperrors::Column(format!(
+                    "Column {} must appear in GROUP BY or be used inside an aggregate function",
+                    column
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the first column of the expression that isn't part of an expression
+    /// of the GROUP BY (nor one of the allowed columns)
+    fn ungrouped_column<'a>(
+        expression: &'a Expression,
+        group_by: &[Expression],
+        allowed: &[String],
+    ) -> Option<&'a String> {
+        if group_by.contains(expression) {
+            return None;
+        }
+        match expression {
+            Expression::Column(name) if allowed.contains(name) => None,
+            Expression::Column(name) => Some(name),
+            _ => expression
+                .children()
+                .into_iter()
+                .find_map(|child| Self::ungrouped_column(child, group_by, allowed)),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+
+use crate::{
+    conditions::value::Value,
+    errors::tperrors::Tperrors,
+    expressions::{expression::Row, subquery::SubqueryRunner},
+    sorter::sort::SortMethod,
+};
+
+use super::{
+    row_source::ResultSource,
+    select_options::SelectOptions,
+    select_query::{QueryContext, SelectQuery},
+};
+
+/// How the rows of two SELECT are combined
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetOperator {
+    /// The rows of both, repeated rows are returned once
+    Union,
+    /// The rows of both, as they are
+    UnionAll,
+    /// The rows that are on both
+    Intersect,
+    /// The rows of the first one that aren't on the second one
+    Except,
+}
+
+impl SetOperator {
+    /// The name of the operator on a query
+    pub fn name(&self) -> &str {
+        match self {
+            SetOperator::Union => "UNION",
+            SetOperator::UnionAll => "UNION ALL",
+            SetOperator::Intersect => "INTERSECT",
+            SetOperator::Except => "EXCEPT",
+        }
+    }
+
+    /// Combines the rows of two SELECT
+    ///
+    /// Rows are compared by the hash key of their values, so two NULL are the same
+    /// (unlike on a condition). Except for UNION ALL, the result has no repeated rows.
+    pub fn combine(&self, left: Vec<Vec<Value>>, right: Vec<Vec<Value>>) -> Vec<Vec<Value>> {
+        let mut seen: HashSet<Vec<Option<String>>> = HashSet::new();
+
+        match self {
+            SetOperator::UnionAll => left.into_iter().chain(right).collect(),
+            SetOperator::Union => left
+                .into_iter()
+                .chain(right)
+                .filter(|row| seen.insert(Self::key(row)))
+                .collect(),
+            SetOperator::Intersect | SetOperator::Except => {
+                let on_right = right
+                    .iter()
+                    .map(|row| Self::key(row))
+                    .collect::<HashSet<Vec<Option<String>>>>();
+                let keep_found = *self == SetOperator::Intersect;
+                left.into_iter()
+                    .filter(|row| {
+                        let key = Self::key(row);
+                        on_right.contains(&key) == keep_found && seen.insert(key)
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The key of a row to look for it on a hash set
+    fn key(row: &[Value]) -> Vec<Option<String>> {
+        row.iter().map(|value| value.hash_key()).collect()
+    }
+}
+
+/// Several SELECT combined with UNION, INTERSECT or EXCEPT
+///
+/// Example: ```SELECT Nombre FROM clientes UNION SELECT Nombre FROM socios ORDER BY Nombre;```
+///
+/// # Fields
+///
+/// * `first` - The first SELECT (with its ; at the end).
+///
+/// * `operations` - Each SELECT that follows and how it's combined with the ones before it.
+///
+/// * `sorting` - The ORDER BY at the end, applied to the combined rows.
+///
+/// * `limit` - The LIMIT (or FETCH) at the end, applied to the combined rows.
+///
+/// * `offset` - The OFFSET at the end, applied to the combined rows.
+pub struct CompoundQuery {
+    pub first: String,
+    pub operations: Vec<(SetOperator, String)>,
+    pub sorting: Option<Vec<SortMethod>>,
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+impl CompoundQuery {
+    /// Runs every SELECT and combines their rows
+    ///
+    /// INTERSECT is applied before UNION and EXCEPT, like on SQL.
+    ///
+    /// The names of the columns are the ones of the first SELECT, every SELECT
+    /// must return the same number of columns
+    pub fn resolve(
+        self,
+        runner: &dyn SubqueryRunner,
+        outer: Option<&Row>,
+    ) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors> {
+        let (header, first_rows) = runner.run(&self.first, outer)?;
+
+        // the INTERSECT are combined first, the rest of them are kept for later
+        let mut terms: Vec<(Option<SetOperator>, Vec<Vec<Value>>)> = vec![(None, first_rows)];
+        for (operator, query) in self.operations {
+            let (columns, rows) = runner.run(&query, outer)?;
+            if columns.len() != header.len() {
+                return Err(Tperrors::Syntax(format!(
+                    "Each SELECT combined with {} must return the same number of columns, {} and {} found",
+                    operator.name(),
+                    header.len(),
+                    columns.len()
+                )));
+            }
+            match (&operator, terms.last_mut()) {
+                (SetOperator::Intersect, Some((_, last))) => {
+                    *last = operator.combine(std::mem::take(last), rows);
+                }
+                _ => terms.push((Some(operator), rows)),
+            }
+        }
+
+        let mut rows: Vec<Vec<Value>> = Vec::new();
+        for (operator, term) in terms {
+            rows = match operator {
+                Some(operator) => operator.combine(rows, term),
+                None => term,
+            };
+        }
+
+        if self.sorting.is_none() && self.limit.is_none() && self.offset == 0 {
+            return Ok((header, rows));
+        }
+
+        // the clauses at the end are a SELECT over the combined rows
+        let query = SelectQuery {
+            columns: vec!["*".to_string()],
+            conditions: None,
+            sorting: self.sorting,
+            options: SelectOptions {
+                limit: self.limit,
+                offset: self.offset,
+                ..SelectOptions::default()
+            },
+        };
+        query.resolve_values(
+            &mut ResultSource::new(header, rows),
+            &QueryContext::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(values: &[i64]) -> Vec<Vec<Value>> {
+        values.iter().map(|v| vec![Value::Integer(*v)]).collect()
+    }
+
+    #[test]
+    fn combine_rows_of_two_selects() {
+        let left = rows(&[1, 2, 2, 3]);
+        let right = rows(&[3, 4, 4]);
+
+        assert_eq!(
+            SetOperator::UnionAll.combine(left.clone(), right.clone()),
+            rows(&[1, 2, 2, 3, 3, 4, 4])
+        );
+        assert_eq!(
+            SetOperator::Union.combine(left.clone(), right.clone()),
+            rows(&[1, 2, 3, 4])
+        );
+        assert_eq!(
+            SetOperator::Intersect.combine(left.clone(), right.clone()),
+            rows(&[3])
+        );
+        assert_eq!(SetOperator::Except.combine(left, right), rows(&[1, 2]));
+    }
+
+    #[test]
+    fn combine_treats_nulls_as_the_same_row() {
+        let left = vec![vec![Value::Null], vec![Value::Integer(1)]];
+        let right = vec![vec![Value::Null]];
+
+        assert_eq!(
+            SetOperator::Union.combine(left.clone(), right.clone()),
+            left.clone()
+        );
+        assert_eq!(SetOperator::Except.combine(left, right), rows(&[1]));
+    }
+}
@@ -1,5 +1,4 @@
 use std::{
-    cmp::Ordering,
     collections::HashMap,
     fs::{self, File},
     io::{BufRead, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
@@ -8,12 +7,41 @@ use std::{
 
 use crate::{
     conditions::{condition::Condition, value::Value},
+    expressions::{
+        expression::{column_position, is_qualified_name, Expression, Row},
+        parser::ExpressionParser,
+    },
     sorter::sort::SortMethod,
 };
 
+use super::{
+    join::JoinCondition,
+    merge::{MergeAction, MergeCase, MergeClause, MergeQuery},
+    on_conflict::{ConflictAction, OnConflict},
+    row_source::{RowCallback, RowSource},
+    select_options::SelectOptions,
+    select_query::SelectQuery,
+};
 use crate::errors::fileerrors::*;
 use crate::errors::tperrors::*;
 
+/// What a WHEN clause of a MERGE does, with its values parsed
+enum MergeChange {
+    /// the position of each changed column with the expression of its value
+    Update(Vec<(usize, Expression)>),
+    Delete,
+    /// the position of each given column with the expression of its value
+    Insert(Vec<(usize, Expression)>),
+    Nothing,
+}
+
+/// A WHEN clause of a MERGE parsed against the columns of both tables
+struct CompiledMergeClause {
+    case: MergeCase,
+    condition: Option<Expression>,
+    change: MergeChange,
+}
+
 pub struct Table<R: Read + Seek> {
     file_name: String,
     reader: BufReader<R>,
@@ -79,136 +107,37 @@ impl<R: Read + Seek> Table<R> {
     /// ```SELECT Nombre FROM table WHERE Apellido = 'Doe' ORDER BY Nombre ASC;```
     ///
     /// The result will be a vector of vector of string (The content readed from the csv)
-
     pub fn resolve_select(
         &mut self,
         columns: Vec<String>,
         opt_conditions_as_str: Option<&str>,
         vector_sorting: Option<Vec<SortMethod>>,
     ) -> Result<Vec<Vec<String>>, Tperrors> {
-        let columns_from_file = self.get_column_from_file()?;
+        self.resolve_select_with_options(
+            columns,
+            opt_conditions_as_str,
+            vector_sorting,
+            &SelectOptions::default(),
+        )
+    }
 
-        // if len is 1 AND the only element is a * (joker) we need to get all the columns
-        let index_requested_columns = if columns.len() == 1 && columns[0] == "*" {
-            (0..columns_from_file.len()).collect::<Vec<usize>>()
-        } else {
-            columns
-                .iter()
-                .map(|c| {
-                    columns_from_file
-                        .iter()
-                        .position(|col| col == c)
-                        .ok_or_else(|| {
-                            Tperrors::Column(format!("Invalid column {} inside the query", c))
-                        })
-                })
-                .collect::<Result<Vec<usize>, Tperrors>>()?
+    /// Same as resolve_select, applying the options of the query
+    ///
+    /// The query is resolved reading the rows of this table (see SelectQuery)
+    pub fn resolve_select_with_options(
+        &mut self,
+        columns: Vec<String>,
+        opt_conditions_as_str: Option<&str>,
+        vector_sorting: Option<Vec<SortMethod>>,
+        options: &SelectOptions,
+    ) -> Result<Vec<Vec<String>>, Tperrors> {
+        let query = SelectQuery {
+            columns,
+            conditions: opt_conditions_as_str.map(|c| c.to_string()),
+            sorting: vector_sorting,
+            options: options.clone(),
         };
-
-        let mut result: Vec<Vec<String>> = Vec::new();
-
-        match self.reader.seek(SeekFrom::Start(0)) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(Tperrors::Generic(format!(
-                    "Error while trying to read the file: {}",
-                    e
-                )));
-            }
-        }
-        let reader = &mut self.reader;
-
-        let index_columns = (0..columns_from_file.len()).collect::<Vec<usize>>();
-        for line in reader.by_ref().lines().skip(1) {
-            let line = match line {
-                Ok(l) => l,
-                Err(e) => {
-                    return Err(Tperrors::Generic(format!(
-                        "Error while trying to read the file: {}",
-                        e
-                    )));
-                }
-            };
-            let splitted_line = line.split(",").collect::<Vec<&str>>();
-
-            if opt_conditions_as_str.is_some() {
-                let (extracted_conditions, _line_to_write) =
-                    Self::extract_conditions(&index_columns, &splitted_line, &columns_from_file);
-                //panic!("Extracted conditions: {:?}", extracted_conditions);
-                let condition = Condition::new(extracted_conditions);
-                let str_conditions = opt_conditions_as_str.unwrap_or("");
-
-                match condition.matches_condition(str_conditions) {
-                    Ok(true) => {
-                        result.push(splitted_line.iter().map(|s| s.to_string()).collect());
-                    }
-                    Ok(false) => {}
-                    Err(e) => {
-                        let e = e.to_string();
-                        return Err(Tperrors::Generic(e));
-                    }
-                }
-            } else {
-                result.push(splitted_line.iter().map(|s| s.to_string()).collect());
-            }
-        }
-
-        // at this point, i have the result of the query
-        // I need to sort it as needed, and now keep only the columns requested
-        if let Some(vec_sort) = vector_sorting {
-            for sort_method in &vec_sort {
-                let column = sort_method.get_by_column();
-                let index = columns_from_file
-                    .iter()
-                    .position(|c| c == column)
-                    .ok_or_else(|| {
-                        Tperrors::Column(format!("Invalid column {} inside the query", column))
-                    })?;
-
-                result.sort_by(|a, b| {
-                    let a_value = a[index].as_str();
-                    let b_value = b[index].as_str();
-
-                    match a_value.cmp(b_value) {
-                        Ordering::Less => {
-                            if sort_method.is_ascending() {
-                                Ordering::Less
-                            } else {
-                                Ordering::Greater
-                            }
-                        }
-                        Ordering::Greater => {
-                            if sort_method.is_ascending() {
-                                Ordering::Greater
-                            } else {
-                                Ordering::Less
-                            }
-                        }
-                        Ordering::Equal => Ordering::Equal,
-                    }
-                });
-            }
-        }
-
-        // last thing, we filter the columns requested
-        result = result
-            .iter()
-            .map(|line| {
-                index_requested_columns
-                    .iter()
-                    .map(|i| line[*i].to_string())
-                    .collect()
-            })
-            .collect();
-
-        // lets only now keep the headers of the columns requested
-        let header_requested = index_requested_columns
-            .iter()
-            .map(|i| columns_from_file[*i].to_string())
-            .collect::<Vec<String>>();
-
-        result.insert(0, header_requested); // we add at the head the columns of the db
-        Ok(result)
+        query.resolve(self)
     }
 
     /// given a columns and values as Vec of String
@@ -286,6 +215,101 @@ impl<R: Read + Seek> Table<R> {
         Ok(vector_of_lines_to_writte)
     }
 
+    /// Given the columns to fill (all of them if it's empty) and the rows of a query
+    ///
+    /// returns the lines to add to the file, like resolve_insert.
+    ///
+    /// Every row must have a value for each column, and each value must have the type of
+    /// the values already on its column (a column of numbers can't get a text). The
+    /// columns that aren't given are left empty (NULL).
+    pub fn resolve_insert_rows(
+        &mut self,
+        columns: &[String],
+        rows: Vec<Vec<Value>>,
+        width: usize,
+    ) -> Result<Vec<Vec<String>>, Tperrors> {
+        let table_columns = self.get_column_from_file()?;
+        let columns = match columns.is_empty() {
+            true => table_columns.clone(),
+            false => columns.to_vec(),
+        };
+
+        let mut positions: Vec<usize> = Vec::new();
+        for column in &columns {
+            match table_columns.iter().position(|c| c == column) {
+                Some(position) if !positions.contains(&position) => positions.push(position),
+                Some(_) => {
+                    return Err(Tperrors::Syntax(format!(
+                        "Column {} is given more than once",
+                        column
+                    )));
+                }
+                None => {
+                    return Err(Tperrors::Column(format!(
+                        "Invalid column {} inside the query",
+                        column
+                    )));
+                }
+            }
+        }
+        if width != columns.len() {
+            return Err(Tperrors::Syntax(format!(
+                "Invalid INSERT query (Columns and values count mismatch, {} columns and {} values)",
+                columns.len(),
+                width
+            )));
+        }
+
+        let types = self.first_values()?;
+        let mut lines: Vec<Vec<String>> = Vec::new();
+        for row in rows {
+            let mut line = vec![String::new(); table_columns.len()];
+            for (value, position) in row.into_iter().zip(positions.iter()) {
+                if !Self::same_type(&types[*position], &value) {
+                    return Err(Tperrors::Syntax(format!(
+                        "Invalid value {} for column {}, its values are like {}",
+                        value, table_columns[*position], types[*position]
+                    )));
+                }
+                line[*position] = Self::field_of(value)?;
+            }
+            lines.push(line);
+        }
+        // the lines have every column, so they are written as they are
+        self.resolve_insert(table_columns, lines)
+    }
+
+    /// The first value that isn't NULL of every column, it tells the type of the column
+    ///
+    /// A column without values is NULL
+    fn first_values(&mut self) -> Result<Vec<Value>, Tperrors> {
+        let mut first_values = vec![Value::Null; self.get_column_from_file()?.len()];
+        self.scan(&mut |_, values| {
+            for (first, value) in first_values.iter_mut().zip(values) {
+                if first.is_null() {
+                    *first = value;
+                }
+            }
+            Ok(first_values.iter().any(|value| value.is_null()))
+        })?;
+        Ok(first_values)
+    }
+
+    /// Returns true if the value can be written on a column whose values are like `existing`
+    ///
+    /// Any value can be written as text, and an integer is also a real number
+    fn same_type(existing: &Value, value: &Value) -> bool {
+        matches!(
+            (existing, value),
+            (Value::Null, _)
+                | (_, Value::Null)
+                | (Value::String(_), _)
+                | (Value::Integer(_), Value::Integer(_))
+                | (Value::Real(_), Value::Integer(_) | Value::Real(_))
+                | (Value::Boolean(_), Value::Boolean(_))
+        )
+    }
+
     /// Private function that handles
     ///
     /// Given the columns to update, the values to update, and the conditions as str
@@ -293,44 +317,27 @@ impl<R: Read + Seek> Table<R> {
     /// it will resolve the query, if ok it will return Ok() else it will throw an error
     ///
     /// containing the result of the query.
+    ///
+    /// Every value is an expression computed against the row before any change, so
+    /// ```SET a = b, b = a``` swaps both columns.
+    ///
+    /// Returns the values of the changed rows, after the change
     fn resolve_update<W: Write>(
         &mut self,
         columns: Vec<String>,
         values: Vec<String>,
         opt_conditions: Option<&str>,
         file_to_write: W, // its either a Cursor o a File as temp
-    ) -> Result<(), Tperrors> {
+    ) -> Result<Vec<Vec<Value>>, Tperrors> {
         // we need to check if the columns are valid
         let splitted_columns_from_file = self.get_column_from_file()?;
 
-        let index_selected_column: Vec<usize> = splitted_columns_from_file
-            .iter()
-            .enumerate()
-            .filter(|(_i, c)| columns.contains(&c.to_string()))
-            .map(|(i, _c)| i)
-            .collect::<Vec<usize>>();
-
-        let index_all_columns = (0..splitted_columns_from_file.len()).collect::<Vec<usize>>();
+        let condition = Self::compile_conditions(opt_conditions, &splitted_columns_from_file)?;
 
         // we need to change the value of the columns
-        // we use a hash to store the new values
-        // and keys the index of the columns of change
+        // we store the position of each column to change with the expression of its value
         // the change is done if the conditions are met
-        let mut hash_changes: HashMap<usize, String> = HashMap::new();
-
-        for (i, _col) in splitted_columns_from_file.iter().enumerate() {
-            if index_selected_column.contains(&i) {
-                let position = match index_selected_column.iter().position(|&x| x == i) {
-                    Some(p) => p,
-                    None => {
-                        return Err(Tperrors::Generic(
-                            "Position not found inside the columns".to_string(),
-                        ));
-                    }
-                };
-                hash_changes.insert(i, values[position].to_string());
-            }
-        }
+        let changes = Self::compile_assignments(&columns, &values, &splitted_columns_from_file)?;
 
         match self.reader.seek(SeekFrom::Start(0)) {
             Ok(_) => {}
@@ -343,6 +350,7 @@ impl<R: Read + Seek> Table<R> {
         }
 
         let mut temporal_file = BufWriter::new(file_to_write);
+        let mut changed_rows: Vec<Vec<Value>> = Vec::new();
 
         match Self::write_a_line(splitted_columns_from_file.join(","), &mut temporal_file) {
             Ok(_) => {}
@@ -354,7 +362,7 @@ impl<R: Read + Seek> Table<R> {
             }
         }
 
-        for line in self.reader.by_ref().lines().skip(1) {
+        for (number, line) in self.reader.by_ref().lines().skip(1).enumerate() {
             let line = match line {
                 Ok(l) => l,
                 Err(e) => {
@@ -365,69 +373,42 @@ impl<R: Read + Seek> Table<R> {
                 }
             };
             let splitted_line = line.split(",").collect::<Vec<&str>>();
+            let values = Self::values_from_line(&line);
 
-            match opt_conditions {
-                Some(str_conditions) => {
-                    let splitted_columns_as_string = splitted_columns_from_file.as_slice();
-                    let (vec_conditions, _) = Self::extract_conditions(
-                        &index_all_columns,
-                        &splitted_line,
-                        splitted_columns_as_string,
-                    );
-
-                    // lets see all keys and values
-                    let condition = Condition::new(vec_conditions);
-
-                    match condition.matches_condition(str_conditions) {
-                        Ok(true) => {
-                            // criteria reached, we need to change the index
-                            // of the columns according to the hash database with the proper value
-                            let mut new_line = splitted_line.to_vec();
-                            for (i, value) in hash_changes.iter() {
-                                new_line[*i] = value;
-                            }
-                            // convert it as Vec<String
-
-                            match Self::write_a_line(new_line.join(","), &mut temporal_file) {
-                                Ok(_) => {}
-                                Err(e) => {
-                                    return Err(Tperrors::Generic(format!(
-                                        "Error while trying to write the file: {}",
-                                        e
-                                    )));
-                                }
-                            }
-                        }
-                        Ok(false) => {
-                            match Self::write_a_line(splitted_line.join(","), &mut temporal_file) {
-                                Ok(_) => {}
-                                Err(e) => {
-                                    return Err(Tperrors::Generic(format!(
-                                        "Error while trying to write the file: {}",
-                                        e
-                                    )));
-                                }
-                            }
-                        }
-                        Err(_) => {
-                            return Err(Tperrors::Generic("Error checking conditions".to_string()));
-                        }
-                    }
+            let row = Row::new(&splitted_columns_from_file, &values).with_number(number + 1);
+
+            if Self::row_matches(&condition, &row)? {
+                // criteria reached, we need to change the index
+                // of the columns with the value computed from the original row
+                let new_values = changes
+                    .iter()
+                    .map(|(_, expression)| Self::field_of(expression.evaluate(&row)?))
+                    .collect::<Result<Vec<String>, Tperrors>>()?;
+
+                let mut new_line = splitted_line.to_vec();
+                for ((i, _), value) in changes.iter().zip(new_values.iter()) {
+                    new_line[*i] = value;
                 }
-                None => {
-                    // we need to change the values
-                    let mut new_line = splitted_line.to_vec();
-                    for (i, value) in hash_changes.iter() {
-                        new_line[*i] = value;
+                let new_line = new_line.join(",");
+                changed_rows.push(Self::values_from_line(&new_line));
+
+                match Self::write_a_line(new_line, &mut temporal_file) {
+                    Ok(_) => {}
+                    Err(e) => {
+                        return Err(Tperrors::Generic(format!(
+                            "Error while trying to write the file: {}",
+                            e
+                        )));
                     }
-                    match Self::write_a_line(new_line.join(","), &mut temporal_file) {
-                        Ok(_) => {}
-                        Err(e) => {
-                            return Err(Tperrors::Generic(format!(
-                                "Error while trying to write the file: {}",
-                                e
-                            )));
-                        }
+                }
+            } else {
+                match Self::write_a_line(line, &mut temporal_file) {
+                    Ok(_) => {}
+                    Err(e) => {
+                        return Err(Tperrors::Generic(format!(
+                            "Error while trying to write the file: {}",
+                            e
+                        )));
                     }
                 }
             }
@@ -441,21 +422,22 @@ impl<R: Read + Seek> Table<R> {
                 )));
             }
         }
-        Ok(())
+        Ok(changed_rows)
     }
 
     /// Function to resolve the update query
     ///
     /// Under a file path, it will resolve the update query
     ///
-    /// It will return the path of the temporal file to make later make the switch
+    /// It will return the path of the temporal file to make later make the switch,
+    /// with the values of the changed rows
     ///
     pub fn resolve_update_for_file(
         &mut self,
         columns: Vec<String>,
         values: Vec<String>,
         opt_conditions: Option<&str>,
-    ) -> Result<String, Tperrors> {
+    ) -> Result<(String, Vec<Vec<Value>>), Tperrors> {
         let temporal_file_path = match self.generate_temporal_file_path() {
             Ok(path) => path,
             Err(e) => {
@@ -474,8 +456,14 @@ impl<R: Read + Seek> Table<R> {
                 )));
             }
         };
-        self.resolve_update(columns, values, opt_conditions, temporal_file)?;
-        Ok(temporal_file_path)
+        // if the update fails the table is left as it was, without the temporal file
+        match self.resolve_update(columns, values, opt_conditions, temporal_file) {
+            Ok(changed_rows) => Ok((temporal_file_path, changed_rows)),
+            Err(e) => {
+                let _ = fs::remove_file(&temporal_file_path);
+                Err(e)
+            }
+        }
     }
 
     pub fn resolve_update_mock(
@@ -501,39 +489,641 @@ impl<R: Read + Seek> Table<R> {
         Ok(BufReader::new(Cursor::new(inner_buffer)))
     }
 
-    /// Helper to extract the conditions from the splitted line
+    /// Given the lines of an INSERT (the ones of resolve_insert or resolve_insert_rows)
+    /// and its ON CONFLICT clause, writes the table with the lines inserted on the writer
     ///
-    /// Given a index of columns, the columns and the splitted line
+    /// A line whose key is already on the table is not inserted, with DO UPDATE the row
+    /// of the table is updated instead. The table is read only once, for every line.
     ///
-    /// we return a hash of conditions AND the line itself.
+    /// Returns the values of the updated rows followed by the ones of the inserted rows
+    fn resolve_upsert<W: Write>(
+        &mut self,
+        lines: Vec<Vec<String>>,
+        on_conflict: &OnConflict,
+        file_to_write: W,
+    ) -> Result<Vec<Vec<Value>>, Tperrors> {
+        let splitted_columns_from_file = self.get_column_from_file()?;
+
+        let mut key: Vec<usize> = Vec::new();
+        for column in &on_conflict.key {
+            match column_position(&splitted_columns_from_file, column) {
+                Some(position) => key.push(position),
+                None => {
+                    return Err(Tperrors::Column(format!(
+                        "Invalid column {} inside the ON CONFLICT key",
+                        column
+                    )));
+                }
+            }
+        }
+
+        // the values of DO UPDATE SET can read the line that wasn't inserted (EXCLUDED.column)
+        let columns_with_excluded = OnConflict::columns_with_excluded(&splitted_columns_from_file);
+        let changes = match &on_conflict.action {
+            ConflictAction::Nothing => None,
+            ConflictAction::Update { columns, values } => {
+                let changes = Self::compile_assignments_with(
+                    columns,
+                    values,
+                    &columns_with_excluded,
+                    OnConflict::normalize_excluded,
+                )?;
+                if changes
+                    .iter()
+                    .any(|(position, _)| *position >= splitted_columns_from_file.len())
+                {
+                    return Err(Tperrors::Column(
+                        "Only the columns of the table can be changed by DO UPDATE SET".to_string(),
+                    ));
+                }
+                Some(changes)
+            }
+        };
+
+        // a full line may come as a single field, so the fields are split again
+        let lines = lines
+            .into_iter()
+            .map(|line| line.join(","))
+            .collect::<Vec<String>>();
+        let proposed = lines
+            .iter()
+            .map(|line| Self::values_from_line(line))
+            .collect::<Vec<Vec<Value>>>();
+
+        // the line to insert of every key, a key with NULL never conflicts
+        let mut pending: HashMap<Vec<Option<String>>, usize> = HashMap::new();
+        let mut conflicted = vec![false; proposed.len()];
+        for (i, values) in proposed.iter().enumerate() {
+            let line_key = match Self::key_of(&key, values) {
+                Some(line_key) => line_key,
+                None => continue,
+            };
+            if pending.contains_key(&line_key) {
+                if changes.is_some() {
+                    return Err(Tperrors::Generic(
+                        "ON CONFLICT DO UPDATE can't change the same row twice, a key is repeated on the inserted rows"
+                            .to_string(),
+                    ));
+                }
+                conflicted[i] = true;
+                continue;
+            }
+            pending.insert(line_key, i);
+        }
+
+        match self.reader.seek(SeekFrom::Start(0)) {
+            Ok(_) => {}
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to read the file: {}",
+                    e
+                )));
+            }
+        }
+
+        let mut temporal_file = BufWriter::new(file_to_write);
+        let mut changed_rows: Vec<Vec<Value>> = Vec::new();
+        Self::write_temporal_line(splitted_columns_from_file.join(","), &mut temporal_file)?;
+
+        for (number, line) in self.reader.by_ref().lines().skip(1).enumerate() {
+            let line = match line {
+                Ok(l) => l,
+                Err(e) => {
+                    return Err(Tperrors::Generic(format!(
+                        "Error while trying to read the file: {}",
+                        e
+                    )));
+                }
+            };
+            let values = Self::values_from_line(&line);
+            let found = Self::key_of(&key, &values).and_then(|k| pending.get(&k).copied());
+
+            let (i, changes) = match (found, &changes) {
+                (Some(i), Some(changes)) => (i, changes),
+                (Some(i), None) => {
+                    conflicted[i] = true;
+                    Self::write_temporal_line(line, &mut temporal_file)?;
+                    continue;
+                }
+                (None, _) => {
+                    Self::write_temporal_line(line, &mut temporal_file)?;
+                    continue;
+                }
+            };
+            conflicted[i] = true;
+
+            // the values are computed from the row of the table and the line not inserted
+            let values_with_excluded = values
+                .iter()
+                .chain(proposed[i].iter())
+                .cloned()
+                .collect::<Vec<Value>>();
+            let row =
+                Row::new(&columns_with_excluded, &values_with_excluded).with_number(number + 1);
+            let new_values = changes
+                .iter()
+                .map(|(_, expression)| Self::field_of(expression.evaluate(&row)?))
+                .collect::<Result<Vec<String>, Tperrors>>()?;
+
+            let mut new_line = line.split(",").collect::<Vec<&str>>();
+            for ((position, _), value) in changes.iter().zip(new_values.iter()) {
+                new_line[*position] = value;
+            }
+            let new_line = new_line.join(",");
+            changed_rows.push(Self::values_from_line(&new_line));
+            Self::write_temporal_line(new_line, &mut temporal_file)?;
+        }
+
+        // the lines without conflicts are inserted at the end
+        for (i, line) in lines.into_iter().enumerate() {
+            if !conflicted[i] {
+                changed_rows.push(proposed[i].clone());
+                Self::write_temporal_line(line, &mut temporal_file)?;
+            }
+        }
+        match temporal_file.flush() {
+            Ok(_) => {}
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to write the file: {}",
+                    e
+                )));
+            }
+        }
+        Ok(changed_rows)
+    }
+
+    /// Function to resolve an INSERT with ON CONFLICT
     ///
-    fn extract_conditions(
-        index_columns: &[usize],
-        splitted_line: &[&str],
-        columns_from_query: &[String],
-    ) -> (Vec<(String, Value)>, Vec<String>) {
-        let selected_columns = index_columns
+    /// It will return the path of the temporal file to later make the switch,
+    /// with the values of the updated and inserted rows
+    pub fn resolve_upsert_for_file(
+        &mut self,
+        lines: Vec<Vec<String>>,
+        on_conflict: &OnConflict,
+    ) -> Result<(String, Vec<Vec<Value>>), Tperrors> {
+        let temporal_file_path = match self.generate_temporal_file_path() {
+            Ok(path) => path,
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to generate temporal file: {}",
+                    e
+                )));
+            }
+        };
+        let temporal_file = match File::create(&temporal_file_path) {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to create temporal file: {}",
+                    e
+                )));
+            }
+        };
+        match self.resolve_upsert(lines, on_conflict, temporal_file) {
+            Ok(changed_rows) => Ok((temporal_file_path, changed_rows)),
+            Err(e) => {
+                let _ = fs::remove_file(&temporal_file_path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Given a MERGE and the rows of its source, writes the target changed by its
+    /// WHEN clauses on the writer
+    ///
+    /// The target is read only once: every row of it is looked up on the rows of the
+    /// source (on a hash map when the ON condition compares columns with ```=```), and
+    /// the rows of the source that no row matched are inserted at the end.
+    ///
+    /// Returns the number of rows updated, deleted and inserted
+    fn resolve_merge<W: Write>(
+        &mut self,
+        merge: &MergeQuery,
+        source_columns: &[String],
+        source_rows: &[Vec<Value>],
+        file_to_write: W,
+    ) -> Result<usize, Tperrors> {
+        let splitted_columns_from_file = self.get_column_from_file()?;
+        let width = splitted_columns_from_file.len();
+
+        // the conditions and values read the columns of both tables, qualified
+        let target_columns = MergeQuery::qualified(&merge.target, &splitted_columns_from_file);
+        let source_columns = MergeQuery::qualified(&merge.source, source_columns);
+        let columns = target_columns
             .iter()
-            .map(|i| splitted_line[*i])
-            .map(|s| s.to_string())
+            .chain(source_columns.iter())
+            .cloned()
             .collect::<Vec<String>>();
 
-        let mut vec_conditions: Vec<(String, Value)> = Vec::new();
+        let on =
+            JoinCondition::compile(Some(&merge.on), &target_columns, &source_columns, &columns)?;
+        let clauses = merge
+            .clauses
+            .iter()
+            .map(|clause| Self::compile_merge_clause(clause, &splitted_columns_from_file, &columns))
+            .collect::<Result<Vec<CompiledMergeClause>, Tperrors>>()?;
+
+        // the rows of the source by the values of their key
+        let (target_key, source_key): (Vec<usize>, Vec<usize>) = on.keys.iter().copied().unzip();
+        let mut by_key: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+        if !on.keys.is_empty() {
+            for (i, values) in source_rows.iter().enumerate() {
+                if let Some(key) = JoinCondition::hash_key(values, &source_key) {
+                    by_key.entry(key).or_default().push(i);
+                }
+            }
+        }
+        let every_row = (0..source_rows.len()).collect::<Vec<usize>>();
+
+        match self.reader.seek(SeekFrom::Start(0)) {
+            Ok(_) => {}
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to read the file: {}",
+                    e
+                )));
+            }
+        }
 
-        // for each column, we need to map the condition
-        for (j, _col) in selected_columns.iter().enumerate() {
-            // with this we get the column that we want to check
-            let column_condition = columns_from_query[j].as_str();
-            // with this we get the value of the column
-            let trimmed_value = selected_columns[j].trim().to_string();
+        let mut temporal_file = BufWriter::new(file_to_write);
+        let mut matched = vec![false; source_rows.len()];
+        let mut changed = 0;
+        Self::write_temporal_line(splitted_columns_from_file.join(","), &mut temporal_file)?;
 
-            if let Ok(v) = trimmed_value.parse::<i64>() {
-                vec_conditions.push((column_condition.to_string(), Value::Integer(v)));
-            } else {
-                vec_conditions.push((column_condition.to_string(), Value::String(trimmed_value)));
+        for (number, line) in self.reader.by_ref().lines().skip(1).enumerate() {
+            let line = match line {
+                Ok(l) => l,
+                Err(e) => {
+                    return Err(Tperrors::Generic(format!(
+                        "Error while trying to read the file: {}",
+                        e
+                    )));
+                }
+            };
+            let values = Self::values_from_line(&line);
+
+            let candidates = match on.keys.is_empty() {
+                true => &every_row,
+                false => match JoinCondition::hash_key(&values, &target_key)
+                    .and_then(|key| by_key.get(&key))
+                {
+                    Some(candidates) => candidates,
+                    None => &Vec::new(),
+                },
+            };
+            let mut found: Vec<usize> = Vec::new();
+            for i in candidates {
+                if !JoinCondition::same_keys(&values, &target_key, &source_rows[*i], &source_key) {
+                    continue;
+                }
+                let joined = values
+                    .iter()
+                    .chain(source_rows[*i].iter())
+                    .cloned()
+                    .collect::<Vec<Value>>();
+                let row = Row::new(&columns, &joined).with_number(number + 1);
+                if Self::row_matches(&on.residual, &row)? {
+                    found.push(*i);
+                }
+            }
+            for i in &found {
+                matched[*i] = true;
+            }
+
+            // without a row of the source its columns are NULL
+            let (case, joined) = match found.as_slice() {
+                [] => (
+                    MergeCase::NotMatchedBySource,
+                    values
+                        .iter()
+                        .cloned()
+                        .chain(std::iter::repeat_n(Value::Null, source_columns.len()))
+                        .collect::<Vec<Value>>(),
+                ),
+                [i] => (
+                    MergeCase::Matched,
+                    values
+                        .iter()
+                        .chain(source_rows[*i].iter())
+                        .cloned()
+                        .collect::<Vec<Value>>(),
+                ),
+                _ if merge.has_matched_clause() => {
+                    return Err(Tperrors::Generic(format!(
+                        "MERGE can't change the same row twice, the row {} of {} matches {} rows of the source",
+                        number + 1,
+                        merge.target.name,
+                        found.len()
+                    )));
+                }
+                _ => {
+                    Self::write_temporal_line(line, &mut temporal_file)?;
+                    continue;
+                }
+            };
+            let row = Row::new(&columns, &joined).with_number(number + 1);
+
+            match Self::merge_change_for(&clauses, case, &row)? {
+                Some(MergeChange::Delete) => changed += 1,
+                Some(MergeChange::Update(changes)) => {
+                    let new_values = changes
+                        .iter()
+                        .map(|(_, expression)| Self::field_of(expression.evaluate(&row)?))
+                        .collect::<Result<Vec<String>, Tperrors>>()?;
+
+                    let mut new_line = line.split(",").collect::<Vec<&str>>();
+                    for ((position, _), value) in changes.iter().zip(new_values.iter()) {
+                        new_line[*position] = value;
+                    }
+                    changed += 1;
+                    Self::write_temporal_line(new_line.join(","), &mut temporal_file)?;
+                }
+                _ => Self::write_temporal_line(line, &mut temporal_file)?,
+            }
+        }
+
+        // the rows of the source without a row of the target, its columns are NULL
+        for (i, values) in source_rows.iter().enumerate() {
+            if matched[i] {
+                continue;
+            }
+            let joined = std::iter::repeat_n(Value::Null, width)
+                .chain(values.iter().cloned())
+                .collect::<Vec<Value>>();
+            let row = Row::new(&columns, &joined);
+
+            if let Some(MergeChange::Insert(changes)) =
+                Self::merge_change_for(&clauses, MergeCase::NotMatchedByTarget, &row)?
+            {
+                let mut new_line = vec![String::new(); width];
+                for (position, expression) in changes {
+                    new_line[*position] = Self::field_of(expression.evaluate(&row)?)?;
+                }
+                changed += 1;
+                Self::write_temporal_line(new_line.join(","), &mut temporal_file)?;
             }
         }
-        (vec_conditions, selected_columns)
+        match temporal_file.flush() {
+            Ok(_) => {}
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to write the file: {}",
+                    e
+                )));
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Function to resolve a MERGE
+    ///
+    /// It will return the path of the temporal file to later make the switch,
+    /// with the number of rows changed
+    pub fn resolve_merge_for_file(
+        &mut self,
+        merge: &MergeQuery,
+        source_columns: &[String],
+        source_rows: &[Vec<Value>],
+    ) -> Result<(String, usize), Tperrors> {
+        let temporal_file_path = match self.generate_temporal_file_path() {
+            Ok(path) => path,
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to generate temporal file: {}",
+                    e
+                )));
+            }
+        };
+        let temporal_file = match File::create(&temporal_file_path) {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to create temporal file: {}",
+                    e
+                )));
+            }
+        };
+        match self.resolve_merge(merge, source_columns, source_rows, temporal_file) {
+            Ok(changed) => Ok((temporal_file_path, changed)),
+            Err(e) => {
+                let _ = fs::remove_file(&temporal_file_path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Parses a WHEN clause of a MERGE against the columns of both tables
+    ///
+    /// Only the columns of the target can be changed by UPDATE SET or INSERT
+    fn compile_merge_clause(
+        clause: &MergeClause,
+        table_columns: &[String],
+        columns: &[String],
+    ) -> Result<CompiledMergeClause, Tperrors> {
+        let condition = Self::compile_conditions(clause.condition.as_deref(), columns)?;
+        let changes = match &clause.action {
+            MergeAction::Update {
+                columns: names,
+                values,
+            } => Self::compile_assignments(names, values, columns)?,
+            MergeAction::Insert {
+                columns: names,
+                values,
+            } => {
+                // without columns every column of the target is given
+                let names = match names.is_empty() {
+                    true => table_columns.to_vec(),
+                    false => names.to_vec(),
+                };
+                if names.len() != values.len() {
+                    return Err(Tperrors::Syntax(format!(
+                        "Invalid INSERT of the MERGE, {} columns but {} values",
+                        names.len(),
+                        values.len()
+                    )));
+                }
+                Self::compile_assignments(&names, values, columns)?
+            }
+            MergeAction::Delete => Vec::new(),
+            MergeAction::Nothing => Vec::new(),
+        };
+        if changes
+            .iter()
+            .any(|(position, _)| *position >= table_columns.len())
+        {
+            return Err(Tperrors::Column(
+                "Only the columns of the target can be changed by a MERGE".to_string(),
+            ));
+        }
+        let change = match clause.action {
+            MergeAction::Update { .. } => MergeChange::Update(changes),
+            MergeAction::Insert { .. } => MergeChange::Insert(changes),
+            MergeAction::Delete => MergeChange::Delete,
+            MergeAction::Nothing => MergeChange::Nothing,
+        };
+        Ok(CompiledMergeClause {
+            case: clause.case.clone(),
+            condition,
+            change,
+        })
+    }
+
+    /// The change of the first WHEN clause of the case whose condition the row meets,
+    /// None if no clause applies (the row is left as it is)
+    fn merge_change_for<'a>(
+        clauses: &'a [CompiledMergeClause],
+        case: MergeCase,
+        row: &Row,
+    ) -> Result<Option<&'a MergeChange>, Tperrors> {
+        for clause in clauses.iter().filter(|clause| clause.case == case) {
+            if Self::row_matches(&clause.condition, row)? {
+                return Ok(Some(&clause.change));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Writes a line of an upsert or a merge on the temporal file
+    fn write_temporal_line<W: Write>(line: String, file: &mut W) -> Result<(), Tperrors> {
+        match Self::write_a_line(line, file) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Tperrors::Generic(format!(
+                "Error while trying to write the file: {}",
+                e
+            ))),
+        }
+    }
+
+    /// The values of the key of a row, None if any of them is NULL
+    fn key_of(key: &[usize], values: &[Value]) -> Option<Vec<Option<String>>> {
+        key.iter()
+            .map(|position| match values.get(*position) {
+                Some(value) if !value.is_null() => Some(value.hash_key()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Parses the conditions of the query (if they exist) against the columns of the table
+    fn compile_conditions(
+        opt_conditions: Option<&str>,
+        columns: &[String],
+    ) -> Result<Option<Expression>, Tperrors> {
+        match opt_conditions {
+            Some(str_conditions) => Ok(Some(Condition::compile(str_conditions, columns)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses the values of an UPDATE, each one with the position of its column
+    ///
+    /// Like on conditions, a name that isn't a column is a value: ```SET Nombre = Luis```
+    ///
+    /// Returns a Column error if a column isn't on the table, or a value is a misspelled column
+    fn compile_assignments(
+        columns: &[String],
+        values: &[String],
+        table_columns: &[String],
+    ) -> Result<Vec<(usize, Expression)>, Tperrors> {
+        Self::compile_assignments_with(columns, values, table_columns, |_| {})
+    }
+
+    /// Like compile_assignments, but every value is changed by `normalize` after being
+    /// parsed (Example: to write ```excluded.Edad``` as ```EXCLUDED.Edad```)
+    fn compile_assignments_with(
+        columns: &[String],
+        values: &[String],
+        table_columns: &[String],
+        normalize: fn(&mut Expression),
+    ) -> Result<Vec<(usize, Expression)>, Tperrors> {
+        if columns.len() != values.len() {
+            return Err(Tperrors::Syntax(
+                "Invalid UPDATE query (every column needs a value)".to_string(),
+            ));
+        }
+        let mut changes: Vec<(usize, Expression)> = Vec::new();
+
+        for (column, value) in columns.iter().zip(values) {
+            let position = match column_position(table_columns, column) {
+                Some(position) => position,
+                None => {
+                    return Err(Tperrors::Column(format!(
+                        "Invalid column {} inside the query",
+                        column
+                    )));
+                }
+            };
+            if changes.iter().any(|(p, _)| *p == position) {
+                return Err(Tperrors::Syntax(format!(
+                    "Column {} is changed more than once",
+                    column
+                )));
+            }
+
+            let mut expression = ExpressionParser::parse(value)?;
+            normalize(&mut expression);
+            let expression = match expression {
+                Expression::Column(name) if column_position(table_columns, &name).is_none() => {
+                    Self::bare_word_value(name, table_columns)?
+                }
+                expression => expression,
+            };
+            if expression.contains_aggregate() || expression.contains_window() {
+                return Err(Tperrors::Syntax(format!(
+                    "Aggregate and window functions can't be used on the value of {}",
+                    column
+                )));
+            }
+            expression.validate_columns(table_columns)?;
+            changes.push((position, expression));
+        }
+        Ok(changes)
+    }
+
+    /// The value of a bare word on a SET that isn't a column (```SET Nombre = Luis```)
+    ///
+    /// A qualified name (```c.Edad```) or a column written with other case (```edad```)
+    /// is a misspelled column, so it's a Column error instead of being written as text
+    fn bare_word_value(name: String, table_columns: &[String]) -> Result<Expression, Tperrors> {
+        let same_as_a_column = table_columns.iter().any(|column| {
+            let column = column.rsplit('.').next().unwrap_or(column);
+            column.eq_ignore_ascii_case(&name)
+        });
+        if is_qualified_name(&name) || same_as_a_column {
+            return Err(Tperrors::Column(format!(
+                "Invalid column {} inside the query",
+                name
+            )));
+        }
+        Ok(Expression::Literal(Value::String(name)))
+    }
+
+    /// The text of a value on the csv, NULL is an empty field
+    ///
+    /// Fields are not quoted, so a value with a comma or a new line can't be written
+    fn field_of(value: Value) -> Result<String, Tperrors> {
+        let field = value.to_string();
+        if field.contains(',') || field.contains('\n') {
+            return Err(Tperrors::Generic(format!(
+                "Value {} can't be written on the table (it has a comma or a new line)",
+                field
+            )));
+        }
+        Ok(field)
+    }
+
+    /// Checks if a row meets the conditions, a query without conditions matches every row
+    fn row_matches(condition: &Option<Expression>, row: &Row) -> Result<bool, Tperrors> {
+        match condition {
+            Some(expression) => expression.matches(row),
+            None => Ok(true),
+        }
+    }
+
+    /// Helper to convert a line readed from the csv into the values of the row
+    fn values_from_line(line: &str) -> Vec<Value> {
+        line.split(",").map(Value::from_field).collect()
     }
 
     /// Function that handles the insert query
@@ -574,11 +1164,13 @@ impl<R: Read + Seek> Table<R> {
     /// it will write the result on the writer and return Ok() if everything is ok
     ///
     /// If it fails it will throw a error from std::io::Error
+    ///
+    /// Returns the values of the deleted rows
     fn resolve_delete<W: Write>(
         &mut self,
         conditions: Option<&str>,
         file: W,
-    ) -> Result<(), Tperrors> {
+    ) -> Result<Vec<Vec<Value>>, Tperrors> {
         // we need to check if the conditions are met
         // if they are met, we need to delete the line
         // else we need to keep the line
@@ -588,6 +1180,8 @@ impl<R: Read + Seek> Table<R> {
         let splitted_columns_from_file = self.get_column_from_file()?;
 
         let columns_from_csv = splitted_columns_from_file.join(",");
+        let condition = Self::compile_conditions(conditions, &splitted_columns_from_file)?;
+
         match self.reader.seek(SeekFrom::Start(0)) {
             Ok(_) => {}
             Err(e) => {
@@ -599,6 +1193,7 @@ impl<R: Read + Seek> Table<R> {
         }
 
         let mut temporal_file = BufWriter::new(file);
+        let mut deleted_rows: Vec<Vec<Value>> = Vec::new();
 
         match Self::write_a_line(columns_from_csv, &mut temporal_file) {
             Ok(_) => {}
@@ -610,7 +1205,7 @@ impl<R: Read + Seek> Table<R> {
             }
         }
 
-        for line in self.reader.by_ref().lines().skip(1) {
+        for (number, line) in self.reader.by_ref().lines().skip(1).enumerate() {
             let line = match line {
                 Ok(l) => l,
                 Err(e) => {
@@ -620,47 +1215,23 @@ impl<R: Read + Seek> Table<R> {
                     )));
                 }
             };
-            let splitted_line = line.split(",").collect::<Vec<&str>>();
+            let values = Self::values_from_line(&line);
 
-            match conditions {
-                Some(str_conditions) => {
-                    let splitted_columns_as_string = splitted_columns_from_file
-                        .iter()
-                        .map(|s| s.to_string())
-                        .collect::<Vec<String>>();
-
-                    let (extracted_conditions, _line_to_write) = Self::extract_conditions(
-                        &(0..splitted_columns_as_string.len()).collect::<Vec<usize>>(),
-                        &splitted_line,
-                        &splitted_columns_as_string,
-                    );
-                    let condition = Condition::new(extracted_conditions);
-                    match condition.matches_condition(str_conditions) {
-                        Ok(true) => {
-                            // critera matches? we do nothing
-                        }
-                        Ok(false) => {
-                            // criteria reached, we need to change the index
-                            // of the columns according to the hash database with the proper value
-
-                            match Self::write_a_line(line, &mut temporal_file) {
-                                Ok(_) => {}
-                                Err(e) => {
-                                    return Err(Tperrors::Generic(format!(
-                                        "Error while trying to write the file: {}",
-                                        e
-                                    )));
-                                }
-                            }
-                        }
-                        Err(_) => {
-                            return Err(Tperrors::Generic("Error checking conditions".to_string()));
-                        }
+            let row = Row::new(&splitted_columns_from_file, &values).with_number(number + 1);
+
+            // a DELETE without conditions removes every row
+            if Self::row_matches(&condition, &row)? {
+                deleted_rows.push(values);
+            } else {
+                match Self::write_a_line(line, &mut temporal_file) {
+                    Ok(_) => {}
+                    Err(e) => {
+                        return Err(Tperrors::Generic(format!(
+                            "Error while trying to write the file: {}",
+                            e
+                        )));
                     }
                 }
-                None => {
-                    // we do basically nothing
-                }
             }
         }
         match temporal_file.flush() {
@@ -672,18 +1243,19 @@ impl<R: Read + Seek> Table<R> {
                 )));
             }
         }
-        Ok(())
+        Ok(deleted_rows)
     }
 
     /// Function that resolves the delete query
     ///
     /// Given a condition, it will return the path of the temporal file
+    /// with the values of the deleted rows
     ///
     /// If it fails it will throw a error from std::io::Error
     pub fn resolve_delete_for_file(
         &mut self,
         conditions: Option<&str>,
-    ) -> Result<String, Tperrors> {
+    ) -> Result<(String, Vec<Vec<Value>>), Tperrors> {
         let temporal_file_path = match self.generate_temporal_file_path() {
             Ok(path) => path,
             Err(e) => {
@@ -702,8 +1274,89 @@ impl<R: Read + Seek> Table<R> {
                 )));
             }
         };
-        self.resolve_delete(conditions, temporal_file)?;
-        Ok(temporal_file_path)
+        match self.resolve_delete(conditions, temporal_file) {
+            Ok(deleted_rows) => Ok((temporal_file_path, deleted_rows)),
+            Err(e) => {
+                let _ = fs::remove_file(&temporal_file_path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Writes only the header of the table on the writer (without a new line at the
+    /// end, like a new table), the rows are counted but never parsed
+    ///
+    /// Returns the number of removed rows
+    fn resolve_truncate<W: Write>(&mut self, file_to_write: W) -> Result<usize, Tperrors> {
+        let splitted_columns_from_file = self.get_column_from_file()?;
+
+        match self.reader.seek(SeekFrom::Start(0)) {
+            Ok(_) => {}
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to read the file: {}",
+                    e
+                )));
+            }
+        }
+
+        let mut removed_rows = 0;
+        for line in self.reader.by_ref().lines().skip(1) {
+            match line {
+                Ok(l) if l.is_empty() => {}
+                Ok(_) => removed_rows += 1,
+                Err(e) => {
+                    return Err(Tperrors::Generic(format!(
+                        "Error while trying to read the file: {}",
+                        e
+                    )));
+                }
+            }
+        }
+
+        let mut temporal_file = BufWriter::new(file_to_write);
+        match temporal_file
+            .write_all(splitted_columns_from_file.join(",").as_bytes())
+            .and_then(|_| temporal_file.flush())
+        {
+            Ok(_) => Ok(removed_rows),
+            Err(e) => Err(Tperrors::Generic(format!(
+                "Error while trying to write the file: {}",
+                e
+            ))),
+        }
+    }
+
+    /// Function that resolves the truncate query
+    ///
+    /// It will return the path of the temporal file (with only the header)
+    /// with the number of removed rows
+    pub fn resolve_truncate_for_file(&mut self) -> Result<(String, usize), Tperrors> {
+        let temporal_file_path = match self.generate_temporal_file_path() {
+            Ok(path) => path,
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to generate temporal file: {}",
+                    e
+                )));
+            }
+        };
+        let temporal_file = match File::create(&temporal_file_path) {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to create temporal file: {}",
+                    e
+                )));
+            }
+        };
+        match self.resolve_truncate(temporal_file) {
+            Ok(removed_rows) => Ok((temporal_file_path, removed_rows)),
+            Err(e) => {
+                let _ = fs::remove_file(&temporal_file_path);
+                Err(e)
+            }
+        }
     }
 
     /// Mock function that resolves the delete query
@@ -805,10 +1458,7 @@ impl<R: Read + Seek> Table<R> {
         let since_the_epoch = match start.duration_since(UNIX_EPOCH) {
             Ok(time) => time,
             Err(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "Error getting time",
-                ));
+                return Err(std::io::Error::other("Error getting time"));
             }
         };
         Ok(format!(
@@ -819,6 +1469,52 @@ impl<R: Read + Seek> Table<R> {
     }
 }
 
+impl<R: Read + Seek> RowSource for Table<R> {
+    fn columns(&mut self) -> Result<Vec<String>, Tperrors> {
+        self.get_column_from_file()
+    }
+
+    /// Reads the rows of the table, numbered from 1 after the header
+    fn scan(&mut self, on_row: &mut RowCallback) -> Result<(), Tperrors> {
+        match self.reader.seek(SeekFrom::Start(0)) {
+            Ok(_) => {}
+            Err(e) => {
+                return Err(Tperrors::Generic(format!(
+                    "Error while trying to read the file: {}",
+                    e
+                )));
+            }
+        }
+
+        for (number, line) in self.reader.by_ref().lines().skip(1).enumerate() {
+            let line = match line {
+                Ok(l) => l,
+                Err(e) => {
+                    return Err(Tperrors::Generic(format!(
+                        "Error while trying to read the file: {}",
+                        e
+                    )));
+                }
+            };
+            if !on_row(number + 1, Self::values_from_line(&line))? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// The size of the file
+    fn estimated_size(&mut self) -> Result<u64, Tperrors> {
+        match self.reader.seek(SeekFrom::End(0)) {
+            Ok(size) => Ok(size),
+            Err(e) => Err(Tperrors::Generic(format!(
+                "Error while trying to read the file: {}",
+                e
+            ))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -851,7 +1547,7 @@ mod tests {
 
         for invalid_route in invalid_routes {
             let table = Table::<File>::new(invalid_route.to_string());
-            assert_eq!(table.is_err(), true);
+            assert!(table.is_err());
         }
     }
 
@@ -863,7 +1559,7 @@ mod tests {
         let columns = vec!["Edad".to_string(), "Tesis".to_string()];
         let conditions = Some("WHERE name = 'John'");
         let result = table.resolve_select(columns, conditions, None);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -884,7 +1580,7 @@ mod tests {
         // SELECT Nombre, Edad FROM test ORDER BY Trabajo Profesional;
         // so we are trying to sort by a column that does not exist
         let result = table.resolve_select(columns, conditions, sorting);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -894,7 +1590,7 @@ mod tests {
         let columns = vec!["Nombre".to_string(), "Edad".to_string()];
         let result = table.resolve_select(columns, None, None);
         println!("{:?}", result);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
     }
 
     #[test]
@@ -925,7 +1621,7 @@ mod tests {
 
         // execute_Selects do a print, so we need to hook it
 
-        let expected_result = vec![
+        let expected_result = [
             vec!["Edad".to_string(), "Nombre".to_string()],
             vec!["45".to_string(), "Carlos".to_string()],
         ];
@@ -948,7 +1644,7 @@ mod tests {
 
         // execute_Selects do a print, so we need to hook it
 
-        let expected_result = vec![vec!["Nombre".to_string()], vec!["Carlos".to_string()]];
+        let expected_result = [vec!["Nombre".to_string()], vec!["Carlos".to_string()]];
 
         let result = table.resolve_select(columns, conditions, sorting).unwrap();
 
@@ -969,7 +1665,7 @@ mod tests {
 
         // execute_Selects do a print, so we need to hook it
 
-        let expected_result = vec![
+        let expected_result = [
             vec!["Correo electronico".to_string()],
             vec!["csanchez@gmail.com".to_string()],
         ];
@@ -988,7 +1684,7 @@ mod tests {
         let conditions = Some("(Edad >= 32 AND Edad <= 40) AND (Nombre = Juan OR Nombre = Pedro)");
         let result = table.resolve_select(columns, conditions, None).unwrap();
 
-        let expected_result = vec![
+        let expected_result = [
             vec!["Nombre".to_string(), "Profesion".to_string()],
             vec!["Juan".to_string(), "medico".to_string()],
             vec!["Pedro".to_string(), "diseñador".to_string()],
@@ -1009,7 +1705,7 @@ mod tests {
         let conditions = Some("Edad>=41 AND Edad<=43");
         let result = table.resolve_select(columns, conditions, None).unwrap();
 
-        let expected_result = vec![
+        let expected_result = [
             vec!["Nombre".to_string(), "Edad".to_string()],
             vec!["Laura".to_string(), "41".to_string()],
         ];
@@ -1030,7 +1726,7 @@ mod tests {
         let conditions = Some("Edad>=41 AND Edad <= 43");
         let result = table.resolve_select(columns, conditions, None).unwrap();
 
-        let expected_result = vec![
+        let expected_result = [
             vec!["Nombre".to_string(), "Edad".to_string()],
             vec!["Laura".to_string(), "41".to_string()],
         ];
@@ -1056,7 +1752,7 @@ mod tests {
 
         let result = table.resolve_select(column, None, ordering);
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
     }
 
     #[test]
@@ -1072,7 +1768,7 @@ mod tests {
         }]);
 
         let result = table.resolve_select(column, None, ordering);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
     }
 
     #[test]
@@ -1086,7 +1782,7 @@ mod tests {
         let conditions = Some("Edad=45 AND");
         let result = table.resolve_select(column, conditions, None);
 
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -1100,7 +1796,7 @@ mod tests {
         let conditions = Some("Profesion='contador y ingeniero'");
         let result = table.resolve_select(column, conditions, None).unwrap();
 
-        let expected_result = vec![
+        let expected_result = [
             vec!["Nombre".to_string(), "Edad".to_string()],
             vec!["Andrés".to_string(), "34".to_string()],
         ];
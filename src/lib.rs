@@ -2,6 +2,7 @@
 pub mod conditions;
 pub mod consults;
 pub mod errors;
+pub mod expressions;
 pub mod extractors;
 pub mod handler_tables;
 pub mod sorter;
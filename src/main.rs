@@ -1,7 +1,22 @@
-use std::fs::File;
+use std::{
+    fs::File,
+    io::{IsTerminal, Write},
+    rc::Rc,
+};
 
 use tp_individual::{
-    consults::{delete::Delete, insert::Insert, select::Select, update::Update},
+    consults::{
+        create_table::CreateTable,
+        delete::Delete,
+        insert::Insert,
+        merge::Merge,
+        safe_updates::SafeUpdates,
+        select::{Select, SelectRunner},
+        summary::ExecutionSummary,
+        truncate::Truncate,
+        update::Update,
+        view::View,
+    },
     errors::tperrors::Tperrors,
     extractors::{extractor::Extractor, sqlcommand::SQLCommand},
     handler_tables::folder_tables::FolderTables,
@@ -38,8 +53,10 @@ fn run(args: Vec<String>) -> Result<(), Tperrors> {
     // Now, we have the arguments...
     let file = &args[1];
     let consult = &args[2].trim();
+    // the rest of the arguments are flags, Example: --unsafe-updates
+    let safe_updates = SafeUpdates::from_flags(&args[3..], std::io::stdin().is_terminal())?;
 
-    let folder_tables = FolderTables::new(file)?;
+    let mut folder_tables = FolderTables::new(file)?;
 
     let splitted_consult = consult.split(" ").collect::<Vec<&str>>();
     let command = splitted_consult[0];
@@ -47,7 +64,7 @@ fn run(args: Vec<String>) -> Result<(), Tperrors> {
     let extractor = Extractor;
 
     match command {
-        "SELECT" => {
+        "SELECT" | "WITH" => {
             let select = Select;
 
             if !select.is_valid_query(consult) {
@@ -56,7 +73,7 @@ fn run(args: Vec<String>) -> Result<(), Tperrors> {
                 ));
             }
 
-            match resolve_select(&extractor, folder_tables, consult, select) {
+            match resolve_select(folder_tables, consult, select) {
                 Ok(_) => {}
                 Err(e) => {
                     return Err(e);
@@ -68,12 +85,13 @@ fn run(args: Vec<String>) -> Result<(), Tperrors> {
 
             if !insert.is_valid_query(consult) {
                 return Err(Tperrors::Syntax(
-                    "Invalid insert query (Missing either INSERT INTO, VALUES or ;)".to_string(),
+                    "Invalid insert query (Missing either INSERT INTO, VALUES (or SELECT) or ;)"
+                        .to_string(),
                 ));
             }
 
             match resolve_insert(&extractor, folder_tables, consult, insert) {
-                Ok(_) => {}
+                Ok(summary) => print_summary(&summary),
                 Err(e) => {
                     return Err(e);
                 }
@@ -88,8 +106,8 @@ fn run(args: Vec<String>) -> Result<(), Tperrors> {
                 ));
             }
 
-            match resolve_update(&extractor, folder_tables, consult, update) {
-                Ok(_) => {}
+            match resolve_update(&extractor, folder_tables, consult, update, safe_updates) {
+                Ok(summary) => print_summary(&summary),
                 Err(e) => {
                     return Err(e);
                 }
@@ -104,13 +122,95 @@ fn run(args: Vec<String>) -> Result<(), Tperrors> {
                 ));
             }
 
-            match resolve_delete(&extractor, folder_tables, consult, delete) {
-                Ok(_) => {}
+            match resolve_delete(&extractor, folder_tables, consult, delete, safe_updates) {
+                Ok(summary) => print_summary(&summary),
                 Err(e) => {
                     return Err(e);
                 }
             };
         }
+        "TRUNCATE" => {
+            let truncate = Truncate;
+
+            if !truncate.is_valid_query(consult) {
+                return Err(Tperrors::Syntax(
+                    "Invalid truncate query (Missing either TRUNCATE [TABLE], the table or ;)"
+                        .to_string(),
+                ));
+            }
+
+            let mut table = return_proper_table_to_work_with(
+                &extractor,
+                folder_tables,
+                consult,
+                SQLCommand::Truncate,
+            )?;
+            let summary = truncate.execute_truncate(&mut table)?;
+            print_summary(&summary);
+        }
+        "MERGE" => {
+            let merge = Merge;
+
+            if !merge.is_valid_query(consult) {
+                return Err(Tperrors::Syntax(
+                    "Invalid merge query (Missing either MERGE INTO, USING, ON, WHEN or ;)"
+                        .to_string(),
+                ));
+            }
+
+            let summary = merge.execute(&folder_tables, consult)?;
+            print_summary(&summary);
+        }
+        "CREATE" if consult.starts_with("CREATE TABLE") => {
+            let create_table = CreateTable;
+
+            if !create_table.is_valid_query(consult) {
+                return Err(Tperrors::Syntax(
+                    "Invalid create query (Missing either CREATE TABLE, AS or ;)".to_string(),
+                ));
+            }
+
+            let summary = create_table.execute(&mut folder_tables, consult)?;
+            print_summary(&summary);
+        }
+        "CREATE" => {
+            let view = View;
+
+            if !view.is_valid_create_query(consult) {
+                return Err(Tperrors::Syntax(
+                    "Invalid create query (Missing either CREATE [MATERIALIZED] VIEW, AS or ;)"
+                        .to_string(),
+                ));
+            }
+
+            let summary = view.execute_create(&mut folder_tables, consult)?;
+            print_summary(&summary);
+        }
+        "DROP" => {
+            let view = View;
+
+            if !view.is_valid_drop_query(consult) {
+                return Err(Tperrors::Syntax(
+                    "Invalid drop query (Missing either DROP [MATERIALIZED] VIEW or ;)".to_string(),
+                ));
+            }
+
+            let summary = view.execute_drop(&mut folder_tables, consult)?;
+            print_summary(&summary);
+        }
+        "REFRESH" => {
+            let view = View;
+
+            if !view.is_valid_refresh_query(consult) {
+                return Err(Tperrors::Syntax(
+                    "Invalid refresh query (Missing either REFRESH MATERIALIZED VIEW or ;)"
+                        .to_string(),
+                ));
+            }
+
+            let summary = view.execute_refresh(&mut folder_tables, consult)?;
+            print_summary(&summary);
+        }
         _ => {
             return Err(Tperrors::Syntax("Invalid command".to_string()));
         }
@@ -136,6 +236,14 @@ fn return_proper_table_to_work_with(
         }
     };
 
+    // the rows of a materialized view are only written by its query
+    if folder_tables.is_materialized_view(extracted_table_name) {
+        return Err(Tperrors::Table(format!(
+            "{} is a materialized view, it can only be changed by REFRESH MATERIALIZED VIEW",
+            extracted_table_name
+        )));
+    }
+
     let table: Table<File> = match folder_tables.get_path(extracted_table_name) {
         Some(table_path) => match Table::<File>::new(table_path) {
             Ok(table) => table,
@@ -151,48 +259,41 @@ fn return_proper_table_to_work_with(
     Ok(table)
 }
 fn resolve_select(
-    extractor: &Extractor,
     folder_tables: FolderTables,
     consult: &str,
     select: Select,
 ) -> Result<(), Tperrors> {
-    let mut table =
-        return_proper_table_to_work_with(extractor, folder_tables, consult, SQLCommand::Select)?;
-
-    if !select.is_valid_query(consult) {
-        return Err(Tperrors::Syntax(
-            "Invalid select query (Missing either SELECT , FROM or ;)".to_string(),
-        ));
-    }
-
-    // lets get the columns selected from the query
-    let columns = match extractor.extract_columns_for_select(consult) {
-        Ok(columns) => columns,
-        Err(e) => {
-            return Err(e);
+    // a materialized view read by the query may be older than the tables it reads
+    for name in folder_tables.sources_of(consult) {
+        if !folder_tables.is_materialized_view(&name) {
+            continue;
         }
-    };
-
-    // Conditions of the query (if they exists)
-    let conditions = extractor.extract_as_str_conditions(consult);
-
-    if let Some(c) = conditions {
-        if c.is_empty() {
-            return Err(Tperrors::Syntax("incomplete input".to_string()));
+        let stale = folder_tables.stale_sources(&name)?;
+        if !stale.is_empty() {
+            eprintln!(
+                "WARNING: materialized view {} is older than {}, run REFRESH MATERIALIZED VIEW {};",
+                name,
+                stale.join(", "),
+                name
+            );
         }
     }
 
-    // Sorting method (if existst)
-    let sorting_vector = match extractor.extract_orderby_as_str(consult) {
-        Some(sorting) => {
-            let vec_sort = extractor.parser_orderby_from_str_to_vec(sorting);
-            Some(vec_sort)
-        }
-        None => None,
-    };
+    // subqueries (and derived tables) read the tables of the same folder
+    let runner = SelectRunner::new(Rc::new(folder_tables));
 
     // lets execute the query
-    select.execute_select(&mut table, columns, conditions, sorting_vector)
+    select.execute_with_runner(consult, &runner)
+}
+
+/// Prints the rows of the RETURNING clause (if any) and the command tag of the query
+///
+/// Example: ```UPDATE 3```
+fn print_summary(summary: &ExecutionSummary) {
+    if let Some((header, rows)) = &summary.returning {
+        Select.print_rows(header, rows);
+    }
+    println!("{}", summary.tag());
 }
 
 fn resolve_insert(
@@ -200,22 +301,60 @@ fn resolve_insert(
     folder_tables: FolderTables,
     consult: &str,
     insert: Insert,
-) -> Result<(), Tperrors> {
-    let mut table =
-        return_proper_table_to_work_with(extractor, folder_tables, consult, SQLCommand::Insert)?;
+) -> Result<ExecutionSummary, Tperrors> {
+    // the SELECT that gives the rows reads the tables of the same folder
+    let runner = SelectRunner::new(Rc::new(folder_tables.clone()));
     if !insert.is_valid_query(consult) {
         return Err(Tperrors::Syntax(
-            "Invalid insert query (Missing either INSERT INTO, VALUES or ;)".to_string(),
+            "Invalid insert query (Missing either INSERT INTO, VALUES (or SELECT) or ;)"
+                .to_string(),
         ));
     }
 
-    let (columns, values) = match extractor.extract_columns_and_values_for_insert(consult) {
+    // the rows changed are printed like a SELECT if there is a RETURNING clause,
+    // it's removed before looking for the table
+    let (consult, returning) = extractor.extract_returning(consult)?;
+    let mut table =
+        return_proper_table_to_work_with(extractor, folder_tables, &consult, SQLCommand::Insert)?;
+    // with ON CONFLICT, the rows whose key is already on the table are skipped or update it
+    let (consult, on_conflict) = extractor.extract_on_conflict(&consult)?;
+
+    if let Some((columns, query)) = extractor.extract_insert_select(&consult)? {
+        return insert.execute_insert_select(
+            &mut table,
+            columns,
+            &query,
+            &runner,
+            on_conflict.as_ref(),
+            returning.as_ref(),
+        );
+    }
+
+    let (columns, values) = match extractor.extract_columns_and_values_for_insert(&consult) {
         Ok((columns, values)) => (columns, values),
         Err(e) => {
             return Err(e);
         }
     };
-    insert.execute_insert(&mut table, columns, values)
+    insert.execute_insert(
+        &mut table,
+        columns,
+        values,
+        on_conflict.as_ref(),
+        returning.as_ref(),
+    )
+}
+
+/// Asks the user on the terminal, true if the answer is yes
+fn confirm_on_terminal(question: &str) -> bool {
+    eprint!("{}", question);
+    let _ = std::io::stderr().flush();
+
+    let mut answer = String::new();
+    match std::io::stdin().read_line(&mut answer) {
+        Ok(_) => matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+        Err(_) => false,
+    }
 }
 
 fn resolve_update(
@@ -223,25 +362,34 @@ fn resolve_update(
     folder_tables: FolderTables,
     consult: &str,
     update: Update,
-) -> Result<(), Tperrors> {
-    let mut table =
-        return_proper_table_to_work_with(extractor, folder_tables, consult, SQLCommand::Update)?;
+    safe_updates: SafeUpdates,
+) -> Result<ExecutionSummary, Tperrors> {
     if !update.is_valid_query(consult) {
         return Err(Tperrors::Syntax(
             "Invalid update query (Missing either UPDATE, SET, WHERE or ;)".to_string(),
         ));
     }
 
-    let (columns, values) = match extractor.extract_columns_and_values_for_update(consult) {
+    let (consult, returning) = extractor.extract_returning(consult)?;
+    let mut table =
+        return_proper_table_to_work_with(extractor, folder_tables, &consult, SQLCommand::Update)?;
+
+    let (columns, values) = match extractor.extract_columns_and_values_for_update(&consult) {
         Ok((columns, values)) => (columns, values),
         Err(e) => {
             return Err(e);
         }
     };
 
-    let conditions = extractor.extract_as_str_conditions(consult);
+    let conditions = extractor.extract_as_str_conditions(&consult);
+    safe_updates.check(
+        SQLCommand::Update,
+        extractor.extract_table(&consult, SQLCommand::Update)?,
+        conditions,
+        confirm_on_terminal,
+    )?;
 
-    update.execute_update(&mut table, columns, values, conditions)
+    update.execute_update(&mut table, columns, values, conditions, returning.as_ref())
 }
 
 fn resolve_delete(
@@ -249,26 +397,34 @@ fn resolve_delete(
     folder_tables: FolderTables,
     consult: &str,
     delete: Delete,
-) -> Result<(), Tperrors> {
-    let mut table =
-        return_proper_table_to_work_with(extractor, folder_tables, consult, SQLCommand::Delete)?;
-
+    safe_updates: SafeUpdates,
+) -> Result<ExecutionSummary, Tperrors> {
     if !delete.is_valid_query(consult) {
         return Err(Tperrors::Syntax(
             "Invalid delete query (Missing either DELETE, FROM, WHERE or ;)".to_string(),
         ));
     }
 
-    let conditions = extractor.extract_as_str_conditions(consult);
-
-    delete.execute_delete(&mut table, conditions)
+    // without WHERE the table ends on the ;, so RETURNING is removed first
+    let (consult, returning) = extractor.extract_returning(consult)?;
+    let mut table =
+        return_proper_table_to_work_with(extractor, folder_tables, &consult, SQLCommand::Delete)?;
+    let conditions = extractor.extract_as_str_conditions(&consult);
+    safe_updates.check(
+        SQLCommand::Delete,
+        extractor.extract_table(&consult, SQLCommand::Delete)?,
+        conditions,
+        confirm_on_terminal,
+    )?;
+
+    delete.execute_delete(&mut table, conditions, returning.as_ref())
 }
 
 #[test]
 fn run_with_invalid_number_of_args() {
     let args = vec!["".to_string()];
     let result = run(args);
-    assert_eq!(result.is_err(), true);
+    assert!(result.is_err());
 }
 
 #[test]
@@ -280,5 +436,29 @@ fn run_invalid_table_throws_error() {
     ];
     let result = run(args);
 
-    assert_eq!(result.is_err(), true);
+    assert!(result.is_err());
+}
+
+#[test]
+fn run_returning_without_where() {
+    let folder = std::env::temp_dir().join("tp_individual_run_returning_without_where");
+    let _ = std::fs::remove_dir_all(&folder);
+    std::fs::create_dir_all(&folder).unwrap();
+    std::fs::copy("./tables/ordenes.csv", folder.join("ordenes.csv")).unwrap();
+    let run_on_folder = |query: &str| {
+        run(vec![
+            "".to_string(),
+            folder.to_str().unwrap().to_string(),
+            query.to_string(),
+            "--unsafe-updates".to_string(),
+        ])
+    };
+
+    assert!(run_on_folder("UPDATE ordenes SET cantidad = 0 RETURNING id;").is_ok());
+    assert!(run_on_folder("DELETE FROM ordenes RETURNING id;").is_ok());
+    assert_eq!(
+        std::fs::read_to_string(folder.join("ordenes.csv")).unwrap(),
+        "id,id_cliente,producto,cantidad\n"
+    );
+    assert!(run_on_folder("INSERT INTO ordenes VALUES (200, 1, Mesa, 1) RETURNING id;").is_ok());
 }
@@ -1,4 +1,6 @@
-use std::fmt::Debug;
+use std::{cmp::Ordering, fmt::Debug};
+
+use crate::conditions::value::Value;
 
 /// SortMethod is a struct that holds the information about how to sort the data.
 ///
@@ -36,6 +38,24 @@ impl SortMethod {
     pub fn get_by_column(&self) -> &String {
         &self.by_column
     }
+
+    /// Compares two values following this sort method
+    ///
+    /// Numbers are compared as numbers and NULL goes first when ascending.
+    pub fn compare(&self, a: &Value, b: &Value) -> Ordering {
+        let ordering = match (a.is_null(), b.is_null()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => a.compare(b).unwrap_or(Ordering::Equal),
+        };
+
+        if self.is_ascending() {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
 }
 
 impl PartialEq for SortMethod {
@@ -65,6 +85,17 @@ mod tests {
         };
 
         assert_eq!(sort_method.get_by_column(), "test_column");
-        assert_eq!(sort_method.is_ascending(), true);
+        assert!(sort_method.is_ascending());
+    }
+
+    #[test]
+    fn test_sort_method_compares_numbers() {
+        let sort_method = SortMethod {
+            by_column: "Edad".to_string(),
+            ascending: false,
+        };
+
+        let ordering = sort_method.compare(&Value::Integer(100), &Value::Integer(20));
+        assert_eq!(ordering, Ordering::Less);
     }
 }
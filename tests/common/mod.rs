@@ -1,3 +1,10 @@
+use std::{fs, path::PathBuf, rc::Rc};
+
+use tp_individual::{
+    conditions::value::Value, consults::select::SelectRunner, errors::tperrors::Tperrors,
+    expressions::subquery::SubqueryRunner, handler_tables::folder_tables::FolderTables,
+};
+
 const CSV_DATA: &str = "Id,Nombre,Apellido,Edad,Correo electronico,Profesion\n\
 1,Juan,Perez,32,jperez@gmail.com,medico\n\
 2,Maria,Gomez,28,mgomez@gmail.com,abogado\n\
@@ -11,6 +18,23 @@ const CSV_DATA: &str = "Id,Nombre,Apellido,Edad,Correo electronico,Profesion\n\
 10,Paula,Hernández,31,phernandez@gmail.com,publicista\n\
 ";
 
+const ORDERS_CSV_DATA: &str = "id,id_cliente,producto,cantidad\n\
+101,1,Laptop,1\n\
+103,1,Monitor,1\n\
+102,2,Teléfono,2\n\
+104,3,Teclado,1\n\
+105,4,Mouse,2\n\
+106,5,Impresora,1\n\
+107,6,Altavoces,1\n\
+108,4,Auriculares,1\n\
+109,5,Laptop,1\n\
+110,6,Teléfono,2\n\
+";
+
+pub fn orders_csv_data_as_bytes() -> &'static [u8] {
+    ORDERS_CSV_DATA.as_bytes()
+}
+
 pub fn csv_data_as_bytes() -> &'static [u8] {
     CSV_DATA.as_bytes()
 }
@@ -21,3 +45,36 @@ pub fn return_iterator_from_mock_csv_file() -> impl Iterator<Item = String> {
         .split(|byte| *byte == b'\n')
         .map(|line| String::from_utf8(line.to_vec()).unwrap())
 }
+
+/// Copies the tables of ./tables into a new folder, so rows can be changed on it
+pub fn copy_of_tables(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("tp_individual_{}", name));
+    let _ = fs::remove_dir_all(&folder);
+    fs::create_dir_all(&folder).unwrap();
+    for table in ["clientes.csv", "ordenes.csv"] {
+        fs::copy(format!("./tables/{}", table), folder.join(table)).unwrap();
+    }
+    folder
+}
+
+/// Runs a SELECT against the tables of a folder, returning its rows
+pub fn run_query(folder: &str, query: &str) -> Result<Vec<Vec<Value>>, Tperrors> {
+    let (_, rows) = run_query_with_header(folder, query)?;
+    Ok(rows)
+}
+
+/// Same as run_query, returning the header of the result too
+pub fn run_query_with_header(
+    folder: &str,
+    query: &str,
+) -> Result<(Vec<String>, Vec<Vec<Value>>), Tperrors> {
+    let runner = SelectRunner::new(Rc::new(FolderTables::new(folder)?));
+    runner.run(query, None)
+}
+
+/// The values of the rows as they are printed
+pub fn as_text(rows: &[Vec<Value>]) -> Vec<Vec<String>> {
+    rows.iter()
+        .map(|row| row.iter().map(|value| value.to_string()).collect())
+        .collect()
+}
@@ -71,7 +71,7 @@ fn integration_delete_expanded_query_with_column_as_condition() -> Result<(), Tp
 
     match delete.execute_delete_mock(&mut table, condition) {
         Ok(mocked_file) => {
-            let expected_output_vectors = vec![
+            let expected_output_vectors = [
                 "Id,Nombre,Apellido,Edad,Correo electronico,Profesion", // ofc we are gonna have the header.
                 "1,Juan,Perez,32,jperez@gmail.com,medico",
                 "2,Maria,Gomez,28,mgomez@gmail.com,abogado",
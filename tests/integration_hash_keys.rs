@@ -0,0 +1,67 @@
+use tp_individual::errors::tperrors::Tperrors;
+
+pub mod common;
+
+/// Folder whose codes ```007```, ```7``` and ```7.0``` are two texts and a number
+const CODIGOS: &str = "./tests/data/codigos";
+
+#[test]
+fn integration_in_list_finds_the_same_rows_as_equal() -> Result<(), Tperrors> {
+    // '007' is equal to the number 7, but not to the text 7.0
+    let equal = common::run_query(CODIGOS, "SELECT id FROM codigos WHERE cp = '007';")?;
+    assert_eq!(common::as_text(&equal), vec![vec!["1"], vec!["2"]]);
+
+    let rows = common::run_query(CODIGOS, "SELECT id FROM codigos WHERE cp IN ('007');")?;
+    assert_eq!(rows, equal);
+    let rows = common::run_query(CODIGOS, "SELECT id FROM codigos WHERE cp IN ('007', 'x');")?;
+    assert_eq!(rows, equal);
+    let rows = common::run_query(
+        CODIGOS,
+        "SELECT id FROM codigos WHERE cp IN (SELECT cp FROM codigos WHERE id = 1);",
+    )?;
+    assert_eq!(rows, equal);
+    Ok(())
+}
+
+#[test]
+fn integration_texts_that_look_like_numbers_are_not_merged() -> Result<(), Tperrors> {
+    let rows = common::run_query(CODIGOS, "SELECT DISTINCT cp FROM codigos;")?;
+    assert_eq!(
+        common::as_text(&rows),
+        vec![vec!["007"], vec!["7"], vec!["7.0"]]
+    );
+
+    let rows = common::run_query(
+        CODIGOS,
+        "SELECT cp, COUNT(*) FROM codigos GROUP BY cp ORDER BY cp;",
+    )?;
+    assert_eq!(
+        common::as_text(&rows),
+        vec![vec!["007", "1"], vec!["7", "1"], vec!["7.0", "1"]]
+    );
+
+    let rows = common::run_query(
+        CODIGOS,
+        "SELECT cp FROM codigos UNION SELECT cp FROM codigos;",
+    )?;
+    assert_eq!(rows.len(), 3);
+
+    // each text is only joined with itself and with the number
+    let rows = common::run_query(
+        CODIGOS,
+        "SELECT a.id, b.id FROM codigos a JOIN codigos b ON a.cp = b.cp ORDER BY a.id, b.id;",
+    )?;
+    assert_eq!(
+        common::as_text(&rows),
+        vec![
+            vec!["1", "1"],
+            vec!["1", "2"],
+            vec!["2", "1"],
+            vec!["2", "2"],
+            vec!["2", "3"],
+            vec!["3", "2"],
+            vec!["3", "3"],
+        ]
+    );
+    Ok(())
+}
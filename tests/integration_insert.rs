@@ -85,7 +85,7 @@ fn integration_insert_query_with_limited_columns_and_multiple_values() -> Result
 
     match insert.execute_insert_mock(&mut table, columns_to_insert, values_to_insert) {
         Ok(vec_lines) => {
-            let expected_output = vec![",Juan,,20,,", ",Pedro,,30,,", ",Maria,,40,,"];
+            let expected_output = [",Juan,,20,,", ",Pedro,,30,,", ",Maria,,40,,"];
 
             assert_eq!(vec_lines.len(), 3); // vector of lines should be 3 because we are adding 3 values
 
@@ -134,7 +134,7 @@ fn integration_insert_query_with_all_columns_and_multiple_values() -> Result<(),
 
     match insert.execute_insert_mock(&mut table, columns_to_insert, values_to_insert) {
         Ok(vec_lines) => {
-            let vec_expected_string_output = vec![
+            let vec_expected_string_output = [
                 "99,Juan,Carolo,22,test@gmail.com,maestro",
                 "100,Pedro,Perez,30,test@gmail.com,electronico",
             ];
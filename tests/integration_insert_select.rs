@@ -0,0 +1,134 @@
+use std::{fs, rc::Rc};
+
+use tp_individual::{
+    conditions::value::Value,
+    consults::{
+        create_table::CreateTable, insert::Insert, select::SelectRunner, summary::ExecutionSummary,
+    },
+    errors::tperrors::Tperrors,
+    extractors::{extractor::Extractor, sqlcommand::SQLCommand},
+    handler_tables::folder_tables::FolderTables,
+};
+
+pub mod common;
+
+/// Runs an INSERT INTO ... SELECT query like the main program does
+fn run_insert(folder: &str, query: &str) -> Result<ExecutionSummary, Tperrors> {
+    let folder_tables = FolderTables::new(folder)?;
+    let runner = SelectRunner::new(Rc::new(folder_tables.clone()));
+    let (columns, select) = match Extractor.extract_insert_select(query)? {
+        Some(insert_select) => insert_select,
+        None => {
+            return Err(Tperrors::Syntax(
+                "Expected an INSERT ... SELECT".to_string(),
+            ))
+        }
+    };
+    let name = Extractor.extract_table(query, SQLCommand::Insert)?;
+    let mut table = folder_tables.open_table(name)?;
+    Insert.execute_insert_select(&mut table, columns, &select, &runner, None, None)
+}
+
+#[test]
+fn integration_create_table_as_select() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("create_table_as_select");
+    let path = folder.to_str().unwrap();
+    let mut folder_tables = FolderTables::new(path)?;
+
+    let summary = CreateTable.execute(
+        &mut folder_tables,
+        "CREATE TABLE viejos AS SELECT Id, Nombre, Edad AS anios FROM clientes WHERE Edad > 40;",
+    )?;
+    assert_eq!(summary.tag(), "SELECT 2");
+
+    let content = fs::read_to_string(folder.join("viejos.csv")).unwrap();
+    assert_eq!(content, "Id,Nombre,anios\n3,Carlos,45\n6,Laura,41");
+
+    let rows = common::run_query(path, "SELECT Nombre FROM viejos WHERE anios > 42;")?;
+    assert_eq!(rows, vec![vec![Value::String("Carlos".to_string())]]);
+    Ok(())
+}
+
+#[test]
+fn integration_create_table_with_a_used_name_or_repeated_columns_fails() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("create_table_fails");
+    let path = folder.to_str().unwrap();
+    let mut folder_tables = FolderTables::new(path)?;
+
+    let result = CreateTable.execute(
+        &mut folder_tables,
+        "CREATE TABLE clientes AS SELECT * FROM ordenes;",
+    );
+    assert!(result.is_err());
+
+    let result = CreateTable.execute(
+        &mut folder_tables,
+        "CREATE TABLE repetidas AS SELECT Id, Id FROM clientes;",
+    );
+    assert!(result.is_err());
+    assert!(!folder.join("repetidas.csv").exists());
+    Ok(())
+}
+
+#[test]
+fn integration_insert_select_fills_the_given_columns() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("insert_select_given_columns");
+    let path = folder.to_str().unwrap();
+
+    let summary = run_insert(
+        path,
+        "INSERT INTO clientes (Nombre, Id) SELECT producto, id FROM ordenes WHERE cantidad > 1 AND id_cliente = 2;",
+    )?;
+    assert_eq!(summary.rows, 1);
+
+    let rows = common::run_query(
+        path,
+        "SELECT Id, Nombre, Edad FROM clientes WHERE Id > 100;",
+    )?;
+    let expected = vec![vec![
+        Value::Integer(102),
+        Value::String("Teléfono".to_string()),
+        Value::Null,
+    ]];
+    assert_eq!(rows, expected);
+
+    let summary = run_insert(path, "INSERT INTO ordenes SELECT id + 100, id_cliente, producto, cantidad FROM ordenes WHERE id_cliente = 1;")?;
+    assert_eq!(summary.tag(), "INSERT 2");
+    let rows = common::run_query(path, "SELECT COUNT(*) FROM ordenes WHERE id > 200;")?;
+    assert_eq!(rows, vec![vec![Value::Integer(2)]]);
+    Ok(())
+}
+
+#[test]
+fn integration_insert_select_with_invalid_rows_fails() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("insert_select_invalid_rows");
+    let path = folder.to_str().unwrap();
+    let before = fs::read_to_string(folder.join("clientes.csv")).unwrap();
+
+    // more values than columns
+    let result = run_insert(
+        path,
+        "INSERT INTO clientes (Id) SELECT id, producto FROM ordenes;",
+    );
+    assert!(result.is_err());
+
+    // a text on a column of numbers
+    let result = run_insert(
+        path,
+        "INSERT INTO clientes (Edad) SELECT producto FROM ordenes;",
+    );
+    assert!(result.is_err());
+
+    // a column that isn't on the table
+    let result = run_insert(
+        path,
+        "INSERT INTO clientes (Inventada) SELECT id FROM ordenes;",
+    );
+    assert!(result.is_err());
+
+    assert_eq!(
+        fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+        before
+    );
+    Ok(())
+}
@@ -0,0 +1,268 @@
+use std::io::Cursor;
+
+use tp_individual::{
+    consults::select::Select,
+    errors::tperrors::Tperrors,
+    handler_tables::{
+        join::{JoinKind, JoinSource},
+        row_source::QualifiedSource,
+        select_options::SelectOptions,
+        select_query::SelectQuery,
+        table::Table,
+    },
+    sorter::sort::SortMethod,
+};
+
+pub mod common;
+
+/// ```ordenes o``` or ```clientes c```
+fn mocked_table(data: &'static [u8], alias: &str) -> Box<QualifiedSource> {
+    let table = Table::<Cursor<&[u8]>>::mock(format!("query_join_{}", alias), data);
+    Box::new(QualifiedSource::new(Box::new(table), alias))
+}
+
+/// ```FROM ordenes o JOIN clientes c ON condition```
+fn orders_join_clients(condition: &str) -> JoinSource {
+    JoinSource::new(
+        mocked_table(common::orders_csv_data_as_bytes(), "o"),
+        mocked_table(common::csv_data_as_bytes(), "c"),
+        JoinKind::Inner,
+        Some(condition),
+    )
+}
+
+fn select_all(columns: &[&str], sorting: Option<Vec<SortMethod>>) -> SelectQuery {
+    SelectQuery {
+        columns: columns.iter().map(|c| c.to_string()).collect(),
+        conditions: None,
+        sorting,
+        options: SelectOptions::default(),
+    }
+}
+
+fn sorted_by(columns: &[&str]) -> Option<Vec<SortMethod>> {
+    Some(
+        columns
+            .iter()
+            .map(|column| SortMethod {
+                by_column: column.to_string(),
+                ascending: true,
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn integration_inner_join_with_aliases() -> Result<(), Tperrors> {
+    // SELECT c.Nombre, o.producto, cantidad FROM ordenes o JOIN clientes c ON o.id_cliente = c.Id
+    // WHERE c.Edad > 30 ORDER BY c.Nombre, o.id;
+
+    let select = Select;
+    let mut source = orders_join_clients("o.id_cliente = c.Id");
+
+    let query = SelectQuery {
+        columns: vec![
+            "c.Nombre".to_string(),
+            "o.producto".to_string(),
+            "cantidad".to_string(),
+        ],
+        conditions: Some("c.Edad > 30".to_string()),
+        sorting: sorted_by(&["c.Nombre", "o.id"]),
+        options: SelectOptions::default(),
+    };
+
+    let vector_of_lines = select.execute_query_mock(&mut source, query)?;
+
+    let expected_output = vec![
+        vec!["Nombre", "producto", "cantidad"],
+        vec!["Ana", "Mouse", "2"],
+        vec!["Ana", "Auriculares", "1"],
+        vec!["Carlos", "Teclado", "1"],
+        vec!["Juan", "Laptop", "1"],
+        vec!["Juan", "Monitor", "1"],
+        vec!["Laura", "Altavoces", "1"],
+        vec!["Laura", "Teléfono", "2"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_inner_join_with_extra_condition_and_groups() -> Result<(), Tperrors> {
+    // SELECT Nombre, SUM(cantidad) AS total FROM ordenes o JOIN clientes c
+    // ON c.Id = o.id_cliente AND o.producto != 'Laptop' GROUP BY Nombre ORDER BY Nombre;
+
+    let select = Select;
+    let mut source = orders_join_clients("c.Id = o.id_cliente AND o.producto != 'Laptop'");
+
+    let query = SelectQuery {
+        columns: vec!["Nombre".to_string(), "SUM(cantidad) AS total".to_string()],
+        conditions: None,
+        sorting: sorted_by(&["Nombre"]),
+        options: SelectOptions {
+            group_by: vec!["Nombre".to_string()],
+            ..SelectOptions::default()
+        },
+    };
+
+    let vector_of_lines = select.execute_query_mock(&mut source, query)?;
+
+    let expected_output = vec![
+        vec!["Nombre", "total"],
+        vec!["Ana", "3"],
+        vec!["Carlos", "1"],
+        vec!["Juan", "1"],
+        vec!["Laura", "3"],
+        vec!["Luis", "1"],
+        vec!["Maria", "2"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_inner_join_ambiguous_column_fails() {
+    // SELECT * FROM ordenes o JOIN clientes c ON o.id_cliente = c.Id;  (id and Id are different)
+    // SELECT producto FROM ordenes o JOIN ordenes x ON o.id = x.id;  (producto is on both tables)
+
+    let select = Select;
+    let mut source = orders_join_clients("o.id_cliente = c.Id");
+    let query = SelectQuery {
+        columns: vec!["*".to_string()],
+        conditions: None,
+        sorting: None,
+        options: SelectOptions::default(),
+    };
+    assert!(select.execute_query_mock(&mut source, query).is_ok());
+
+    let orders = Table::<Cursor<&[u8]>>::mock(
+        "query_join_ordenes".to_string(),
+        common::orders_csv_data_as_bytes(),
+    );
+    let other_orders = Table::<Cursor<&[u8]>>::mock(
+        "query_join_ordenes".to_string(),
+        common::orders_csv_data_as_bytes(),
+    );
+    let mut source = JoinSource::new(
+        Box::new(QualifiedSource::new(Box::new(orders), "o")),
+        Box::new(QualifiedSource::new(Box::new(other_orders), "x")),
+        JoinKind::Inner,
+        Some("o.id = x.id"),
+    );
+    let query = SelectQuery {
+        columns: vec!["producto".to_string()],
+        conditions: None,
+        sorting: None,
+        options: SelectOptions::default(),
+    };
+    let result = select.execute_query_mock(&mut source, query);
+    assert!(matches!(result, Err(Tperrors::Column(_))));
+}
+
+#[test]
+fn integration_inner_join_without_equality_uses_nested_loop() -> Result<(), Tperrors> {
+    // SELECT c.Nombre, o.id FROM ordenes o JOIN clientes c ON o.cantidad > c.Id ORDER BY o.id;
+
+    let select = Select;
+    let mut source = orders_join_clients("o.cantidad > c.Id");
+
+    let query = select_all(&["c.Nombre", "o.id"], sorted_by(&["o.id"]));
+    let vector_of_lines = select.execute_query_mock(&mut source, query)?;
+
+    let expected_output = vec![
+        vec!["Nombre", "id"],
+        vec!["Juan", "102"],
+        vec!["Juan", "105"],
+        vec!["Juan", "110"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_left_join_clients_without_orders() -> Result<(), Tperrors> {
+    // SELECT c.Nombre FROM clientes c LEFT JOIN ordenes o ON o.id_cliente = c.Id
+    // WHERE o.id IS NULL ORDER BY c.Id;
+
+    let select = Select;
+    let mut source = JoinSource::new(
+        mocked_table(common::csv_data_as_bytes(), "c"),
+        mocked_table(common::orders_csv_data_as_bytes(), "o"),
+        JoinKind::Left,
+        Some("o.id_cliente = c.Id"),
+    );
+
+    let mut query = select_all(&["c.Nombre"], sorted_by(&["c.Id"]));
+    query.conditions = Some("o.id IS NULL".to_string());
+    let vector_of_lines = select.execute_query_mock(&mut source, query)?;
+
+    let expected_output = vec![
+        vec!["Nombre"],
+        vec!["Pedro"],
+        vec!["Lucía"],
+        vec!["Diego"],
+        vec!["Paula"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_right_join_fills_with_null() -> Result<(), Tperrors> {
+    // SELECT c.Nombre, o.id FROM ordenes o RIGHT JOIN clientes c
+    // ON o.id_cliente = c.Id AND o.cantidad > 1 ORDER BY c.Id LIMIT 5;
+
+    let select = Select;
+    let mut source = JoinSource::new(
+        mocked_table(common::orders_csv_data_as_bytes(), "o"),
+        mocked_table(common::csv_data_as_bytes(), "c"),
+        JoinKind::Right,
+        Some("o.id_cliente = c.Id AND o.cantidad > 1"),
+    );
+
+    let mut query = select_all(&["c.Nombre", "o.id"], sorted_by(&["c.Id"]));
+    query.options.limit = Some(5);
+    let vector_of_lines = select.execute_query_mock(&mut source, query)?;
+
+    let expected_output = vec![
+        vec!["Nombre", "id"],
+        vec!["Juan", ""],
+        vec!["Maria", "102"],
+        vec!["Carlos", ""],
+        vec!["Ana", "105"],
+        vec!["Luis", ""],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_full_and_cross_join() -> Result<(), Tperrors> {
+    // SELECT COUNT(*), COUNT(c.Id), COUNT(o.id) FROM clientes c FULL OUTER JOIN ordenes o
+    // ON o.id_cliente = c.Id AND c.Edad > 40;
+
+    let select = Select;
+    let mut source = JoinSource::new(
+        mocked_table(common::csv_data_as_bytes(), "c"),
+        mocked_table(common::orders_csv_data_as_bytes(), "o"),
+        JoinKind::Full,
+        Some("o.id_cliente = c.Id AND c.Edad > 40"),
+    );
+
+    let query = select_all(&["COUNT(*)", "COUNT(c.Id)", "COUNT(o.id)"], None);
+    let vector_of_lines = select.execute_query_mock(&mut source, query)?;
+    // Carlos and Laura have 3 orders, the other 8 clients and 7 orders have no match
+    assert_eq!(vector_of_lines[1], vec!["18", "11", "10"]);
+
+    // SELECT COUNT(*) FROM clientes c CROSS JOIN ordenes o;
+    let mut source = JoinSource::new(
+        mocked_table(common::csv_data_as_bytes(), "c"),
+        mocked_table(common::orders_csv_data_as_bytes(), "o"),
+        JoinKind::Cross,
+        None,
+    );
+    let query = select_all(&["COUNT(*)"], None);
+    let vector_of_lines = select.execute_query_mock(&mut source, query)?;
+    assert_eq!(vector_of_lines[1], vec!["100"]);
+    Ok(())
+}
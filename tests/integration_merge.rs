@@ -0,0 +1,135 @@
+use std::{fs, path::PathBuf};
+
+use tp_individual::{
+    conditions::value::Value, consults::merge::Merge, errors::tperrors::Tperrors,
+    handler_tables::folder_tables::FolderTables,
+};
+
+pub mod common;
+
+/// A copy of ./tables with the table nuevos, the source of the MERGE queries
+fn copy_with_new_clients(name: &str) -> PathBuf {
+    let folder = common::copy_of_tables(name);
+    fs::write(
+        folder.join("nuevos.csv"),
+        "Id,Nombre,Edad
+2,Maria,30
+3,Carlos,40
+12,Sofia,22",
+    )
+    .unwrap();
+    folder
+}
+
+fn merge(folder: &str, query: &str) -> Result<String, Tperrors> {
+    let folder_tables = FolderTables::new(folder)?;
+    Ok(Merge.execute(&folder_tables, query)?.tag())
+}
+
+fn ages(rows: &[(i64, i64)]) -> Vec<Vec<Value>> {
+    rows.iter()
+        .map(|(id, age)| vec![Value::Integer(*id), Value::Integer(*age)])
+        .collect()
+}
+
+#[test]
+fn integration_merge_updates_inserts_and_deletes_in_one_query() -> Result<(), Tperrors> {
+    let folder = copy_with_new_clients("merge_every_clause");
+    let path = folder.to_str().unwrap();
+
+    let tag = merge(
+        path,
+        "MERGE INTO clientes c USING nuevos n ON c.Id = n.Id \
+         WHEN MATCHED AND n.Edad > c.Edad THEN UPDATE SET Edad = n.Edad \
+         WHEN NOT MATCHED THEN INSERT (Id, Nombre, Edad) VALUES (n.Id, n.Nombre, n.Edad) \
+         WHEN NOT MATCHED BY SOURCE AND c.Id > 4 THEN DELETE;",
+    )?;
+    // Maria is updated, Sofia inserted and the clients from 5 to 11 deleted
+    assert_eq!(tag, "MERGE 9");
+
+    let rows = common::run_query(path, "SELECT Id, Edad FROM clientes;")?;
+    assert_eq!(rows, ages(&[(1, 32), (2, 30), (3, 45), (4, 36), (12, 22)]));
+
+    let rows = common::run_query(path, "SELECT Nombre, Apellido FROM clientes WHERE Id = 12;")?;
+    assert_eq!(
+        rows,
+        vec![vec![Value::String("Sofia".to_string()), Value::Null]]
+    );
+    Ok(())
+}
+
+#[test]
+fn integration_merge_with_a_derived_table_as_source() -> Result<(), Tperrors> {
+    let folder = copy_with_new_clients("merge_derived_table");
+    let path = folder.to_str().unwrap();
+
+    let tag = merge(
+        path,
+        "MERGE INTO clientes c USING (SELECT id_cliente, COUNT(*) AS total FROM ordenes GROUP BY id_cliente) AS t \
+         ON c.Id = t.id_cliente WHEN MATCHED THEN UPDATE SET Edad = t.total;",
+    )?;
+    assert_eq!(tag, "MERGE 6");
+
+    let rows = common::run_query(path, "SELECT Id, Edad FROM clientes WHERE Id IN (1, 4, 7);")?;
+    assert_eq!(rows, ages(&[(1, 2), (4, 2), (7, 33)]));
+    Ok(())
+}
+
+#[test]
+fn integration_merge_fails_when_a_row_matches_twice() -> Result<(), Tperrors> {
+    let folder = copy_with_new_clients("merge_matches_twice");
+    let path = folder.to_str().unwrap();
+    let before = fs::read_to_string(folder.join("clientes.csv")).unwrap();
+
+    // the client 1 has two orders
+    let result = merge(
+        path,
+        "MERGE INTO clientes c USING ordenes o ON c.Id = o.id_cliente WHEN MATCHED THEN UPDATE SET Edad = o.cantidad;",
+    );
+    assert!(matches!(result, Err(Tperrors::Generic(_))));
+
+    let after = fs::read_to_string(folder.join("clientes.csv")).unwrap();
+    assert_eq!(before, after);
+    Ok(())
+}
+
+#[test]
+fn integration_merge_can_only_change_columns_of_the_target() -> Result<(), Tperrors> {
+    let folder = copy_with_new_clients("merge_source_column");
+    let path = folder.to_str().unwrap();
+
+    let result = merge(
+        path,
+        "MERGE INTO clientes c USING nuevos n ON c.Id = n.Id WHEN MATCHED THEN UPDATE SET n.Edad = 1;",
+    );
+    assert!(matches!(result, Err(Tperrors::Column(_))));
+
+    let result = merge(
+        path,
+        "MERGE INTO clientes c USING nuevos n ON c.Id = n.Id WHEN MATCHED THEN INSERT VALUES (n.Id);",
+    );
+    assert!(matches!(result, Err(Tperrors::Syntax(_))));
+    Ok(())
+}
+
+#[test]
+fn integration_merge_with_a_misspelled_on_column_fails() -> Result<(), Tperrors> {
+    let folder = copy_with_new_clients("merge_misspelled_on");
+    let path = folder.to_str().unwrap();
+    let before = fs::read_to_string(folder.join("clientes.csv")).unwrap();
+
+    // n.ID isn't a column, it must not be compared as the text 'n.ID' (matching nothing)
+    let result = merge(
+        path,
+        "MERGE INTO clientes c USING nuevos n ON c.Id = n.ID WHEN MATCHED THEN UPDATE SET Edad = n.Edad WHEN NOT MATCHED BY SOURCE THEN DELETE;",
+    );
+    assert!(
+        matches!(&result, Err(Tperrors::Column(message)) if message.contains("Invalid column n.ID"))
+    );
+
+    let after = fs::read_to_string(folder.join("clientes.csv")).unwrap();
+    assert_eq!(before, after);
+    let files = fs::read_dir(&folder).unwrap().count();
+    assert_eq!(files, 3);
+    Ok(())
+}
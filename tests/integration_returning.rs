@@ -0,0 +1,120 @@
+use std::fs;
+
+use tp_individual::{
+    conditions::value::Value,
+    consults::{delete::Delete, insert::Insert, update::Update},
+    errors::tperrors::Tperrors,
+    extractors::extractor::Extractor,
+    handler_tables::{folder_tables::FolderTables, returning::Returning},
+};
+
+pub mod common;
+
+fn returning_of(query: &str) -> Returning {
+    match Extractor.extract_returning(query) {
+        Ok((_, Some(returning))) => returning,
+        _ => panic!("Expected a RETURNING clause on {}", query),
+    }
+}
+
+#[test]
+fn integration_update_returning_new_values() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("update_returning");
+    let folder_tables = FolderTables::new(folder.to_str().unwrap())?;
+    let mut table = folder_tables.open_table("clientes")?;
+
+    let returning = returning_of("UPDATE clientes SET Edad = Edad + 1 RETURNING Id, Edad;");
+    let summary = Update.execute_update(
+        &mut table,
+        vec!["Edad".to_string()],
+        vec!["Edad + 1".to_string()],
+        Some("Id <= 2"),
+        Some(&returning),
+    )?;
+    assert_eq!(summary.tag(), "UPDATE 2");
+    let (header, rows) = summary.returning.unwrap();
+
+    assert_eq!(header, vec!["Id", "Edad"]);
+    assert_eq!(
+        rows,
+        vec![
+            vec![Value::Integer(1), Value::Integer(33)],
+            vec![Value::Integer(2), Value::Integer(29)],
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn integration_delete_returning_removed_values() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("delete_returning");
+    let folder_tables = FolderTables::new(folder.to_str().unwrap())?;
+    let mut table = folder_tables.open_table("ordenes")?;
+
+    let returning = returning_of("DELETE FROM ordenes RETURNING id, producto || '!' AS borrado;");
+    let summary =
+        Delete.execute_delete(&mut table, Some("producto = 'Laptop'"), Some(&returning))?;
+    assert_eq!(summary.tag(), "DELETE 2");
+    let (header, rows) = summary.returning.unwrap();
+
+    assert_eq!(header, vec!["id", "borrado"]);
+    assert_eq!(
+        rows,
+        vec![
+            vec![Value::Integer(101), Value::String("Laptop!".to_string())],
+            vec![Value::Integer(109), Value::String("Laptop!".to_string())],
+        ]
+    );
+    let content = fs::read_to_string(folder.join("ordenes.csv")).unwrap();
+    assert!(!content.contains("Laptop"));
+    Ok(())
+}
+
+#[test]
+fn integration_insert_returning_inserted_values() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("insert_returning");
+    let folder_tables = FolderTables::new(folder.to_str().unwrap())?;
+    let mut table = folder_tables.open_table("ordenes")?;
+
+    let returning = returning_of("INSERT INTO ordenes VALUES (1) RETURNING *;");
+    let summary = Insert.execute_insert(
+        &mut table,
+        vec!["id".to_string(), "producto".to_string()],
+        vec![vec!["200".to_string(), "Silla".to_string()]],
+        None,
+        Some(&returning),
+    )?;
+    assert_eq!(summary.tag(), "INSERT 1");
+    let (header, rows) = summary.returning.unwrap();
+
+    assert_eq!(header, vec!["id", "id_cliente", "producto", "cantidad"]);
+    assert_eq!(
+        rows,
+        vec![vec![
+            Value::Integer(200),
+            Value::Null,
+            Value::String("Silla".to_string()),
+            Value::Null,
+        ]]
+    );
+    Ok(())
+}
+
+#[test]
+fn integration_returning_with_invalid_column_fails() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("returning_invalid_column");
+    let folder_tables = FolderTables::new(folder.to_str().unwrap())?;
+    let mut table = folder_tables.open_table("clientes")?;
+
+    let returning = returning_of("DELETE FROM clientes RETURNING Inventada;");
+    let before = fs::read_to_string(folder.join("clientes.csv")).unwrap();
+    let result = Delete.execute_delete(&mut table, Some("Id = 1"), Some(&returning));
+
+    // the clause is checked before any row is deleted
+    assert!(result.is_err());
+    assert_eq!(
+        fs::read_to_string(folder.join("clientes.csv")).unwrap(),
+        before
+    );
+    Ok(())
+}
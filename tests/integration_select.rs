@@ -1,7 +1,9 @@
 use std::{io::Cursor, vec};
 
 use tp_individual::{
-    consults::select::Select, errors::tperrors::Tperrors, handler_tables::table::Table,
+    consults::select::Select,
+    errors::tperrors::Tperrors,
+    handler_tables::{select_options::SelectOptions, table::Table},
     sorter::sort::SortMethod,
 };
 
@@ -13,7 +15,7 @@ fn integration_simple_select_query() -> Result<(), Tperrors> {
     let select = Select;
     let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
 
-    let columns: Vec<String> = Vec::from(vec!["Nombre".to_string(), "Edad".to_string()]);
+    let columns: Vec<String> = vec!["Nombre".to_string(), "Edad".to_string()];
 
     let condition = Some("Edad >=33");
     let sort_method = None;
@@ -54,7 +56,7 @@ fn integration_advanced_select_query() -> Result<(), Tperrors> {
     let select = Select;
     let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
 
-    let columns: Vec<String> = Vec::from(vec!["Nombre".to_string(), "Edad".to_string()]);
+    let columns: Vec<String> = vec!["Nombre".to_string(), "Edad".to_string()];
 
     let condition = Some("(Nombre = Luis OR Edad>15) AND NOT Nombre = Paula");
     let sort_method = None;
@@ -99,7 +101,7 @@ fn integration_advanced_test_constant() -> Result<(), Tperrors> {
     let select = Select;
     let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
 
-    let columns: Vec<String> = Vec::from(vec!["*".to_string()]);
+    let columns: Vec<String> = vec!["*".to_string()];
 
     let condition = Some("1=1");
     let sort_method = None;
@@ -133,7 +135,7 @@ fn integration_advanced_select_query_with_columns_from_table_as_conditions() ->
     let select = Select;
     let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
 
-    let columns: Vec<String> = Vec::from(vec!["*".to_string()]);
+    let columns: Vec<String> = vec!["*".to_string()];
 
     let condition = Some("Id>Edad");
     let sort_method = None;
@@ -150,7 +152,7 @@ fn integration_advanced_select_query_with_columns_from_table_as_conditions() ->
                 "Profesion",
             ];
 
-            for (_i, row) in vector_of_lines.iter().enumerate() {
+            for row in vector_of_lines.iter() {
                 let expected_row = &expected_header_as_vec;
 
                 for (j, cell) in row.iter().enumerate() {
@@ -177,7 +179,7 @@ fn integration_select_advanced_query_with_nested_conditions_and_spaced_condition
     let select = Select;
     let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
 
-    let columns: Vec<String> = Vec::from(vec!["*".to_string()]);
+    let columns: Vec<String> = vec!["*".to_string()];
 
     // Check out i'm mixingg attached conditions with spaced conditions and it still works.
 
@@ -225,7 +227,7 @@ fn integration_select_advanced_query_sorting_with_columns_not_present_on_the_que
     let select = Select;
     let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
 
-    let columns: Vec<String> = Vec::from(vec!["Nombre".to_string(), "Apellido".to_string()]);
+    let columns: Vec<String> = vec!["Nombre".to_string(), "Apellido".to_string()];
     let condition = Some("Id>8");
     let sort_method = Some(vec![SortMethod {
         by_column: "Edad".to_string(),
@@ -234,7 +236,7 @@ fn integration_select_advanced_query_sorting_with_columns_not_present_on_the_que
 
     match select.execute_select_mock(&mut table, columns, condition, sort_method) {
         Ok(vector_of_lines) => {
-            let expected_output = vec![
+            let expected_output = [
                 vec!["Nombre", "Apellido"],
                 vec!["Paula", "Hernández"],
                 vec!["Diego", "Navarro"],
@@ -254,3 +256,497 @@ fn integration_select_advanced_query_sorting_with_columns_not_present_on_the_que
     }
     Ok(())
 }
+
+#[test]
+fn integration_select_computed_columns_with_aliases_sorted_by_alias() -> Result<(), Tperrors> {
+    // SELECT Nombre || ' ' || Apellido AS completo, Edad * 12 AS meses FROM clientes WHERE Id>7 ORDER BY meses DESC;
+
+    let file_name = String::from("query_select_computed_columns_with_aliases");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec![
+        "Nombre || ' ' || Apellido AS completo".to_string(),
+        "Edad * 12 AS meses".to_string(),
+    ];
+    let condition = Some("Id>7");
+    let sort_method = Some(vec![SortMethod {
+        by_column: "meses".to_string(),
+        ascending: false,
+    }]);
+
+    let vector_of_lines =
+        select.execute_select_mock(&mut table, columns, condition, sort_method)?;
+
+    let expected_output = vec![
+        vec!["completo", "meses"],
+        vec!["Diego Navarro", "468"],
+        vec!["Paula Hernández", "372"],
+        vec!["Lucía Ramos", "312"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_with_like_and_ilike_conditions() -> Result<(), Tperrors> {
+    // SELECT Nombre FROM clientes WHERE Apellido ILIKE '%ez' AND Nombre NOT LIKE 'L%';
+
+    let file_name = String::from("query_select_with_like_and_ilike_conditions");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["Nombre".to_string()];
+    let condition = Some("Apellido ILIKE '%EZ' AND Nombre NOT LIKE 'L%'");
+
+    let vector_of_lines = select.execute_select_mock(&mut table, columns, condition, None)?;
+
+    let expected_output = vec![
+        vec!["Nombre"],
+        vec!["Juan"],
+        vec!["Maria"],
+        vec!["Carlos"],
+        vec!["Pedro"],
+        vec!["Paula"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_with_in_list_and_between() -> Result<(), Tperrors> {
+    // SELECT Nombre, Edad FROM clientes WHERE Id IN (1, 3, 5, 9) AND Edad NOT BETWEEN 30 AND 40;
+
+    let file_name = String::from("query_select_with_in_list_and_between");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["Nombre".to_string(), "Edad".to_string()];
+    let condition = Some("Id IN (1, 3, 5, 9) AND Edad NOT BETWEEN 30 AND 40");
+
+    let vector_of_lines = select.execute_select_mock(&mut table, columns, condition, None)?;
+
+    let expected_output = vec![
+        vec!["Nombre", "Edad"],
+        vec!["Carlos", "45"],
+        vec!["Luis", "29"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_with_string_functions() -> Result<(), Tperrors> {
+    // SELECT UPPER(Apellido) AS apellido, LENGTH(Nombre) AS largo FROM clientes
+    // WHERE SUBSTR(Apellido, 1, 1) = 'M' OR INSTR(Apellido, 'á') > 0;
+
+    let file_name = String::from("query_select_with_string_functions");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec![
+        "UPPER(Apellido) AS apellido".to_string(),
+        "LENGTH(Nombre) AS largo".to_string(),
+    ];
+    let condition = Some("SUBSTR(Apellido, 1, 1) = 'M' OR INSTR(Apellido, 'á') > 0");
+
+    let vector_of_lines = select.execute_select_mock(&mut table, columns, condition, None)?;
+
+    let expected_output = vec![
+        vec!["apellido", "largo"],
+        vec!["SÁNCHEZ", "6"],
+        vec!["MARTÍNEZ", "4"],
+        vec!["FERNÁNDEZ", "5"],
+        vec!["HERNÁNDEZ", "5"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_with_a_huge_pad_fails() {
+    // SELECT LPAD(Nombre, 99999999999, 'x') FROM clientes;
+
+    let file_name = String::from("query_select_with_a_huge_pad");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["LPAD(Nombre, 99999999999, 'x')".to_string()];
+
+    let result = select.execute_select_mock(&mut table, columns, None, None);
+    assert!(matches!(result, Err(Tperrors::Syntax(_))));
+}
+
+#[test]
+fn integration_select_with_case_coalesce_and_cast() -> Result<(), Tperrors> {
+    // SELECT Nombre, CASE WHEN Edad >= 40 THEN 'senior' WHEN Edad >= 30 THEN 'adulto'
+    // ELSE 'joven' END AS grupo, CAST(Edad AS REAL) / 2 AS mitad FROM clientes WHERE Id <= 3;
+
+    let file_name = String::from("query_select_with_case_coalesce_and_cast");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec![
+        "Nombre".to_string(),
+        "CASE WHEN Edad >= 40 THEN 'senior' WHEN Edad >= 30 THEN 'adulto' ELSE 'joven' END AS grupo"
+            .to_string(),
+        "CAST(Edad AS REAL) / 2 AS mitad".to_string(),
+        "COALESCE(NULLIF(Profesion, 'medico'), 'doctor') AS profesion".to_string(),
+    ];
+    let condition = Some("Id <= 3");
+
+    let vector_of_lines = select.execute_select_mock(&mut table, columns, condition, None)?;
+
+    let expected_output = vec![
+        vec!["Nombre", "grupo", "mitad", "profesion"],
+        vec!["Juan", "adulto", "16", "doctor"],
+        vec!["Maria", "joven", "14", "abogado"],
+        vec!["Carlos", "senior", "22.5", "ingeniero"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_with_invalid_cast_names_row_and_column() {
+    // SELECT CAST(Nombre AS INTEGER) FROM clientes;
+
+    let file_name = String::from("query_select_with_invalid_cast");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["CAST(Nombre AS INTEGER)".to_string()];
+
+    match select.execute_select_mock(&mut table, columns, None, None) {
+        Err(Tperrors::Generic(message)) => {
+            assert_eq!(message, "Can't cast Juan to INTEGER (row 1, column Nombre)")
+        }
+        other => panic!("Expected a cast error, found {:?}", other),
+    }
+}
+
+#[test]
+fn integration_select_with_aggregate_functions() -> Result<(), Tperrors> {
+    // SELECT COUNT(*), COUNT(DISTINCT Profesion), SUM(Edad), AVG(Edad), MIN(Apellido), MAX(Edad) - MIN(Edad)
+    // FROM clientes WHERE Edad > 30;
+
+    let file_name = String::from("query_select_with_aggregate_functions");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec![
+        "COUNT(*) AS total".to_string(),
+        "COUNT(DISTINCT Profesion) AS profesiones".to_string(),
+        "SUM(Edad) AS suma".to_string(),
+        "AVG(Edad) AS promedio".to_string(),
+        "MIN(Apellido) AS primero".to_string(),
+        "MAX(Edad) - MIN(Edad) AS rango".to_string(),
+    ];
+    let condition = Some("Edad > 30");
+
+    let vector_of_lines = select.execute_select_mock(&mut table, columns, condition, None)?;
+
+    let expected_output = vec![
+        vec![
+            "total",
+            "profesiones",
+            "suma",
+            "promedio",
+            "primero",
+            "rango",
+        ],
+        vec!["7", "7", "257", "36.714285714285715", "Domínguez", "14"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_with_aggregate_functions_without_rows() -> Result<(), Tperrors> {
+    // SELECT COUNT(Edad), SUM(Edad) FROM clientes WHERE Edad > 100;
+
+    let file_name = String::from("query_select_with_aggregate_functions_without_rows");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["COUNT(Edad)".to_string(), "SUM(Edad)".to_string()];
+    let condition = Some("Edad > 100");
+
+    let vector_of_lines = select.execute_select_mock(&mut table, columns, condition, None)?;
+
+    let expected_output = vec![vec!["COUNT(Edad)", "SUM(Edad)"], vec!["0", ""]];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_mixing_columns_and_aggregate_functions_fails() {
+    // SELECT Nombre, COUNT(*) FROM clientes;
+
+    let file_name = String::from("query_select_mixing_columns_and_aggregate_functions");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["Nombre".to_string(), "COUNT(*)".to_string()];
+
+    let result = select.execute_select_mock(&mut table, columns, None, None);
+    assert!(matches!(result, Err(Tperrors::Column(_))));
+}
+
+#[test]
+fn integration_select_with_group_by_and_having() -> Result<(), Tperrors> {
+    // SELECT id_cliente, SUM(cantidad) AS total, COUNT(*) AS ordenes FROM ordenes
+    // GROUP BY id_cliente HAVING SUM(cantidad) > 1 ORDER BY total DESC, id_cliente;
+
+    let file_name = String::from("query_select_with_group_by_and_having");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::orders_csv_data_as_bytes());
+
+    let columns: Vec<String> = vec![
+        "id_cliente".to_string(),
+        "SUM(cantidad) AS total".to_string(),
+        "COUNT(*) AS ordenes".to_string(),
+    ];
+    let sort_method = Some(vec![
+        SortMethod {
+            by_column: "total".to_string(),
+            ascending: false,
+        },
+        SortMethod {
+            by_column: "id_cliente".to_string(),
+            ascending: true,
+        },
+    ]);
+    let options = SelectOptions {
+        group_by: vec!["id_cliente".to_string()],
+        having: Some("SUM(cantidad) > 1".to_string()),
+        ..SelectOptions::default()
+    };
+
+    let vector_of_lines =
+        select.execute_select_mock_with_options(&mut table, columns, None, sort_method, options)?;
+
+    let expected_output = vec![
+        vec!["id_cliente", "total", "ordenes"],
+        vec!["4", "3", "2"],
+        vec!["6", "3", "2"],
+        vec!["1", "2", "2"],
+        vec!["2", "2", "1"],
+        vec!["5", "2", "2"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_group_by_expression() -> Result<(), Tperrors> {
+    // SELECT Edad / 10 * 10 AS decada, COUNT(*) FROM clientes GROUP BY Edad / 10 * 10 ORDER BY decada;
+
+    let file_name = String::from("query_select_group_by_expression");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec![
+        "Edad / 10 * 10 AS decada".to_string(),
+        "COUNT(*) AS cantidad".to_string(),
+    ];
+    let sort_method = Some(vec![SortMethod {
+        by_column: "decada".to_string(),
+        ascending: true,
+    }]);
+    let options = SelectOptions {
+        group_by: vec!["Edad / 10 * 10".to_string()],
+        ..SelectOptions::default()
+    };
+
+    let vector_of_lines =
+        select.execute_select_mock_with_options(&mut table, columns, None, sort_method, options)?;
+
+    let expected_output = vec![
+        vec!["decada", "cantidad"],
+        vec!["20", "3"],
+        vec!["30", "5"],
+        vec!["40", "2"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_column_not_in_group_by_fails() {
+    // SELECT producto, SUM(cantidad) FROM ordenes GROUP BY id_cliente;
+
+    let file_name = String::from("query_select_column_not_in_group_by");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::orders_csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["producto".to_string(), "SUM(cantidad)".to_string()];
+    let options = SelectOptions {
+        group_by: vec!["id_cliente".to_string()],
+        ..SelectOptions::default()
+    };
+
+    let result = select.execute_select_mock_with_options(&mut table, columns, None, None, options);
+    assert!(matches!(result, Err(Tperrors::Column(_))));
+}
+
+#[test]
+fn integration_select_distinct_sorted() -> Result<(), Tperrors> {
+    // SELECT DISTINCT producto FROM ordenes ORDER BY producto;
+
+    let file_name = String::from("query_select_distinct_sorted");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::orders_csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["producto".to_string()];
+    let sort_method = Some(vec![SortMethod {
+        by_column: "producto".to_string(),
+        ascending: true,
+    }]);
+    let options = SelectOptions {
+        distinct: true,
+        ..SelectOptions::default()
+    };
+
+    let vector_of_lines =
+        select.execute_select_mock_with_options(&mut table, columns, None, sort_method, options)?;
+
+    let expected_output = vec![
+        vec!["producto"],
+        vec!["Altavoces"],
+        vec!["Auriculares"],
+        vec!["Impresora"],
+        vec!["Laptop"],
+        vec!["Monitor"],
+        vec!["Mouse"],
+        vec!["Teclado"],
+        vec!["Teléfono"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_distinct_sorted_by_column_not_selected_fails() {
+    // SELECT DISTINCT producto FROM ordenes ORDER BY cantidad;
+
+    let file_name = String::from("query_select_distinct_sorted_by_column_not_selected");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::orders_csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["producto".to_string()];
+    let sort_method = Some(vec![SortMethod {
+        by_column: "cantidad".to_string(),
+        ascending: true,
+    }]);
+    let options = SelectOptions {
+        distinct: true,
+        ..SelectOptions::default()
+    };
+
+    let result =
+        select.execute_select_mock_with_options(&mut table, columns, None, sort_method, options);
+    assert!(matches!(result, Err(Tperrors::Column(_))));
+}
+
+#[test]
+fn integration_select_limit_and_offset() -> Result<(), Tperrors> {
+    // SELECT Nombre FROM clientes WHERE Edad > 30 LIMIT 2 OFFSET 1;
+
+    let file_name = String::from("query_select_limit_and_offset");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["Nombre".to_string()];
+    let options = SelectOptions {
+        limit: Some(2),
+        offset: 1,
+        ..SelectOptions::default()
+    };
+
+    let vector_of_lines = select.execute_select_mock_with_options(
+        &mut table,
+        columns,
+        Some("Edad > 30"),
+        None,
+        options,
+    )?;
+
+    let expected_output = vec![vec!["Nombre"], vec!["Carlos"], vec!["Ana"]];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_sorted_with_limit() -> Result<(), Tperrors> {
+    // SELECT Nombre, Edad FROM clientes ORDER BY Edad DESC OFFSET 1 ROWS FETCH FIRST 3 ROWS ONLY;
+
+    let file_name = String::from("query_select_sorted_with_limit");
+    let select = Select;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["Nombre".to_string(), "Edad".to_string()];
+    let sort_method = Some(vec![SortMethod {
+        by_column: "Edad".to_string(),
+        ascending: false,
+    }]);
+    let options = SelectOptions {
+        limit: Some(3),
+        offset: 1,
+        ..SelectOptions::default()
+    };
+
+    let vector_of_lines =
+        select.execute_select_mock_with_options(&mut table, columns, None, sort_method, options)?;
+
+    let expected_output = vec![
+        vec!["Nombre", "Edad"],
+        vec!["Laura", "41"],
+        vec!["Diego", "39"],
+        vec!["Ana", "36"],
+    ];
+    assert_eq!(vector_of_lines, expected_output);
+    Ok(())
+}
+
+#[test]
+fn integration_select_printed_as_a_valid_csv() -> Result<(), Tperrors> {
+    // SELECT SUBSTR(Apellido, 2, 3), LPAD(Nombre, 8, 'ñ'), REPLACE(Profesion, 'e', ', ')
+    // FROM clientes WHERE Id = 1;
+
+    let (header, rows) = common::run_query_with_header(
+        "./tables",
+        "SELECT SUBSTR(Apellido, 2, 3), LPAD(Nombre, 8, 'ñ'), REPLACE(Profesion, 'e', ', ') FROM clientes WHERE Id = 1;",
+    )?;
+    let header_line = Select::csv_line(&header);
+    let row_line = Select::csv_line(&common::as_text(&rows)[0]);
+
+    assert_eq!(fields_of(&header_line).len(), 3);
+    assert_eq!(fields_of(&header_line), header);
+    assert_eq!(fields_of(&row_line), vec!["ere", "ññññJuan", "m, dico"]);
+
+    // the quotes of a field are doubled
+    let line = Select::csv_line(&["dijo \"hola\"".to_string(), "Juan".to_string()]);
+    assert_eq!(line, "\"dijo \"\"hola\"\"\",Juan");
+    assert_eq!(fields_of(&line), vec!["dijo \"hola\"", "Juan"]);
+    Ok(())
+}
+
+/// Splits a line of a CSV, the commas between double quotes are part of the field
+fn fields_of(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
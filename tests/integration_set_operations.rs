@@ -0,0 +1,67 @@
+use tp_individual::{conditions::value::Value, errors::tperrors::Tperrors};
+
+pub mod common;
+
+fn first_column(rows: &[Vec<Value>]) -> Vec<String> {
+    rows.iter().map(|row| row[0].to_string()).collect()
+}
+
+#[test]
+fn integration_union_removes_repeated_rows() -> Result<(), Tperrors> {
+    let (header, rows) = common::run_query_with_header(
+        "./tables",
+        "SELECT Id FROM clientes WHERE Id < 3 UNION SELECT id_cliente FROM ordenes WHERE id_cliente < 4;",
+    )?;
+
+    assert_eq!(header, vec!["Id"]);
+    assert_eq!(first_column(&rows), vec!["1", "2", "3"]);
+    Ok(())
+}
+
+#[test]
+fn integration_union_all_keeps_every_row() -> Result<(), Tperrors> {
+    let (_, rows) = common::run_query_with_header(
+        "./tables",
+        "SELECT Id FROM clientes WHERE Id < 3 UNION ALL SELECT id_cliente FROM ordenes WHERE id_cliente < 3;",
+    )?;
+
+    assert_eq!(first_column(&rows), vec!["1", "2", "1", "1", "2"]);
+    Ok(())
+}
+
+#[test]
+fn integration_intersect_and_except() -> Result<(), Tperrors> {
+    let (_, rows) = common::run_query_with_header(
+        "./tables",
+        "SELECT Id FROM clientes INTERSECT SELECT id_cliente FROM ordenes;",
+    )?;
+    assert_eq!(first_column(&rows), vec!["1", "2", "3", "4", "5", "6"]);
+
+    let (_, rows) = common::run_query_with_header(
+        "./tables",
+        "SELECT Id FROM clientes EXCEPT SELECT id_cliente FROM ordenes;",
+    )?;
+    assert_eq!(first_column(&rows), vec!["7", "8", "9", "10", "11"]);
+    Ok(())
+}
+
+#[test]
+fn integration_order_by_and_limit_apply_to_the_combined_rows() -> Result<(), Tperrors> {
+    let (_, rows) = common::run_query_with_header(
+        "./tables",
+        "SELECT Nombre FROM clientes WHERE Edad > 40 UNION SELECT Nombre FROM clientes WHERE Id < 3 ORDER BY Nombre DESC LIMIT 3;",
+    )?;
+
+    assert_eq!(first_column(&rows), vec!["Maria", "Laura", "Juan"]);
+    Ok(())
+}
+
+#[test]
+fn integration_set_operation_with_different_number_of_columns_fails() {
+    let result = common::run_query_with_header(
+        "./tables",
+        "SELECT Id, Nombre FROM clientes UNION SELECT id_cliente FROM ordenes;",
+    );
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,162 @@
+use tp_individual::{
+    conditions::value::Value,
+    errors::tperrors::Tperrors,
+    handler_tables::{
+        row_source::ResultSource, select_options::SelectOptions, select_query::SelectQuery,
+    },
+};
+
+pub mod common;
+
+fn ids_of(rows: &[Vec<Value>]) -> Vec<String> {
+    rows.iter().map(|row| row[0].to_string()).collect()
+}
+
+#[test]
+fn integration_select_in_subquery() -> Result<(), Tperrors> {
+    let rows = common::run_query(
+        "./tables",
+        "SELECT Id, Nombre FROM clientes WHERE Id IN (SELECT id_cliente FROM ordenes WHERE cantidad > 1);",
+    )?;
+
+    assert_eq!(ids_of(&rows), vec!["2", "4", "6"]);
+    Ok(())
+}
+
+#[test]
+fn integration_select_not_in_subquery() -> Result<(), Tperrors> {
+    let rows = common::run_query(
+        "./tables",
+        "SELECT Id FROM clientes WHERE Id NOT IN (SELECT id_cliente FROM ordenes);",
+    )?;
+
+    assert_eq!(ids_of(&rows), vec!["7", "8", "9", "10", "11"]);
+    Ok(())
+}
+
+#[test]
+fn integration_select_correlated_exists() -> Result<(), Tperrors> {
+    let rows = common::run_query(
+        "./tables",
+        "SELECT Id FROM clientes c WHERE EXISTS (SELECT * FROM ordenes o WHERE o.id_cliente = c.Id AND o.producto = 'Laptop');",
+    )?;
+    assert_eq!(ids_of(&rows), vec!["1", "5"]);
+
+    let rows = common::run_query(
+        "./tables",
+        "SELECT Id FROM clientes c WHERE NOT EXISTS (SELECT * FROM ordenes o WHERE o.id_cliente = c.Id);",
+    )?;
+    assert_eq!(ids_of(&rows), vec!["7", "8", "9", "10", "11"]);
+    Ok(())
+}
+
+#[test]
+fn integration_select_in_subquery_with_many_columns_fails() {
+    let result = common::run_query(
+        "./tables",
+        "SELECT Id FROM clientes WHERE Id IN (SELECT id_cliente, producto FROM ordenes);",
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn integration_select_subquery_with_invalid_column_fails() {
+    let result = common::run_query(
+        "./tables",
+        "SELECT Id FROM clientes WHERE Id IN (SELECT Inventado FROM ordenes);",
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn integration_select_from_derived_table() -> Result<(), Tperrors> {
+    let rows = common::run_query(
+        "./tables",
+        "SELECT t.producto, t.total FROM (SELECT producto, SUM(cantidad) AS total FROM ordenes GROUP BY producto) AS t WHERE t.total > 1 ORDER BY t.producto;",
+    )?;
+
+    let expected = vec![
+        vec![Value::String("Laptop".to_string()), Value::Integer(2)],
+        vec![Value::String("Mouse".to_string()), Value::Integer(2)],
+        vec![Value::String("Teléfono".to_string()), Value::Integer(4)],
+    ];
+    assert_eq!(rows, expected);
+    Ok(())
+}
+
+#[test]
+fn integration_select_scalar_subqueries() -> Result<(), Tperrors> {
+    let rows = common::run_query(
+        "./tables",
+        "SELECT Id FROM clientes WHERE Edad > (SELECT AVG(Edad) FROM clientes) ORDER BY Id;",
+    )?;
+    assert_eq!(ids_of(&rows), vec!["3", "4", "6", "9"]);
+
+    let rows = common::run_query(
+        "./tables",
+        "SELECT Id, (SELECT COUNT(*) FROM ordenes o WHERE o.id_cliente = c.Id) AS pedidos FROM clientes c WHERE Id IN (1, 7);",
+    )?;
+    let expected = vec![
+        vec![Value::Integer(1), Value::Integer(2)],
+        vec![Value::Integer(7), Value::Integer(0)],
+    ];
+    assert_eq!(rows, expected);
+    Ok(())
+}
+
+#[test]
+fn integration_select_scalar_subquery_with_many_rows_fails() {
+    let result = common::run_query(
+        "./tables",
+        "SELECT Id FROM clientes WHERE Id = (SELECT id_cliente FROM ordenes);",
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn integration_select_over_a_query_result() -> Result<(), Tperrors> {
+    let mut source = ResultSource::new(
+        vec!["producto".to_string(), "total".to_string()],
+        vec![
+            vec![Value::String("Laptop".to_string()), Value::Integer(2)],
+            vec![Value::String("Mouse".to_string()), Value::Integer(1)],
+        ],
+    );
+    let query = SelectQuery {
+        columns: vec!["producto".to_string()],
+        conditions: Some("total > 1".to_string()),
+        sorting: None,
+        options: SelectOptions::default(),
+    };
+
+    let vector_of_lines = query.resolve(&mut source)?;
+    assert_eq!(vector_of_lines, vec![vec!["producto"], vec!["Laptop"]]);
+    Ok(())
+}
+
+#[test]
+fn integration_misspelled_qualified_column_fails() {
+    // a qualified name is never compared as a value, on the ON of a join or on a WHERE
+    let result = common::run_query(
+        "./tables",
+        "SELECT o.producto FROM ordenes o JOIN clientes c ON o.id_cliente = c.Idd;",
+    );
+    assert!(
+        matches!(&result, Err(Tperrors::Column(message)) if message.contains("Invalid column c.Idd"))
+    );
+
+    let result = common::run_query(
+        "./tables",
+        "SELECT Id FROM clientes c WHERE EXISTS (SELECT * FROM ordenes o WHERE o.id_cliente = c.Idd);",
+    );
+    assert!(
+        matches!(&result, Err(Tperrors::Column(message)) if message.contains("Invalid column c.Idd"))
+    );
+
+    // a bare word on the right side of a WHERE is still a value
+    let result = common::run_query("./tables", "SELECT Id FROM clientes WHERE Nombre = Juan;");
+    assert_eq!(ids_of(&result.unwrap()), vec!["1"]);
+}
@@ -0,0 +1,66 @@
+use tp_individual::{
+    consults::{delete::Delete, insert::Insert, update::Update},
+    errors::tperrors::Tperrors,
+    extractors::sqlcommand::SQLCommand,
+    handler_tables::folder_tables::FolderTables,
+};
+
+pub mod common;
+
+#[test]
+fn integration_summary_counts_changed_rows() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("summary_counts_changed_rows");
+    let folder_tables = FolderTables::new(folder.to_str().unwrap())?;
+
+    let mut table = folder_tables.open_table("clientes")?;
+    let summary = Update.execute_update(
+        &mut table,
+        vec!["Profesion".to_string()],
+        vec!["'jubilado'".to_string()],
+        Some("Edad > 40"),
+        None,
+    )?;
+    assert_eq!(summary.command, SQLCommand::Update);
+    assert_eq!(summary.rows, 2);
+    assert_eq!(summary.tag(), "UPDATE 2");
+
+    let mut table = folder_tables.open_table("ordenes")?;
+    let summary = Delete.execute_delete(&mut table, Some("cantidad = 1"), None)?;
+    assert_eq!(summary.tag(), "DELETE 7");
+
+    let mut table = folder_tables.open_table("ordenes")?;
+    let summary = Insert.execute_insert(
+        &mut table,
+        vec!["id".to_string(), "producto".to_string()],
+        vec![
+            vec!["200".to_string(), "Silla".to_string()],
+            vec!["201".to_string(), "Mesa".to_string()],
+        ],
+        None,
+        None,
+    )?;
+    assert_eq!(summary.tag(), "INSERT 2");
+    Ok(())
+}
+
+#[test]
+fn integration_summary_of_a_where_without_matches() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("summary_without_matches");
+    let folder_tables = FolderTables::new(folder.to_str().unwrap())?;
+
+    let mut table = folder_tables.open_table("clientes")?;
+    let summary = Update.execute_update(
+        &mut table,
+        vec!["Edad".to_string()],
+        vec!["1".to_string()],
+        Some("Id = 999"),
+        None,
+    )?;
+    assert_eq!(summary.tag(), "UPDATE 0");
+
+    let mut table = folder_tables.open_table("clientes")?;
+    let summary = Delete.execute_delete(&mut table, Some("Nombre = 'Nadie'"), None)?;
+    assert_eq!(summary.rows, 0);
+    assert!(summary.returning.is_none());
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use std::fs;
+
+use tp_individual::{
+    conditions::value::Value,
+    consults::{delete::Delete, insert::Insert, safe_updates::SafeUpdates, truncate::Truncate},
+    errors::tperrors::Tperrors,
+    extractors::sqlcommand::SQLCommand,
+    handler_tables::folder_tables::FolderTables,
+};
+
+pub mod common;
+
+#[test]
+fn integration_truncate_keeps_only_the_header() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("truncate_header");
+    let path = folder.to_str().unwrap();
+
+    let mut table = FolderTables::new(path)?.open_table("ordenes")?;
+    let summary = Truncate.execute_truncate(&mut table)?;
+    assert_eq!(summary.tag(), "TRUNCATE 10");
+
+    let content = fs::read_to_string(folder.join("ordenes.csv")).unwrap();
+    assert_eq!(content, "id,id_cliente,producto,cantidad");
+
+    // the table can be filled again, without blank lines
+    let mut table = FolderTables::new(path)?.open_table("ordenes")?;
+    Insert.execute_insert(
+        &mut table,
+        Vec::new(),
+        vec![vec![
+            "200".to_string(),
+            "1".to_string(),
+            "Mesa".to_string(),
+            "1".to_string(),
+        ]],
+        None,
+        None,
+    )?;
+    let rows = common::run_query(path, "SELECT id, producto FROM ordenes;")?;
+    assert_eq!(
+        rows,
+        vec![vec![Value::Integer(200), Value::String("Mesa".to_string())]]
+    );
+    Ok(())
+}
+
+#[test]
+fn integration_safe_updates_rejects_delete_without_where() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("safe_updates_reject");
+    let path = folder.to_str().unwrap();
+    let before = fs::read_to_string(folder.join("clientes.csv")).unwrap();
+
+    let result = SafeUpdates::Reject.check(SQLCommand::Delete, "clientes", None, |_| true);
+    assert!(matches!(result, Err(Tperrors::Generic(_))));
+
+    // with a WHERE the query runs
+    SafeUpdates::Reject.check(SQLCommand::Delete, "clientes", Some("Id = 1"), |_| false)?;
+    let mut table = FolderTables::new(path)?.open_table("clientes")?;
+    let summary = Delete.execute_delete(&mut table, Some("Id = 1"), None)?;
+    assert_eq!(summary.tag(), "DELETE 1");
+
+    let after = fs::read_to_string(folder.join("clientes.csv")).unwrap();
+    assert_eq!(before.lines().count() - 1, after.lines().count());
+    Ok(())
+}
@@ -12,8 +12,8 @@ fn integration_update_simple_query() -> Result<(), Tperrors> {
     let update = Update;
     let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
 
-    let columns: Vec<String> = Vec::from(vec!["Nombre".to_string(), "Edad".to_string()]);
-    let values: Vec<String> = Vec::from(vec!["TEST".to_string(), "45".to_string()]);
+    let columns: Vec<String> = vec!["Nombre".to_string(), "Edad".to_string()];
+    let values: Vec<String> = vec!["TEST".to_string(), "45".to_string()];
 
     let condition = Some("Edad =31");
 
@@ -39,8 +39,8 @@ fn integration_update_all_values_query() -> Result<(), Tperrors> {
     let update = Update;
     let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
 
-    let columns: Vec<String> = Vec::from(vec!["Nombre".to_string(), "Edad".to_string()]);
-    let values: Vec<String> = Vec::from(vec!["TEST".to_string(), "45".to_string()]);
+    let columns: Vec<String> = vec!["Nombre".to_string(), "Edad".to_string()];
+    let values: Vec<String> = vec!["TEST".to_string(), "45".to_string()];
 
     let condition = None;
 
@@ -65,3 +65,82 @@ fn integration_update_all_values_query() -> Result<(), Tperrors> {
     }
     Ok(())
 }
+
+#[test]
+fn integration_update_values_are_computed_from_the_original_row() -> Result<(), Tperrors> {
+    let file_name = String::from("query_update_values_from_the_original_row");
+    let update = Update;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    // a swap, an expression and a text with = inside
+    let columns: Vec<String> = vec![
+        "Nombre".to_string(),
+        "Apellido".to_string(),
+        "Edad".to_string(),
+        "Profesion".to_string(),
+    ];
+    let values: Vec<String> = vec![
+        "Apellido".to_string(),
+        "Nombre".to_string(),
+        "Edad + 1".to_string(),
+        "'a=b'".to_string(),
+    ];
+
+    let buf_reader = update.execute_update_mock(&mut table, columns, values, Some("Id = 1"))?;
+    let lines = buf_reader
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+
+    assert_eq!(lines[1], "1,Perez,Juan,33,jperez@gmail.com,a=b");
+    assert_eq!(lines[2], "2,Maria,Gomez,28,mgomez@gmail.com,abogado");
+    Ok(())
+}
+
+#[test]
+fn integration_update_unknown_column_fails() {
+    let file_name = String::from("query_update_unknown_column_fails");
+    let update = Update;
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+
+    let columns: Vec<String> = vec!["Nombre".to_string(), "Sueldo".to_string()];
+    let values: Vec<String> = vec!["'TEST'".to_string(), "100".to_string()];
+
+    let result = update.execute_update_mock(&mut table, columns, values, None);
+    assert!(result.is_err());
+}
+
+#[test]
+fn integration_update_value_that_looks_like_a_column_fails() {
+    let update = Update;
+
+    // a qualified name or a column with other case is a misspelled column, not a text
+    for value in ["c.Edad", "edad"] {
+        let file_name = String::from("query_update_value_that_looks_like_a_column");
+        let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+        let result = update.execute_update_mock(
+            &mut table,
+            vec!["Edad".to_string()],
+            vec![value.to_string()],
+            Some("Id = 2"),
+        );
+        assert!(matches!(result, Err(Tperrors::Column(_))), "{}", value);
+    }
+
+    // a bare word is still a value
+    let file_name = String::from("query_update_bare_word_is_a_value");
+    let mut table = Table::<Cursor<&[u8]>>::mock(file_name, common::csv_data_as_bytes());
+    let buf_reader = update
+        .execute_update_mock(
+            &mut table,
+            vec!["Nombre".to_string()],
+            vec!["Luis".to_string()],
+            Some("Id = 2"),
+        )
+        .unwrap();
+    let lines = buf_reader
+        .lines()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>();
+    assert_eq!(lines[2], "2,Luis,Gomez,28,mgomez@gmail.com,abogado");
+}
@@ -0,0 +1,220 @@
+use std::{fs, rc::Rc};
+
+use tp_individual::{
+    conditions::value::Value,
+    consults::{insert::Insert, select::SelectRunner},
+    errors::tperrors::Tperrors,
+    extractors::extractor::Extractor,
+    handler_tables::{folder_tables::FolderTables, on_conflict::OnConflict},
+};
+
+pub mod common;
+
+fn on_conflict_of(query: &str) -> OnConflict {
+    match Extractor.extract_on_conflict(query) {
+        Ok((_, Some(on_conflict))) => on_conflict,
+        _ => panic!("Expected an ON CONFLICT clause on {}", query),
+    }
+}
+
+fn line(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn integration_upsert_do_nothing_skips_existing_keys() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("upsert_do_nothing");
+    let path = folder.to_str().unwrap();
+    let mut table = FolderTables::new(path)?.open_table("ordenes")?;
+
+    let on_conflict = on_conflict_of("INSERT INTO ordenes VALUES (1) ON CONFLICT (id) DO NOTHING;");
+    let summary = Insert.execute_insert(
+        &mut table,
+        Vec::new(),
+        vec![
+            line(&["101", "9", "Silla", "5"]),
+            line(&["200", "9", "Mesa", "1"]),
+            line(&["200", "9", "Mesa repetida", "1"]),
+        ],
+        Some(&on_conflict),
+        None,
+    )?;
+    assert_eq!(summary.tag(), "INSERT 1");
+
+    let rows = common::run_query(
+        path,
+        "SELECT id, producto FROM ordenes WHERE id IN (101, 200);",
+    )?;
+    let expected = vec![
+        vec![Value::Integer(101), Value::String("Laptop".to_string())],
+        vec![Value::Integer(200), Value::String("Mesa".to_string())],
+    ];
+    assert_eq!(rows, expected);
+    Ok(())
+}
+
+#[test]
+fn integration_upsert_do_update_with_excluded_values() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("upsert_do_update");
+    let path = folder.to_str().unwrap();
+    let mut table = FolderTables::new(path)?.open_table("ordenes")?;
+
+    let on_conflict = on_conflict_of(
+        "INSERT INTO ordenes VALUES (1) ON CONFLICT (id) DO UPDATE SET cantidad = cantidad + EXCLUDED.cantidad;",
+    );
+    let returning = Extractor
+        .extract_returning("INSERT INTO ordenes VALUES (1) RETURNING id, cantidad;")?
+        .1
+        .unwrap();
+    let summary = Insert.execute_insert(
+        &mut table,
+        Vec::new(),
+        vec![
+            line(&["102", "2", "Teléfono", "3"]),
+            line(&["200", "9", "Mesa", "1"]),
+        ],
+        Some(&on_conflict),
+        Some(&returning),
+    )?;
+
+    assert_eq!(summary.tag(), "INSERT 2");
+    let expected = vec![
+        vec![Value::Integer(102), Value::Integer(5)],
+        vec![Value::Integer(200), Value::Integer(1)],
+    ];
+    assert_eq!(summary.returning.unwrap().1, expected);
+
+    let rows = common::run_query(path, "SELECT COUNT(*), SUM(cantidad) FROM ordenes;")?;
+    assert_eq!(rows, vec![vec![Value::Integer(11), Value::Integer(17)]]);
+    Ok(())
+}
+
+#[test]
+fn integration_upsert_with_the_rows_of_a_select() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("upsert_select");
+    let path = folder.to_str().unwrap();
+    let folder_tables = FolderTables::new(path)?;
+    let runner = SelectRunner::new(Rc::new(folder_tables.clone()));
+    let mut table = folder_tables.open_table("clientes")?;
+
+    // every client of ./tables is already there, only the age changes
+    let on_conflict = on_conflict_of(
+        "INSERT INTO clientes VALUES (1) ON CONFLICT (Id) DO UPDATE SET Edad = EXCLUDED.Edad;",
+    );
+    let summary = Insert.execute_insert_select(
+        &mut table,
+        vec!["Id".to_string(), "Edad".to_string()],
+        "SELECT id_cliente, SUM(cantidad) FROM ordenes GROUP BY id_cliente;",
+        &runner,
+        Some(&on_conflict),
+        None,
+    )?;
+    assert_eq!(summary.tag(), "INSERT 6");
+
+    let rows = common::run_query(path, "SELECT Nombre, Edad FROM clientes WHERE Id = 6;")?;
+    let expected = vec![vec![Value::String("Laura".to_string()), Value::Integer(3)]];
+    assert_eq!(rows, expected);
+    let rows = common::run_query(path, "SELECT COUNT(*) FROM clientes;")?;
+    assert_eq!(rows, vec![vec![Value::Integer(11)]]);
+    Ok(())
+}
+
+#[test]
+fn integration_upsert_invalid_queries_fail_without_changes() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("upsert_invalid");
+    let path = folder.to_str().unwrap();
+    let before = fs::read_to_string(folder.join("ordenes.csv")).unwrap();
+
+    // the same key twice can't update the same row twice
+    let mut table = FolderTables::new(path)?.open_table("ordenes")?;
+    let on_conflict = on_conflict_of(
+        "INSERT INTO ordenes VALUES (1) ON CONFLICT (id) DO UPDATE SET cantidad = EXCLUDED.cantidad;",
+    );
+    let result = Insert.execute_insert(
+        &mut table,
+        Vec::new(),
+        vec![line(&["101", "1", "A", "1"]), line(&["101", "1", "B", "2"])],
+        Some(&on_conflict),
+        None,
+    );
+    assert!(result.is_err());
+
+    // the key must be a column of the table
+    let on_conflict =
+        on_conflict_of("INSERT INTO ordenes VALUES (1) ON CONFLICT (Inventada) DO NOTHING;");
+    let result = Insert.execute_insert(
+        &mut table,
+        Vec::new(),
+        vec![line(&["300", "1", "A", "1"])],
+        Some(&on_conflict),
+        None,
+    );
+    assert!(result.is_err());
+
+    assert_eq!(
+        fs::read_to_string(folder.join("ordenes.csv")).unwrap(),
+        before
+    );
+    let leftovers = fs::read_dir(&folder)
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .starts_with("temporal_file")
+        })
+        .count();
+    assert_eq!(leftovers, 0);
+    Ok(())
+}
+
+#[test]
+fn integration_upsert_excluded_in_any_case() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("upsert_excluded_case");
+    let path = folder.to_str().unwrap();
+    let mut table = FolderTables::new(path)?.open_table("ordenes")?;
+
+    let on_conflict = on_conflict_of(
+        "INSERT INTO ordenes VALUES (1) ON CONFLICT (id) DO UPDATE SET cantidad = excluded.cantidad;",
+    );
+    let summary = Insert.execute_insert(
+        &mut table,
+        Vec::new(),
+        vec![line(&["101", "1", "Laptop", "7"])],
+        Some(&on_conflict),
+        None,
+    )?;
+    assert_eq!(summary.tag(), "INSERT 1");
+
+    let rows = common::run_query(path, "SELECT cantidad FROM ordenes WHERE id = 101;")?;
+    assert_eq!(rows, vec![vec![Value::Integer(7)]]);
+    Ok(())
+}
+
+#[test]
+fn integration_upsert_unknown_excluded_column_fails() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("upsert_unknown_excluded");
+    let path = folder.to_str().unwrap();
+    let before = fs::read_to_string(folder.join("ordenes.csv")).unwrap();
+    let mut table = FolderTables::new(path)?.open_table("ordenes")?;
+
+    let on_conflict = on_conflict_of(
+        "INSERT INTO ordenes VALUES (1) ON CONFLICT (id) DO UPDATE SET cantidad = EXCLUDED.cantidadd;",
+    );
+    let result = Insert.execute_insert(
+        &mut table,
+        Vec::new(),
+        vec![line(&["101", "1", "Laptop", "7"])],
+        Some(&on_conflict),
+        None,
+    );
+    assert!(
+        matches!(&result, Err(Tperrors::Column(message)) if message.contains("EXCLUDED.cantidadd"))
+    );
+
+    let after = fs::read_to_string(folder.join("ordenes.csv")).unwrap();
+    assert_eq!(before, after);
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use std::{
+    fs::{self, File},
+    time::{Duration, SystemTime},
+};
+
+use tp_individual::{
+    conditions::value::Value, consults::view::View, errors::tperrors::Tperrors,
+    handler_tables::folder_tables::FolderTables,
+};
+
+pub mod common;
+
+#[test]
+fn integration_view_is_read_like_a_table() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("view_is_read_like_a_table");
+    let path = folder.to_str().unwrap();
+    let mut folder_tables = FolderTables::new(path)?;
+
+    let summary = View.execute_create(
+        &mut folder_tables,
+        "CREATE VIEW con_pedidos AS SELECT DISTINCT id_cliente FROM ordenes WHERE cantidad > 1;",
+    )?;
+    assert_eq!(summary.tag(), "CREATE VIEW");
+    assert!(folder.join("con_pedidos.view.sql").exists());
+
+    let rows = common::run_query(
+        path,
+        "SELECT c.Nombre FROM clientes c JOIN con_pedidos p ON p.id_cliente = c.Id ORDER BY c.Id;",
+    )?;
+    let names = rows
+        .iter()
+        .map(|row| row[0].to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(names, vec!["Maria", "Ana", "Laura"]);
+
+    View.execute_drop(&mut folder_tables, "DROP VIEW con_pedidos;")?;
+    assert!(!folder.join("con_pedidos.view.sql").exists());
+    assert!(common::run_query(path, "SELECT * FROM con_pedidos;").is_err());
+
+    fs::remove_dir_all(folder).unwrap();
+    Ok(())
+}
+
+#[test]
+fn integration_view_invalid_definitions_fail() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("view_invalid_definitions_fail");
+    let path = folder.to_str().unwrap();
+    let mut folder_tables = FolderTables::new(path)?;
+
+    let invalid = [
+        "CREATE VIEW clientes AS SELECT * FROM ordenes;",
+        "CREATE VIEW v AS SELECT Inventado FROM clientes;",
+        "CREATE VIEW v AS SELECT * FROM v;",
+    ];
+    for query in invalid {
+        assert!(View.execute_create(&mut folder_tables, query).is_err());
+    }
+    assert!(View
+        .execute_drop(&mut folder_tables, "DROP VIEW clientes;")
+        .is_err());
+    View.execute_drop(&mut folder_tables, "DROP VIEW IF EXISTS v;")?;
+
+    let views = fs::read_dir(&folder)
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .ends_with(".view.sql")
+        })
+        .count();
+    assert_eq!(views, 0);
+
+    fs::remove_dir_all(folder).unwrap();
+    Ok(())
+}
+
+#[test]
+fn integration_view_that_reads_itself_fails() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("view_that_reads_itself_fails");
+    let path = folder.to_str().unwrap();
+    // written by hand, CREATE VIEW doesn't allow them
+    fs::write(folder.join("a.view.sql"), "SELECT * FROM a;\n").unwrap();
+    fs::write(folder.join("b.view.sql"), "SELECT * FROM c;\n").unwrap();
+    fs::write(folder.join("c.view.sql"), "SELECT Id FROM b;\n").unwrap();
+
+    let queries = [
+        ("SELECT * FROM a;", "view a reads itself"),
+        ("SELECT * FROM b;", "view b reads itself"),
+        (
+            "SELECT Nombre FROM clientes WHERE Id IN (SELECT Id FROM c);",
+            "view c reads itself",
+        ),
+    ];
+    for (query, expected) in queries {
+        let result = common::run_query(path, query);
+        assert!(
+            matches!(&result, Err(Tperrors::Table(message)) if message == expected),
+            "{} returned {:?}",
+            query,
+            result
+        );
+    }
+
+    fs::remove_dir_all(folder).unwrap();
+    Ok(())
+}
+
+#[test]
+fn integration_table_with_a_hyphen_is_read() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("table_with_a_hyphen_is_read");
+    let path = folder.to_str().unwrap();
+    fs::copy(folder.join("ordenes.csv"), folder.join("ordenes-2024.csv")).unwrap();
+
+    let rows = common::run_query(path, "SELECT producto FROM ordenes-2024 WHERE id = 101;")?;
+    assert_eq!(rows, vec![vec![Value::String("Laptop".to_string())]]);
+
+    // only the names of new tables and views are checked
+    let mut folder_tables = FolderTables::new(path)?;
+    let result = folder_tables.create_view("ventas-2024", "SELECT * FROM ordenes-2024;");
+    assert!(matches!(&result, Err(Tperrors::Syntax(message)) if message.contains("Invalid name")));
+    assert!(!folder.join("ventas-2024.view.sql").exists());
+
+    fs::remove_dir_all(folder).unwrap();
+    Ok(())
+}
+
+#[test]
+fn integration_materialized_view_refresh_and_staleness() -> Result<(), Tperrors> {
+    let folder = common::copy_of_tables("materialized_view_refresh_and_staleness");
+    let path = folder.to_str().unwrap();
+    let mut folder_tables = FolderTables::new(path)?;
+
+    let summary = View.execute_create(
+        &mut folder_tables,
+        "CREATE MATERIALIZED VIEW totales AS SELECT id_cliente, SUM(cantidad) AS total FROM ordenes GROUP BY id_cliente;",
+    )?;
+    assert_eq!(summary.tag(), "SELECT 6");
+    assert_eq!(
+        fs::read_to_string(folder.join("totales.csv"))
+            .unwrap()
+            .lines()
+            .next(),
+        Some("id_cliente,total")
+    );
+    assert!(folder_tables.stale_sources("totales")?.is_empty());
+
+    let query = "SELECT total FROM totales WHERE id_cliente = 2;";
+    assert_eq!(
+        common::run_query(path, query)?,
+        vec![vec![Value::Integer(2)]]
+    );
+
+    // the orders change after the view was written
+    fs::write(
+        folder.join("ordenes.csv"),
+        "id,id_cliente,producto,cantidad\n101,2,Mouse,5\n",
+    )
+    .unwrap();
+    File::options()
+        .write(true)
+        .open(folder.join("ordenes.csv"))
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(folder_tables.stale_sources("totales")?, vec!["ordenes"]);
+    assert_eq!(
+        common::run_query(path, query)?,
+        vec![vec![Value::Integer(2)]]
+    );
+
+    let summary = View.execute_refresh(&mut folder_tables, "REFRESH MATERIALIZED VIEW totales;")?;
+    assert_eq!(summary.tag(), "SELECT 1");
+    assert_eq!(
+        common::run_query(path, query)?,
+        vec![vec![Value::Integer(5)]]
+    );
+
+    assert!(View
+        .execute_drop(&mut folder_tables, "DROP VIEW totales;")
+        .is_err());
+    let summary = View.execute_drop(&mut folder_tables, "DROP MATERIALIZED VIEW totales;")?;
+    assert_eq!(summary.tag(), "DROP MATERIALIZED VIEW");
+    assert!(!folder.join("totales.csv").exists());
+    assert!(!folder.join("totales.matview.sql").exists());
+
+    fs::remove_dir_all(folder).unwrap();
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use tp_individual::{conditions::value::Value, errors::tperrors::Tperrors};
+
+pub mod common;
+
+fn integers(values: &[i64]) -> Vec<Value> {
+    values.iter().map(|v| Value::Integer(*v)).collect()
+}
+
+#[test]
+fn integration_select_ranks_the_orders_of_each_client() -> Result<(), Tperrors> {
+    let rows = common::run_query(
+        "./tables",
+        "SELECT id, ROW_NUMBER() OVER (PARTITION BY id_cliente ORDER BY cantidad DESC, id) AS n FROM ordenes WHERE id_cliente IN (4, 6) ORDER BY id;",
+    )?;
+
+    let expected = vec![
+        integers(&[105, 1]),
+        integers(&[107, 2]),
+        integers(&[108, 2]),
+        integers(&[110, 1]),
+    ];
+    assert_eq!(rows, expected);
+    Ok(())
+}
+
+#[test]
+fn integration_select_rank_over_groups() -> Result<(), Tperrors> {
+    let rows = common::run_query(
+        "./tables",
+        "SELECT id_cliente, RANK() OVER (ORDER BY SUM(cantidad) DESC) AS puesto, DENSE_RANK() OVER (ORDER BY SUM(cantidad) DESC) AS denso FROM ordenes GROUP BY id_cliente ORDER BY id_cliente;",
+    )?;
+
+    let expected = vec![
+        integers(&[1, 3, 2]),
+        integers(&[2, 3, 2]),
+        integers(&[3, 6, 3]),
+        integers(&[4, 1, 1]),
+        integers(&[5, 3, 2]),
+        integers(&[6, 1, 1]),
+    ];
+    assert_eq!(rows, expected);
+    Ok(())
+}
+
+#[test]
+fn integration_select_running_totals_and_lag() -> Result<(), Tperrors> {
+    let rows = common::run_query(
+        "./tables",
+        "SELECT id, SUM(cantidad) OVER (ORDER BY id ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS total, LAG(id) OVER (ORDER BY id) AS anterior FROM ordenes ORDER BY id LIMIT 3;",
+    )?;
+
+    let expected = vec![
+        vec![Value::Integer(101), Value::Integer(1), Value::Null],
+        integers(&[102, 3, 101]),
+        integers(&[103, 4, 102]),
+    ];
+    assert_eq!(rows, expected);
+    Ok(())
+}
+
+#[test]
+fn integration_select_window_function_on_where_fails() {
+    let result = common::run_query(
+        "./tables",
+        "SELECT id FROM ordenes WHERE ROW_NUMBER() OVER (ORDER BY id) = 1;",
+    );
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,77 @@
+use tp_individual::errors::tperrors::Tperrors;
+
+pub mod common;
+
+#[test]
+fn integration_with_tables_read_by_the_query() -> Result<(), Tperrors> {
+    let (header, rows) = common::run_query_with_header(
+        "./tables",
+        "WITH totales(cliente, total) AS (SELECT id_cliente, SUM(cantidad) FROM ordenes GROUP BY id_cliente), grandes AS (SELECT * FROM totales WHERE total > 2) SELECT c.Nombre, g.total FROM grandes g JOIN clientes c ON c.Id = g.cliente ORDER BY c.Nombre;",
+    )?;
+
+    assert_eq!(header, vec!["Nombre", "total"]);
+    assert_eq!(
+        common::as_text(&rows),
+        vec![vec!["Ana", "3"], vec!["Laura", "3"]]
+    );
+    Ok(())
+}
+
+#[test]
+fn integration_with_inside_a_subquery() -> Result<(), Tperrors> {
+    let (_, rows) = common::run_query_with_header(
+        "./tables",
+        "SELECT Id FROM clientes WHERE Id IN (WITH t AS (SELECT id_cliente FROM ordenes WHERE cantidad > 1) SELECT id_cliente FROM t);",
+    )?;
+
+    assert_eq!(
+        common::as_text(&rows),
+        vec![vec!["2"], vec!["4"], vec!["6"]]
+    );
+    Ok(())
+}
+
+#[test]
+fn integration_with_recursive_follows_a_chain() -> Result<(), Tperrors> {
+    let (header, rows) = common::run_query_with_header(
+        "./tests/data/referidos",
+        "WITH RECURSIVE cadena(id, nivel) AS (SELECT id, 1 FROM referidos WHERE referido_por IS NULL UNION ALL SELECT r.id, c.nivel + 1 FROM referidos r JOIN cadena c ON r.referido_por = c.id) SELECT id, nivel FROM cadena ORDER BY nivel, id;",
+    )?;
+
+    assert_eq!(header, vec!["id", "nivel"]);
+    let expected = vec![
+        vec!["1", "1"],
+        vec!["6", "1"],
+        vec!["2", "2"],
+        vec!["7", "2"],
+        vec!["3", "3"],
+        vec!["4", "3"],
+        vec!["5", "4"],
+    ];
+    assert_eq!(common::as_text(&rows), expected);
+    Ok(())
+}
+
+#[test]
+fn integration_with_recursive_union_stops_on_cycles() -> Result<(), Tperrors> {
+    let (_, rows) = common::run_query_with_header(
+        "./tables",
+        "WITH RECURSIVE n(x) AS (SELECT 1 FROM clientes WHERE Id = 1 UNION SELECT (x + 1) % 3 FROM n) SELECT x FROM n;",
+    )?;
+
+    assert_eq!(
+        common::as_text(&rows),
+        vec![vec!["1"], vec!["2"], vec!["0"]]
+    );
+    Ok(())
+}
+
+#[test]
+fn integration_with_recursive_without_end_fails() {
+    let result = common::run_query_with_header(
+        "./tables",
+        "WITH RECURSIVE n(x) AS (SELECT 1 FROM clientes WHERE Id = 1 UNION ALL SELECT x + 1 FROM n) SELECT x FROM n;",
+    );
+
+    assert!(result.is_err());
+}